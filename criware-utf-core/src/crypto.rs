@@ -0,0 +1,83 @@
+/*!
+The encryption CRIWARE uses for the tables in packets
+
+[`Packet`](crate::Packet) encrypts and decrypts its table automatically. These
+functions work on any buffer instead, like a table that was extracted from a
+packet by another tool.
+
+Encryption XORs each byte with a mask, so encrypting and decrypting are the
+same operation. These use the parameters official tools use (see
+[`CriXorCipher::STANDARD`](crate::CriXorCipher::STANDARD)).
+
+# Example
+```
+# use criware_utf::crypto;
+let mut data = *b"@UTF\0\0\0\x20";
+crypto::encrypt_in_place(&mut data);
+assert!(crypto::can_decrypt(&data));
+crypto::decrypt_in_place(&mut data);
+assert_eq!(&data, b"@UTF\0\0\0\x20");
+```
+*/
+
+/// The bytes data is XORed with, which repeat every 64 bytes
+const DECRYPTION_MASK: [u8; 64] = [
+    95u8, 203u8, 167u8, 179u8, 175u8, 91u8, 119u8, 195u8, 255u8, 235u8, 71u8, 211u8, 79u8, 123u8,
+    23u8, 227u8, 159u8, 11u8, 231u8, 243u8, 239u8, 155u8, 183u8, 3u8, 63u8, 43u8, 135u8, 19u8,
+    143u8, 187u8, 87u8, 35u8, 223u8, 75u8, 39u8, 51u8, 47u8, 219u8, 247u8, 67u8, 127u8, 107u8,
+    199u8, 83u8, 207u8, 251u8, 151u8, 99u8, 31u8, 139u8, 103u8, 115u8, 111u8, 27u8, 55u8, 131u8,
+    191u8, 171u8, 7u8, 147u8, 15u8, 59u8, 215u8, 163u8,
+];
+
+/// Returns `true` if the data starts with an encrypted `@UTF` magic, and can
+/// be decrypted with [`decrypt`]
+///
+pub fn can_decrypt(data: &[u8]) -> bool {
+    data.len() >= 4
+        && data[0..4]
+            .iter()
+            .zip(&DECRYPTION_MASK)
+            .map(|(a, b)| a ^ b)
+            .eq(*b"@UTF")
+}
+
+/// Decrypts `src` into `dst`
+///
+/// # Panics
+///
+/// Panics if the buffers aren't the same length.
+///
+pub fn decrypt(src: &[u8], dst: &mut [u8]) {
+    assert_eq!(src.len(), dst.len(), "buffers must be the same length");
+    for (src, dst) in src.chunks(64).zip(dst.chunks_mut(64)) {
+        for ((dst, src), mask) in dst.iter_mut().zip(src).zip(&DECRYPTION_MASK) {
+            *dst = src ^ mask;
+        }
+    }
+}
+
+/// Decrypts the data in place
+///
+pub fn decrypt_in_place(data: &mut [u8]) {
+    for chunk in data.chunks_mut(64) {
+        for (byte, mask) in chunk.iter_mut().zip(&DECRYPTION_MASK) {
+            *byte ^= mask;
+        }
+    }
+}
+
+/// Encrypts `src` into `dst`
+///
+/// # Panics
+///
+/// Panics if the buffers aren't the same length.
+///
+pub fn encrypt(src: &[u8], dst: &mut [u8]) {
+    decrypt(src, dst);
+}
+
+/// Encrypts the data in place
+///
+pub fn encrypt_in_place(data: &mut [u8]) {
+    decrypt_in_place(data);
+}
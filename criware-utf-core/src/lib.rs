@@ -5,6 +5,7 @@
 
 use thiserror::Error;
 
+pub mod crypto;
 mod packet;
 mod reader;
 mod schema;
@@ -12,12 +13,41 @@ mod table;
 mod value;
 mod writer;
 
-pub use crate::packet::Packet;
-pub use crate::reader::Reader;
-pub use crate::schema::{ColumnStorageFormat, Schema, SchemaColumn};
-pub use crate::table::Table;
-pub use crate::value::{Primitive, Value, ValueKind, utf_size_of};
-pub use crate::writer::{WriteContext, Writer};
+pub use crate::packet::{CriCipher, CriXorCipher, Packet, PacketHeader, PacketPadding, RawPacket};
+pub use crate::reader::{
+    ColumnLocation, MatchReport, Reader, ReaderOptions, SeekSource, Span, SpanKind, SpanMap,
+    StringStats, TableSection, TableSource, TableStream, find_utf_tables,
+    find_utf_tables_with_options,
+};
+pub use crate::schema::{
+    AsciiTableOptions, ColumnChange, ColumnStorageFormat, Schema, SchemaBuilder, SchemaColumn,
+    SchemaDiff, SchemaMatch, SchemaMismatch, SchemaWarning, SchemaWithConstants, TableColumn,
+    TableSchema, TableStats,
+};
+#[cfg(feature = "futures-io")]
+pub use crate::table::AsyncTable;
+pub use crate::table::{DescribedTable, PrefixedTable, Table, check_identical_writes};
+#[cfg(feature = "bitflags")]
+#[doc(hidden)]
+pub use crate::value::__Flags;
+#[cfg(feature = "lzma-rs")]
+pub use crate::value::Lzma;
+#[cfg(feature = "unstable")]
+pub use crate::value::sealed::Primitive as PrimitiveImpl;
+pub use crate::value::{
+    AnyValue, BlobHandle, Codec, CompressedBlob, CpkOffset, CpkSize, CriDateTime,
+    DEFAULT_MAX_DECOMPRESSED_LEN, Encoding, Endianness, FixedPoint, FixedStr, LazySubTable,
+    LenientBool, PackedUtfVersion, Primitive, RawString, UtfVersion, Value, ValueKind, WideString,
+    utf_kind_of, utf_size_of, utf_size_of_kind,
+};
+#[cfg(feature = "flate2")]
+pub use crate::value::{Deflate, Gzip, Zlib};
+#[allow(deprecated)]
+pub use crate::writer::WriteContext;
+pub use crate::writer::{
+    ColumnPromotion, EmptyStrings, OriginalLayout, RawWriter, RoundTripContext, StringOrder,
+    TableLayout, TableNamePlacement, Writer, WriterBuilder, WriterOptions, WriterProfile,
+};
 
 /// Error returned when reading or writing a table fails
 ///
@@ -26,11 +56,17 @@ pub enum Error {
     ///
     /// If a data blob is not the correct size
     ///
-    /// This is only used in the implementation of [`Value`] for `[u8; N]`
+    /// This is only used in the implementations of [`Value`] for `[u8; N]` and
+    /// `uuid::Uuid`
     ///
     #[error("wrong size")]
     BlobWrongSize,
     ///
+    /// If a value is given for a column that a table doesn't have
+    ///
+    #[error("column not found: \"{0}\"")]
+    ColumnNotFound(String),
+    ///
     /// If a string or data blob is unable to be read from a table
     ///
     /// This means the table is malformed
@@ -50,6 +86,24 @@ pub enum Error {
     #[error("reached end of file early (at {0})")]
     EOF(String),
     ///
+    /// If a value read from (or written to) a column can't be converted (see
+    /// [`Error::ValueConversion`])
+    ///
+    /// The first value is the name of the column, the second is the row the
+    /// value is in (or [`None`] if the column is constant), and the third is
+    /// the conversion error.
+    ///
+    #[error("in column \"{0}\"{row}: {2}", row = .1.map(|row| format!(" (row {row})")).unwrap_or_default())]
+    InColumn(String, Option<u32>, Box<Error>),
+    ///
+    /// If a boolean stored in a table is something other than 0 or 1
+    ///
+    /// This is only used in the implementation of [`Value`] for [`bool`]. Use
+    /// [`LenientBool`] to read any nonzero value as `true` instead.
+    ///
+    #[error("invalid boolean: {0} (expected 0 or 1)")]
+    InvalidBool(u8),
+    ///
     /// If the flag associated with the column's storage method is invalid
     /// (table is malformed)
     ///
@@ -62,6 +116,11 @@ pub enum Error {
     #[error("invalid column type flag: 0x{0:02}")]
     InvalidColumnType(u8),
     ///
+    /// If a string can't be parsed as a [`UtfVersion`]
+    ///
+    #[error("invalid version: \"{0}\"")]
+    InvalidVersion(String),
+    ///
     /// If an I/O error happens
     ///
     /// This does not include end-of-file errors. There's a variant for that
@@ -70,16 +129,100 @@ pub enum Error {
     #[error("i/o error: {0}")]
     IOError(std::io::Error),
     ///
+    /// If part of a table is larger than the limit set in [`ReaderOptions`]
+    ///
+    /// The first value is what exceeded its limit, the second is its size,
+    /// and the third is the limit.
+    ///
+    #[error("{0} exceeds limit ({1} > {2})")]
+    LimitExceeded(&'static str, u64, u64),
+    ///
     /// Generic error for any malformed data in the header of a table
     ///
     #[error("malformed header")]
     MalformedHeader,
     ///
+    /// If a row is missing the value of a column when writing
+    ///
+    #[error("missing value for column \"{0}\" in row {1}")]
+    MissingValue(String, u32),
+    ///
+    /// If a path (or OS string) being written isn't valid UTF-8
+    ///
+    /// The value is a lossy copy of the path. This is only used in the
+    /// implementations of [`Value`] for `PathBuf` and `OsString`.
+    ///
+    #[error("path is not valid utf8: \"{0}\"")]
+    NonUtf8Path(String),
+    ///
+    /// If a packet's header declares a larger table than the stream has
+    /// left
+    ///
+    /// The first value is the declared size of the table, and the second is
+    /// the number of bytes that were left.
+    ///
+    #[error("packet declares a {0}-byte table, but only {1} bytes are left")]
+    PacketTruncated(u64, u64),
+    ///
+    /// If a packet written with verification enabled doesn't read back the
+    /// same (see [`Packet::enable_write_verification`])
+    ///
+    /// The value describes what went wrong.
+    ///
+    #[error("packet failed verification: {0}")]
+    PacketVerificationFailed(String),
+    ///
+    /// If the values written to a row don't match the table's rowed columns
+    /// (see [`Writer::begin_row`])
+    ///
+    /// The first value is the index of the row, and the second describes
+    /// what's wrong.
+    ///
+    #[error("row {0} doesn't match the rowed columns: {1}")]
+    RowMismatch(u32, String),
+    ///
+    /// If a row is requested that doesn't exist
+    ///
+    /// The first value is the requested row, and the second is the number of
+    /// rows in the table.
+    ///
+    #[error("row {0} out of range (table has {1} rows)")]
+    RowOutOfRange(u32, u32),
+    ///
+    /// If a string being written contains a NUL character
+    ///
+    /// Strings are terminated by a NUL byte, so anything after it would be
+    /// cut off when the table is read.
+    ///
+    #[error("string {0:?} contains a nul character")]
+    StringContainsNul(String),
+    ///
     /// If a string stored in a table is unable to be decoded
     ///
     #[error("error when decoding utf8 string: {0}")]
     StringMalformed(std::str::Utf8Error),
     ///
+    /// If a string is too long for the buffer it has to fit in (see
+    /// [`FixedStr`])
+    ///
+    /// The first value is the length of the string once it's encoded (in
+    /// bytes), and the second is the most it can be.
+    ///
+    #[error("string is {0} bytes long (at most {1} allowed)")]
+    StringTooLong(usize, usize),
+    ///
+    /// If a string stored in a table is unable to be decoded with a legacy
+    /// encoding (like Shift-JIS)
+    ///
+    #[error("error when decoding {0} string")]
+    StringUndecodable(&'static str),
+    ///
+    /// If a string contains characters that its table's encoding can't
+    /// represent
+    ///
+    #[error("string \"{0}\" cannot be encoded as {1}")]
+    StringUnencodable(String, &'static str),
+    ///
     /// Occurs when writing
     ///
     /// For a rowed optional value, the value in each row must ALL either be
@@ -89,11 +232,71 @@ pub enum Error {
     #[error("optional column conflict: \"{0}\" (values must be all Some or all None)")]
     OptionalColumnConflict(&'static str),
     ///
+    /// If a table being written doesn't fit in the 32-bit offsets (or 16-bit
+    /// counts) of its header
+    ///
+    /// The value is what's too large.
+    ///
+    #[error("{0} too large for a UTF table")]
+    TableTooLarge(&'static str),
+    ///
+    /// If the last string in the string data section is missing its trailing
+    /// NUL (only returned in strict mode)
+    ///
+    /// The value is the offset of the string, relative to the start of the
+    /// table.
+    ///
+    #[error("unterminated string at offset 0x{0:x}")]
+    UnterminatedString(u64),
+    ///
+    /// If a value read for a set of flags (see [`impl_value_bitflags`]) has
+    /// bits that don't belong to any flag
+    ///
+    /// The first value is the name of the type, and the second is the unknown
+    /// bits.
+    ///
+    #[error("unknown bits for flags {0}: 0x{1:x}")]
+    UnknownFlagBits(&'static str, u64),
+    ///
+    /// If a value read for an enum (deriving `UtfEnum`) isn't the
+    /// discriminant of any of its variants
+    ///
+    /// The first value is the name of the enum, and the second is the value.
+    ///
+    #[error("unknown value for enum {0}: {1}")]
+    UnknownEnumValue(&'static str, i128),
+    ///
+    /// If a [`BlobHandle`] is written to a table
+    ///
+    /// Handles don't hold the contents of their blob. Load the blob and write
+    /// it as a `Vec<u8>` instead.
+    ///
+    #[error("blob handles cannot be written")]
+    UnloadedBlob,
+    ///
+    /// If a table is written with options that its implementation of
+    /// [`Table`] doesn't support (see [`Table::write_with_options`])
+    ///
+    #[error("table cannot be written with these writer options")]
+    UnsupportedWriterOptions,
+    ///
     /// If a conversion from a primitive to another value (or vice versa) fails
     ///
+    /// When a value is read from or written to a table, this is wrapped in
+    /// [`Error::InColumn`], which says which column the value is in.
+    ///
     #[error("failed to convert {0} to {1}: {2}")]
     ValueConversion(&'static str, &'static str, Box<dyn std::error::Error>),
     ///
+    /// If two writes that should be identical aren't (see
+    /// [`check_identical_writes`])
+    ///
+    /// The first value is the table that was written differently, and the
+    /// second is the offset of the first byte that differs.
+    ///
+    #[error("{0} table was written differently (bytes differ at offset 0x{1:x})")]
+    WritesDiffer(&'static str, u64),
+    ///
     /// If the name of a column is not what was expected
     ///
     /// This indicates the table doesn't follow the expected schema. The table
@@ -129,18 +332,24 @@ pub enum Error {
 ///
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// A callback that receives the raw bytes of a single value, and may modify
+/// them in place.
+///
+/// When reading, it's called before the bytes are decoded. When writing, it's
+/// called after the value is encoded, but before it's stored.
+///
+pub type CellHook = dyn Fn(&mut [u8]);
+
 pub(crate) trait IOErrorHelper<T> {
     fn io(self, message: &str) -> Result<T>;
 }
-impl IOErrorHelper<()> for std::io::Result<()> {
-    fn io(self, message: &str) -> Result<()> {
+impl<T> IOErrorHelper<T> for std::io::Result<T> {
+    fn io(self, message: &str) -> Result<T> {
         match self {
             Ok(value) => Ok(value),
             Err(error) => match error.kind() {
-                std::io::ErrorKind::UnexpectedEof => {
-                    return Err(Error::EOF(message.to_owned()));
-                }
-                _ => return Err(Error::IOError(error)),
+                std::io::ErrorKind::UnexpectedEof => Err(Error::EOF(message.to_owned())),
+                _ => Err(Error::IOError(error)),
             },
         }
     }
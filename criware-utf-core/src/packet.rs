@@ -1,38 +1,536 @@
 use std::{
-    alloc::{self, Layout},
-    io::{Cursor, Read, Write},
+    io::{Read, Seek, SeekFrom, Write},
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
-use crate::{Error, IOErrorHelper, Result, Table};
+use crate::{
+    Endianness, Error, IOErrorHelper, PrefixedTable, Reader, ReaderOptions, Result, Table, crypto,
+};
+
+mod cipher;
+
+pub use cipher::{CriCipher, CriXorCipher};
+
+/**
+How the table in a packet is padded when it's written
+
+Some containers expect each table to take up a multiple of some block size
+(like 16 or 2048 bytes). Padding is made of zeros, is written before the table
+is encrypted, and is counted in the table size the packet header declares.
+
+# Example
+```
+# use criware_utf_core::PacketPadding;
+assert_eq!(PacketPadding::None.padding_for(1000), 0);
+assert_eq!(PacketPadding::Bytes(24).padding_for(1000), 24);
+assert_eq!(PacketPadding::Align(16).padding_for(1000), 8);
+assert_eq!(PacketPadding::Align(2048).padding_for(2048), 0);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PacketPadding {
+    /// The table isn't padded
+    #[default]
+    None,
+    /// A fixed number of bytes is added after the table
+    Bytes(u64),
+    /// Bytes are added after the table until its size is a multiple of the
+    /// given alignment (with 0 and 1 meaning no padding)
+    Align(u64),
+}
+
+impl PacketPadding {
+    /// Returns the number of bytes added after a table of the given size
+    ///
+    pub fn padding_for(&self, table_size: u64) -> u64 {
+        match *self {
+            PacketPadding::None => 0,
+            PacketPadding::Bytes(count) => count,
+            PacketPadding::Align(align) if align <= 1 => 0,
+            PacketPadding::Align(align) => table_size.next_multiple_of(align) - table_size,
+        }
+    }
+}
+
+/// Writes a table as a packet with the given prefix, header flags,
+/// encryption state, and padding
+///
+/// If a cipher isn't given, the table is encrypted with the standard cipher
+/// (see [`CriXorCipher::STANDARD`]).
+pub(crate) fn write_packet<T: Table>(
+    table: &T,
+    writer: &mut dyn Write,
+    prefix: &[u8; 4],
+    header_flags: u32,
+    encrypted: bool,
+    cipher: Option<&dyn CriCipher>,
+    padding: PacketPadding,
+) -> Result<()> {
+    let mut table_buffer = Vec::new();
+    table.write(&mut table_buffer)?;
+    let padding = padding.padding_for(table_buffer.len() as u64);
+    table_buffer.resize(table_buffer.len() + padding as usize, 0);
+    if encrypted {
+        cipher
+            .unwrap_or(&CriXorCipher::STANDARD)
+            .encrypt(table_buffer.as_mut_slice());
+    }
+    writer.write_all(prefix).io("UTF packet header")?;
+    writer
+        .write_all(&u32::to_le_bytes(header_flags))
+        .io("UTF packet header")?;
+    writer
+        .write_all(&u64::to_le_bytes(table_buffer.len() as u64))
+        .io("UTF packet header")?;
+    writer
+        .write_all(table_buffer.as_slice())
+        .io("UTF packet table")?;
+    Ok(())
+}
+
+/// Size of the chunks [`EncryptingWriter`] encrypts at once (a multiple of the
+/// 64 bytes the standard cipher repeats after)
+const ENCRYPTION_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A stream that counts the bytes written to it, and encrypts them with the
+/// standard cipher in fixed-size chunks before passing them on
+struct EncryptingWriter<'a> {
+    inner: &'a mut dyn Write,
+    encrypted: bool,
+    buffer: Vec<u8>,
+    written: u64,
+}
+
+impl<'a> EncryptingWriter<'a> {
+    fn new(inner: &'a mut dyn Write, encrypted: bool) -> Self {
+        EncryptingWriter {
+            inner,
+            encrypted,
+            buffer: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Encrypts and writes the buffered bytes
+    fn flush_buffer(&mut self) -> std::io::Result<()> {
+        crypto::encrypt_in_place(&mut self.buffer);
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl Write for EncryptingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.encrypted {
+            let length = self.inner.write(buf)?;
+            self.written += length as u64;
+            return Ok(length);
+        }
+        let length = buf.len().min(ENCRYPTION_CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..length]);
+        self.written += length as u64;
+        if self.buffer.len() == ENCRYPTION_CHUNK_SIZE {
+            self.flush_buffer()?;
+        }
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.flush_buffer()?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Writes a table as a packet with the given prefix, header flags,
+/// encryption state, and padding, without buffering the finished table
+///
+/// The table size is written once the table has been, by seeking back to the
+/// header. Tables are always encrypted with the standard cipher.
+pub(crate) fn write_packet_seek<T: Table, W: Write + Seek>(
+    table: &T,
+    writer: &mut W,
+    prefix: &[u8; 4],
+    header_flags: u32,
+    encrypted: bool,
+    padding: PacketPadding,
+) -> Result<()> {
+    let start = writer.stream_position().io("UTF packet header")?;
+    writer.write_all(prefix).io("UTF packet header")?;
+    writer
+        .write_all(&u32::to_le_bytes(header_flags))
+        .io("UTF packet header")?;
+    writer.write_all(&[0u8; 8]).io("UTF packet header")?;
+    let mut table_writer = EncryptingWriter::new(writer, encrypted);
+    table.write(&mut table_writer)?;
+    let padding = padding.padding_for(table_writer.written);
+    std::io::copy(&mut std::io::repeat(0).take(padding), &mut table_writer)
+        .io("UTF packet table")?;
+    table_writer.flush().io("UTF packet table")?;
+    let table_size = table_writer.written;
+    writer
+        .seek(SeekFrom::Start(start + 8))
+        .io("UTF packet header")?;
+    writer
+        .write_all(&u64::to_le_bytes(table_size))
+        .io("UTF packet header")?;
+    writer
+        .seek(SeekFrom::Start(
+            start + PacketHeader::SIZE as u64 + table_size,
+        ))
+        .io("UTF packet table")?;
+    Ok(())
+}
+
+/// Reads back a packet that was just written into memory, and checks that
+/// its table is written to the same bytes again
+///
+/// The table is read back with its layout recorded, so tables that keep a
+/// non-canonical layout (see [`ReaderOptions::record_layout`]) are written
+/// back the way they were written.
+fn verify_packet<T: Table>(
+    packet: &[u8],
+    prefix: &[u8; 4],
+    cipher: Option<&dyn CriCipher>,
+) -> Result<()> {
+    let fail = |message: String| Err(Error::PacketVerificationFailed(message));
+    let options = ReaderOptions {
+        record_layout: true,
+        ..T::reader_options()
+    };
+    let data = match read_packet_data(&mut &packet[..], Some(prefix), cipher, &options) {
+        Ok(data) => data,
+        Err(error) => return fail(format!("packet couldn't be read back: {error}")),
+    };
+    let written =
+        &data.table[..data.table.len() - table_padding(&data.table, options.endianness) as usize];
+    let rewritten = match Reader::from_bytes_with_options(written, options)
+        .and_then(T::read_from_reader)
+        .and_then(|table| table.write_to_vec())
+    {
+        Ok(rewritten) => rewritten,
+        Err(error) => return fail(format!("table couldn't be read back: {error}")),
+    };
+    if rewritten != written {
+        let offset = written
+            .iter()
+            .zip(&rewritten)
+            .position(|(a, b)| a != b)
+            .unwrap_or(written.len().min(rewritten.len()));
+        return fail(format!(
+            "table was read back differently: bytes differ at offset {offset:#x} \
+             ({} bytes written, {} bytes after reading back)",
+            written.len(),
+            rewritten.len()
+        ));
+    }
+    Ok(())
+}
+
+/**
+The 16 bytes at the start of a UTF table packet
+
+Reading just the header is enough to skip over a packet, or to find out which
+table it holds without reading it.
+
+# Example
+```no_run
+# use std::{fs::File, io::{Seek, SeekFrom}};
+# use criware_utf_core::PacketHeader;
+let mut file = File::open("packets.bin")?;
+let header = PacketHeader::read(&mut file)?;
+println!("{:?} packet, {} bytes", header.prefix, header.table_size);
+file.seek(SeekFrom::Current(header.table_size as i64))?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    /// The 4-byte prefix (like `b"CPK "`)
+    pub prefix: [u8; 4],
+    /// The value after the prefix (see [`Packet::header_flags`])
+    pub header_flags: u32,
+    /// The size (in bytes) of the table that follows, as declared by the
+    /// packet
+    pub table_size: u64,
+}
+
+impl PacketHeader {
+    /// The size (in bytes) of a packet header
+    ///
+    pub const SIZE: usize = 16;
+
+    /// Reads a packet header from the given stream
+    ///
+    pub fn read(reader: &mut dyn Read) -> Result<Self> {
+        let mut header = [0u8; Self::SIZE];
+        reader.read_exact(&mut header).io("UTF packet header")?;
+        Ok(Self::from_bytes(&header))
+    }
 
-mod cri_encryption;
+    /// Parses a packet header
+    ///
+    pub fn from_bytes(header: &[u8; Self::SIZE]) -> Self {
+        PacketHeader {
+            prefix: header[0..4].try_into().unwrap(),
+            header_flags: u32::from_le_bytes(header[4..8].try_into().unwrap()),
+            table_size: u64::from_le_bytes(header[8..16].try_into().unwrap()),
+        }
+    }
+
+    /// Returns the size (in bytes) of the whole packet, including the header
+    ///
+    pub fn packet_size(&self) -> u64 {
+        Self::SIZE as u64 + self.table_size
+    }
+}
 
-fn aligned_vec(initial_size: usize, size: usize) -> Vec<u8> {
-    let minimum_size = size.div_ceil(64) << 6;
-    let layout = Layout::from_size_align(minimum_size, 64).expect("Invalid layout");
-    unsafe {
-        let ptr = alloc::alloc(layout);
-        if ptr.is_null() {
-            alloc::handle_alloc_error(layout);
+/// Fills the buffer from the stream, returning [`Error::PacketTruncated`] if
+/// the stream ends first
+fn read_table_data(reader: &mut dyn Read, buffer: &mut [u8]) -> Result<()> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => {
+                return Err(Error::PacketTruncated(buffer.len() as u64, filled as u64));
+            }
+            Ok(length) => filled += length,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(Error::IOError(error)),
         }
-        Vec::from_raw_parts(ptr, initial_size, minimum_size)
+    }
+    Ok(())
+}
+
+/**
+A packet whose table has been read (and decrypted, if it was encrypted), but
+not parsed
+
+Reading a packet this way doesn't need to know which table it holds. Its
+prefix, and the schema of its table (see [`Schema::from_bytes`]), can be
+used to decide, and then [`Packet::from_raw`] reads the table without
+reading the packet again.
+
+[`Schema::from_bytes`]: crate::Schema::from_bytes
+
+# Example
+```
+# use criware_utf::{Packet, RawPacket, Schema, Table, utf_table};
+#[utf_table]
+struct Toc {
+    file_name: String,
+}
+
+#[utf_table]
+struct Etoc {
+    update_date_time: u64,
+}
+
+enum Section {
+    Toc(Packet<Toc>),
+    Etoc(Packet<Etoc>),
+}
+
+# let mut data = Vec::new();
+# Etoc::new().write_packet(&mut data, b"ETOC", true)?;
+let raw = RawPacket::read(&mut data.as_slice())?;
+let schema = Schema::from_bytes(raw.table_data())?;
+println!("{:?} packet holding {}", raw.prefix(), schema.table_name);
+let section = match &raw.prefix() {
+    b"TOC " => Section::Toc(Packet::from_raw(raw)?),
+    b"ETOC" => Section::Etoc(Packet::from_raw(raw)?),
+    prefix => panic!("unknown packet {prefix:?}"),
+};
+assert!(matches!(section, Section::Etoc(packet) if packet.is_encrypted()));
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub struct RawPacket {
+    pub(crate) prefix: [u8; 4],
+    pub(crate) header_flags: u32,
+    pub(crate) encrypted: bool,
+    /// The cipher the table was decrypted with, if it isn't the standard one
+    pub(crate) cipher: Option<Arc<dyn CriCipher + Send + Sync>>,
+    /// The table (decrypted, if it was encrypted)
+    pub(crate) table: Vec<u8>,
+}
+
+impl RawPacket {
+    /**
+    Reads a UTF table packet from the given stream with whatever 4-byte
+    prefix it has, decrypting its table if it's encrypted
+
+    Packets declaring a larger table than the default
+    [`ReaderOptions::max_table_size`] are rejected before any memory is
+    allocated for them.
+     */
+    pub fn read(reader: &mut dyn Read) -> Result<Self> {
+        read_packet_data(reader, None, None, &ReaderOptions::default())
+    }
+
+    /**
+    Reads a UTF table packet from the given stream with whatever 4-byte
+    prefix it has, decrypting its table with the given cipher if it's
+    encrypted (see [`RawPacket::read`] and [`CriCipher`])
+
+    The cipher is passed on to the packet made by [`Packet::from_raw`].
+     */
+    pub fn read_with_cipher(
+        reader: &mut dyn Read,
+        cipher: Arc<dyn CriCipher + Send + Sync>,
+    ) -> Result<Self> {
+        let mut packet = read_packet_data(
+            reader,
+            None,
+            Some(cipher.as_ref()),
+            &ReaderOptions::default(),
+        )?;
+        packet.cipher = Some(cipher);
+        Ok(packet)
+    }
+
+    /**
+    Returns the packet's 4-byte prefix
+     */
+    pub fn prefix(&self) -> [u8; 4] {
+        self.prefix
+    }
+
+    /**
+    Returns the value stored after the prefix in the packet header (see
+    [`Packet::header_flags`])
+     */
+    pub fn header_flags(&self) -> u32 {
+        self.header_flags
+    }
+
+    /**
+    Returns whether or not the table was encrypted
+     */
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    /**
+    Returns the table, decrypted if it was encrypted
+
+    This includes any padding the packet declares after the table.
+     */
+    pub fn table_data(&self) -> &[u8] {
+        &self.table
+    }
+}
+
+/// Checks that a packet header has the given prefix (if one is given), and
+/// declares a table within the size limit of the given options
+fn check_header(
+    header: &PacketHeader,
+    prefix: Option<&[u8; 4]>,
+    options: &ReaderOptions,
+) -> Result<()> {
+    if prefix.is_some_and(|prefix| prefix != &header.prefix) {
+        return Err(Error::WrongTableSchema);
+    }
+    if header.table_size < 32 {
+        return Err(Error::MalformedHeader);
+    }
+    let max_table_size = options.max_table_size as u64 + 8;
+    if header.table_size > max_table_size {
+        return Err(Error::LimitExceeded(
+            "packet size",
+            header.table_size,
+            max_table_size,
+        ));
+    }
+    Ok(())
+}
+
+/// Decrypts an encrypted table in place, checking that it was decrypted
+/// correctly
+///
+/// If a cipher isn't given, the table is decrypted with the standard cipher
+/// (see [`CriXorCipher::STANDARD`]).
+fn decrypt_table(table_data: &mut [u8], cipher: Option<&dyn CriCipher>) -> Result<()> {
+    let cipher = match cipher {
+        Some(cipher) => cipher,
+        None if crypto::can_decrypt(table_data) => &CriXorCipher::STANDARD,
+        None => return Err(Error::DecryptionError),
+    };
+    cipher.decrypt(table_data);
+    if &table_data[0..4] == b"@UTF" {
+        Ok(())
+    } else {
+        Err(Error::DecryptionError)
     }
 }
-fn aligned_vec_empty() -> Vec<u8> {
-    aligned_vec(0, 256)
+
+/// Reads the header and table of a packet with the given prefix (or any
+/// prefix, if one isn't given), decrypting the table if it's encrypted
+///
+/// The table is only read (and memory for it allocated) if it's within the
+/// size limit of the given options. If a cipher isn't given, the table is
+/// decrypted with the standard cipher (see [`CriXorCipher::STANDARD`]).
+pub(crate) fn read_packet_data(
+    reader: &mut dyn Read,
+    prefix: Option<&[u8; 4]>,
+    cipher: Option<&dyn CriCipher>,
+    options: &ReaderOptions,
+) -> Result<RawPacket> {
+    let header = PacketHeader::read(reader)?;
+    check_header(&header, prefix, options)?;
+    let mut table_data = vec![0u8; header.table_size as usize];
+    read_table_data(reader, table_data.as_mut_slice())?;
+    let encrypted = &table_data[0..4] != b"@UTF";
+    if encrypted {
+        decrypt_table(table_data.as_mut_slice(), cipher)?;
+    }
+    Ok(RawPacket {
+        prefix: header.prefix,
+        header_flags: header.header_flags,
+        encrypted,
+        cipher: None,
+        table: table_data,
+    })
 }
-fn aligned_vec_full(size: usize) -> Vec<u8> {
-    aligned_vec(size, size)
+
+/// Returns the number of bytes a (decrypted) table that was read from a
+/// packet is padded with, which is any bytes the packet declares after the
+/// end of the table
+///
+/// The table's size is read with the given byte order.
+fn table_padding(table_data: &[u8], endianness: Endianness) -> u64 {
+    let table_size = endianness.u32_from(table_data[4..8].try_into().unwrap()) as u64 + 8;
+    (table_data.len() as u64).saturating_sub(table_size)
+}
+
+/// Returns the padding a table was read with, so it's kept when the packet is
+/// written back
+fn padding_from(table_data: &[u8], endianness: Endianness) -> PacketPadding {
+    match table_padding(table_data, endianness) {
+        0 => PacketPadding::None,
+        padding => PacketPadding::Bytes(padding),
+    }
 }
 
 /**
 Packed, encryptable UTF table
+
+The packet's 4-byte prefix (like `b"CPK "`) is passed to [`Packet::new`],
+[`Packet::from_table`], or [`Packet::read_packet`] by reference, like it is to
+every other function that takes a prefix. The `_with_prefix` versions of those
+functions take it by value instead, which is handy when it's only known at
+runtime (like when it's read from a file to pick which table to read).
  */
 pub struct Packet<T: Table> {
-    prefix: &'static [u8; 4],
+    prefix: [u8; 4],
     encrypted: bool,
-    unknown_value: u32,
+    header_flags: u32,
+    padding: PacketPadding,
+    verify_writes: bool,
+    cipher: Option<Arc<dyn CriCipher + Send + Sync>>,
     table: T,
 }
 
@@ -42,18 +540,36 @@ impl<T: Table> Packet<T> {
 
     The table itself is initialized with `T::new()`
      */
-    pub fn new(prefix: &'static [u8; 4]) -> Self {
-        Self::from_table(T::new(), prefix)
+    pub fn new(prefix: &[u8; 4]) -> Self {
+        Self::with_prefix(*prefix)
+    }
+
+    /**
+    Creates a new UTF table packet with the given prefix (see [`Packet::new`])
+     */
+    pub fn with_prefix(prefix: [u8; 4]) -> Self {
+        Self::from_table_with_prefix(T::new(), prefix)
     }
 
     /**
     Creates a new UTF table packet with the given prefix
      */
-    pub fn from_table(table: T, prefix: &'static [u8; 4]) -> Self {
+    pub fn from_table(table: T, prefix: &[u8; 4]) -> Self {
+        Self::from_table_with_prefix(table, *prefix)
+    }
+
+    /**
+    Creates a new UTF table packet with the given prefix (see
+    [`Packet::from_table`])
+     */
+    pub fn from_table_with_prefix(table: T, prefix: [u8; 4]) -> Self {
         Packet {
             prefix,
             encrypted: false,
-            unknown_value: 0,
+            header_flags: 0,
+            padding: PacketPadding::None,
+            verify_writes: false,
+            cipher: None,
             table,
         }
     }
@@ -62,75 +578,579 @@ impl<T: Table> Packet<T> {
     Reads a UTF table packet from the given stream, verifying that it has
     the given 4-byte prefix.
      */
-    pub fn read_packet(reader: &mut dyn Read, prefix: &'static [u8; 4]) -> Result<Self> {
-        let mut header = [0u8; 16];
-        reader.read_exact(&mut header).io("UTF packet header")?;
-        if prefix != &header[0..4] {
-            return Err(Error::WrongTableSchema);
-        }
-        let unknown_value = u32::from_le_bytes(header[4..8].try_into().unwrap());
-        let table_size = u64::from_le_bytes(header[8..16].try_into().unwrap());
-        if table_size < 32 {
-            return Err(Error::MalformedHeader);
+    pub fn read_packet(reader: &mut dyn Read, prefix: &[u8; 4]) -> Result<Self> {
+        Self::read_packet_with_prefix(reader, *prefix)
+    }
+
+    /**
+    Reads a UTF table packet from the given stream, verifying that it has
+    the given 4-byte prefix (see [`Packet::read_packet`])
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Packet, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let mut magic = [0u8; 4];
+    magic.copy_from_slice(&std::fs::read("prefix.bin")?[..4]);
+    let mut file = File::open("toc.bin")?;
+    let toc = Packet::<Toc>::read_packet_with_prefix(&mut file, magic)?;
+    assert_eq!(toc.prefix(), magic);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_with_prefix(reader: &mut dyn Read, prefix: [u8; 4]) -> Result<Self> {
+        Self::read_packet_private(reader, Some(prefix), None, T::reader_options())
+    }
+
+    /**
+    Reads a UTF table packet from the given stream, verifying that it has
+    the given 4-byte prefix, and reading its table with the given options
+
+    The options' size limits apply to the packet too: a packet declaring a
+    larger table than `max_table_size` is rejected before any memory is
+    allocated for it. Other packet functions use the table's own options
+    (see [`Table::reader_options`]).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Packet, ReaderOptions, Table, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let options = ReaderOptions {
+        max_table_size: 1 << 20,
+        ..Toc::reader_options()
+    };
+    let mut file = File::open("toc.bin")?;
+    let toc = Packet::<Toc>::read_packet_with_options(&mut file, b"TOC ", options)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_with_options(
+        reader: &mut dyn Read,
+        prefix: &[u8; 4],
+        options: ReaderOptions,
+    ) -> Result<Self> {
+        Self::read_packet_private(reader, Some(*prefix), None, options)
+    }
+
+    /**
+    Reads a UTF table packet from the given stream, verifying that it has
+    the given 4-byte prefix, and decrypting it with the given cipher if it's
+    encrypted (see [`CriCipher`])
+
+    The packet keeps the cipher, so [`Packet::write_packet`] encrypts it the
+    same way again (see [`Packet::cipher`]).
+     */
+    pub fn read_packet_with_cipher(
+        reader: &mut dyn Read,
+        prefix: &[u8; 4],
+        cipher: Arc<dyn CriCipher + Send + Sync>,
+    ) -> Result<Self> {
+        Self::read_packet_private(reader, Some(*prefix), Some(cipher), T::reader_options())
+    }
+
+    /**
+    Reads the table of a packet that was read without knowing which table it
+    holds (see [`RawPacket`])
+
+    The packet keeps the raw packet's prefix, header flags, encryption state,
+    cipher, and padding.
+     */
+    pub fn from_raw(raw: RawPacket) -> Result<Self> {
+        Self::from_raw_with_options(raw, T::reader_options())
+    }
+
+    fn from_raw_with_options(raw: RawPacket, options: ReaderOptions) -> Result<Self> {
+        Ok(Packet {
+            prefix: raw.prefix,
+            encrypted: raw.encrypted,
+            header_flags: raw.header_flags,
+            padding: padding_from(&raw.table, options.endianness),
+            verify_writes: false,
+            table: T::read_from_reader(Reader::from_bytes_with_options(&raw.table, options)?)?,
+            cipher: raw.cipher,
+        })
+    }
+
+    fn read_packet_private(
+        reader: &mut dyn Read,
+        prefix: Option<[u8; 4]>,
+        cipher: Option<Arc<dyn CriCipher + Send + Sync>>,
+        options: ReaderOptions,
+    ) -> Result<Self> {
+        let mut raw = read_packet_data(
+            reader,
+            prefix.as_ref(),
+            cipher.as_deref().map(|cipher| cipher as &dyn CriCipher),
+            &options,
+        )?;
+        raw.cipher = cipher;
+        Self::from_raw_with_options(raw, options)
+    }
+
+    /**
+    Reads a UTF table packet from a slice of memory, verifying that it has
+    the given 4-byte prefix
+
+    Unencrypted tables are read directly from the slice, and encrypted tables
+    are copied only once, to be decrypted. The slice can extend past the end
+    of the packet, so packets can be read one after another using
+    [`PacketHeader::packet_size`] to find where the next one starts.
+
+    # Example
+    ```no_run
+    # use criware_utf::{Packet, PacketHeader, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let data = std::fs::read("packets.bin")?;
+    let mut position = 0;
+    while position < data.len() {
+        let header = PacketHeader::from_bytes(data[position..position + 16].try_into()?);
+        let toc = Packet::<Toc>::read_packet_from_slice(&data[position..], b"TOC ")?;
+        // ... do something ...
+        position += header.packet_size() as usize;
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_from_slice(data: &[u8], prefix: &[u8; 4]) -> Result<Self> {
+        Self::read_packet_from_slice_private(data, prefix, None)
+    }
+
+    /**
+    Reads a UTF table packet from a slice of memory, verifying that it has
+    the given 4-byte prefix, and decrypting it with the given cipher if it's
+    encrypted (see [`Packet::read_packet_from_slice`] and [`CriCipher`])
+
+    The packet keeps the cipher, so [`Packet::write_packet`] encrypts it the
+    same way again (see [`Packet::cipher`]).
+
+    # Example
+    ```
+    # use std::sync::Arc;
+    # use criware_utf::{CriXorCipher, Packet, utf_table};
+    # #[utf_table]
+    # struct Header {}
+    let cipher = Arc::new(CriXorCipher {
+        seed: 0x5f3d,
+        multiplier: 0x4115,
+    });
+    # let mut data = Vec::new();
+    # let mut packet = Packet::<Header>::new(b"CPK ");
+    # packet.enable_encryption();
+    # packet.write_packet_with_cipher(&mut data, cipher.as_ref())?;
+    let header = Packet::<Header>::read_packet_from_slice_with_cipher(&data, b"CPK ", cipher)?;
+    let mut written = Vec::new();
+    header.write_packet(&mut written)?;
+    assert_eq!(written, data);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_from_slice_with_cipher(
+        data: &[u8],
+        prefix: &[u8; 4],
+        cipher: Arc<dyn CriCipher + Send + Sync>,
+    ) -> Result<Self> {
+        Self::read_packet_from_slice_private(data, prefix, Some(cipher))
+    }
+
+    fn read_packet_from_slice_private(
+        data: &[u8],
+        prefix: &[u8; 4],
+        cipher: Option<Arc<dyn CriCipher + Send + Sync>>,
+    ) -> Result<Self> {
+        let header = match data.first_chunk::<{ PacketHeader::SIZE }>() {
+            Some(header) => PacketHeader::from_bytes(header),
+            None => return Err(Error::EOF("UTF packet header".to_owned())),
+        };
+        let options = T::reader_options();
+        check_header(&header, Some(prefix), &options)?;
+        let table_data = &data[PacketHeader::SIZE..];
+        if (table_data.len() as u64) < header.table_size {
+            return Err(Error::PacketTruncated(
+                header.table_size,
+                table_data.len() as u64,
+            ));
         }
-        let mut table_data = aligned_vec_full(table_size as usize);
-        let mut decrypted_table_data = aligned_vec_full(table_size as usize);
-        reader
-            .read_exact(table_data.as_mut_slice())
-            .io("UTF table")?;
-        if &table_data[0..4] == b"@UTF" {
-            return Ok(Packet {
-                prefix,
-                encrypted: false,
-                unknown_value,
-                table: T::read(&mut Cursor::new(table_data))?,
+        let table_data = &table_data[..header.table_size as usize];
+        let (encrypted, padding, table) = if &table_data[0..4] == b"@UTF" {
+            (
+                false,
+                padding_from(table_data, options.endianness),
+                T::read_from_slice(table_data)?,
+            )
+        } else {
+            let mut decrypted = table_data.to_vec();
+            decrypt_table(
+                decrypted.as_mut_slice(),
+                cipher.as_deref().map(|cipher| cipher as &dyn CriCipher),
+            )?;
+            (
+                true,
+                padding_from(&decrypted, options.endianness),
+                T::read_from_slice(&decrypted)?,
+            )
+        };
+        Ok(Packet {
+            prefix: *prefix,
+            encrypted,
+            header_flags: header.header_flags,
+            padding,
+            verify_writes: false,
+            cipher,
+            table,
+        })
+    }
+
+    /**
+    Returns the table
+     */
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    /**
+    Returns the table, which can be modified
+     */
+    pub fn table_mut(&mut self) -> &mut T {
+        &mut self.table
+    }
+
+    /**
+    Takes the table out of the packet
+     */
+    pub fn into_table(self) -> T {
+        self.table
+    }
+
+    /**
+    Replaces the table with the result of the given function, keeping the
+    packet's prefix, header flags, padding, encryption state and cipher, and
+    whether writes are verified
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Packet, Table, utf_table};
+    # #[utf_table(table_name = "Toc")]
+    # struct TocV1 { name: String }
+    # #[utf_table(table_name = "Toc")]
+    # struct TocV2 { name: String, size: u32 }
+    let mut file = File::open("toc.bin")?;
+    let old = Packet::<TocV1>::read_packet(&mut file, b"TOC ")?;
+    let new = old.map_table(|table| {
+        let mut new = TocV2::new();
+        for row in table.rows {
+            new.rows.push(TocV2Row {
+                name: row.name,
+                size: 0,
             });
         }
-        if !cri_encryption::can_decrypt(table_data.as_slice()) {
-            return Err(Error::DecryptionError);
-        }
-        cri_encryption::decrypt(table_data.as_slice(), decrypted_table_data.as_mut_slice());
-        if &decrypted_table_data[0..4] == b"@UTF" {
-            return Ok(Packet {
-                prefix,
-                encrypted: true,
-                unknown_value,
-                table: T::read(&mut Cursor::new(decrypted_table_data))?,
-            });
+        new
+    });
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn map_table<U: Table>(self, f: impl FnOnce(T) -> U) -> Packet<U> {
+        Packet {
+            prefix: self.prefix,
+            encrypted: self.encrypted,
+            header_flags: self.header_flags,
+            padding: self.padding,
+            verify_writes: self.verify_writes,
+            cipher: self.cipher,
+            table: f(self.table),
         }
-        return Err(Error::DecryptionError);
+    }
+
+    /**
+    Returns the packet's 4-byte prefix
+     */
+    pub fn prefix(&self) -> [u8; 4] {
+        self.prefix
+    }
+
+    /**
+    Changes the packet's 4-byte prefix
+     */
+    pub fn set_prefix(&mut self, prefix: [u8; 4]) {
+        self.prefix = prefix;
+    }
+
+    /**
+    Returns the cipher the packet is encrypted with, if it isn't the standard
+    one
+
+    Packets read with [`Packet::read_packet_with_cipher`] (or
+    [`Packet::read_packet_from_slice_with_cipher`]) keep the cipher they were
+    read with, and other packets use [`CriXorCipher::STANDARD`] (returning
+    `None` here).
+     */
+    pub fn cipher(&self) -> Option<&Arc<dyn CriCipher + Send + Sync>> {
+        self.cipher.as_ref()
+    }
+
+    /**
+    Changes the cipher the packet is encrypted with when it's written (see
+    [`Packet::cipher`])
+
+    `None` uses the standard cipher (see [`CriXorCipher::STANDARD`]).
+     */
+    pub fn set_cipher(&mut self, cipher: Option<Arc<dyn CriCipher + Send + Sync>>) {
+        self.cipher = cipher;
     }
 
     /**
     Writes a UTF table packet to the given stream.
+
+    If encryption is enabled, the table is encrypted with the packet's cipher
+    (see [`Packet::cipher`]).
      */
     pub fn write_packet(&self, writer: &mut dyn Write) -> Result<()> {
-        let mut table_buffer = Cursor::new(aligned_vec_empty());
-        self.table.write(&mut table_buffer)?;
-        let table_buffer = {
-            let mut buffer = table_buffer.into_inner();
-            if buffer.capacity() < buffer.len().div_ceil(64) << 6 {
-                buffer.reserve_exact(64 - (buffer.len() & 63));
-            }
-            if self.encrypted {
-                let mut new_buffer = aligned_vec_full(buffer.len());
-                cri_encryption::encrypt(buffer.as_slice(), new_buffer.as_mut_slice());
-                new_buffer
-            } else {
-                buffer
-            }
-        };
-        writer.write_all(self.prefix).io("UTF packet header")?;
-        writer
-            .write_all(&u32::to_le_bytes(self.unknown_value))
-            .io("UTF packet header")?;
-        writer
-            .write_all(&u64::to_le_bytes(table_buffer.len() as u64))
-            .io("UTF packet header")?;
-        writer
-            .write_all(table_buffer.as_slice())
-            .io("UTF packet table")?;
-        Ok(())
+        self.write_packet_private(
+            writer,
+            self.cipher
+                .as_deref()
+                .map(|cipher| cipher as &dyn CriCipher),
+        )
+    }
+
+    /**
+    Writes a UTF table packet to the given stream, encrypting it with the
+    given cipher if encryption is enabled (see [`CriCipher`])
+
+    The given cipher is used instead of the packet's own (see
+    [`Packet::cipher`]).
+     */
+    pub fn write_packet_with_cipher(
+        &self,
+        writer: &mut dyn Write,
+        cipher: &dyn CriCipher,
+    ) -> Result<()> {
+        self.write_packet_private(writer, Some(cipher))
+    }
+
+    fn write_packet_private(
+        &self,
+        writer: &mut dyn Write,
+        cipher: Option<&dyn CriCipher>,
+    ) -> Result<()> {
+        if !self.verify_writes {
+            return write_packet(
+                &self.table,
+                writer,
+                &self.prefix,
+                self.header_flags,
+                self.encrypted,
+                cipher,
+                self.padding,
+            );
+        }
+        let mut packet = Vec::new();
+        write_packet(
+            &self.table,
+            &mut packet,
+            &self.prefix,
+            self.header_flags,
+            self.encrypted,
+            cipher,
+            self.padding,
+        )?;
+        verify_packet::<T>(&packet, &self.prefix, cipher)?;
+        writer.write_all(&packet).io("UTF packet")
+    }
+
+    /**
+    Writes a UTF table packet to the given seekable stream, without copying
+    the finished table into a buffer first
+
+    [`Packet::write_packet`] writes the table into a buffer (and encrypts it
+    there) before writing any of the packet. This writes the table straight
+    to the stream instead (encrypting it in small chunks if encryption is
+    enabled), and seeks back to fill in the table size afterwards. The bytes
+    written are the same.
+
+    This only saves that one copy. The table is still laid out in memory by
+    its [`Writer`](crate::Writer) before it's written (see
+    [`Writer::end`](crate::Writer::end)), so writing a table still takes
+    about as much memory as the table's size.
+
+    Only the standard cipher (see [`CriXorCipher::STANDARD`]) can encrypt a
+    table in chunks. If the packet has another cipher (see
+    [`Packet::cipher`]) and encryption is enabled, the table is written into
+    memory and encrypted as a whole, like [`Packet::write_packet`] does.
+    Packets written this way are never verified (see
+    [`Packet::enable_write_verification`]), since that needs the whole packet
+    in memory.
+
+    # Example
+    ```no_run
+    # use std::{fs::File, io::BufWriter};
+    # use criware_utf::{Packet, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let toc = Packet::<Toc>::new(b"TOC ");
+    let mut file = BufWriter::new(File::create("toc.bin")?);
+    toc.write_packet_seek(&mut file)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_packet_seek(&self, writer: &mut (impl Write + Seek)) -> Result<()> {
+        if let Some(cipher) = &self.cipher
+            && self.encrypted
+        {
+            return write_packet(
+                &self.table,
+                writer,
+                &self.prefix,
+                self.header_flags,
+                self.encrypted,
+                Some(cipher.as_ref()),
+                self.padding,
+            );
+        }
+        write_packet_seek(
+            &self.table,
+            writer,
+            &self.prefix,
+            self.header_flags,
+            self.encrypted,
+            self.padding,
+        )
+    }
+
+    /**
+    Returns the value stored after the prefix in the packet header
+
+    What this value means isn't known. Packets made by official tools often
+    have `0xff` here, and some tools expect to find it. It's kept as-is when
+    a packet is read and written back, and is 0 in new packets.
+     */
+    pub fn header_flags(&self) -> u32 {
+        self.header_flags
+    }
+
+    /**
+    Changes the value stored after the prefix in the packet header (see
+    [`Packet::header_flags`])
+     */
+    pub fn set_header_flags(&mut self, flags: u32) {
+        self.header_flags = flags;
+    }
+
+    /**
+    Returns how the table is padded when the packet is written
+
+    New packets aren't padded. When a packet is read, any bytes it declares
+    after the end of its table are kept as [`PacketPadding::Bytes`], so that
+    writing it back gives a packet of the same size.
+     */
+    pub fn padding(&self) -> PacketPadding {
+        self.padding
+    }
+
+    /**
+    Changes how the table is padded when the packet is written (see
+    [`Packet::padding`])
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Packet, PacketPadding, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let mut toc = Packet::<Toc>::read_packet(&mut File::open("toc.bin")?, b"TOC ")?;
+    toc.set_padding(PacketPadding::Align(2048));
+    toc.write_packet(&mut File::create("toc_new.bin")?)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn set_padding(&mut self, padding: PacketPadding) {
+        self.padding = padding;
+    }
+
+    /**
+    Returns whether or not packets are read back and checked after they're
+    written (see [`Packet::enable_write_verification`])
+     */
+    pub fn verifies_writes(&self) -> bool {
+        self.verify_writes
+    }
+
+    /**
+    Makes [`Packet::write_packet`] (and
+    [`Packet::write_packet_with_cipher`]) check the packet before writing it
+
+    The packet is written into memory first, then read back: it has to
+    decrypt, and its table has to be written to the exact same bytes again.
+    If it doesn't, [`Error::PacketVerificationFailed`] is returned, and
+    nothing is written to the stream.
+
+    This costs an extra read and write of the table, which is worth it for
+    files given to a game that fails silently on a bad table.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Packet, utf_table};
+    # #[utf_table]
+    # struct Toc {}
+    let mut toc = Packet::<Toc>::new(b"TOC ");
+    toc.enable_write_verification();
+    toc.write_packet(&mut File::create("toc.bin")?)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    Tables read with their layout recorded are checked against that layout,
+    so a table that isn't laid out the way this crate would write it still
+    passes:
+    ```
+    # use criware_utf::{
+    #     Packet, Reader, ReaderOptions, Table, TableNamePlacement, WriterOptions, utf_table,
+    # };
+    #[utf_table(round_trip = true)]
+    struct Tab {
+        name: String,
+    }
+
+    # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut table = Tab::new();
+    table.rows.push(TabRow { name: "file.bin".to_owned() });
+    let mut data = Vec::new();
+    table.write_with_options(
+        &mut data,
+        WriterOptions {
+            table_name_placement: TableNamePlacement::BeforeSentinel,
+            ..Tab::writer_options()
+        },
+    )?;
+    let options = ReaderOptions {
+        record_layout: true,
+        ..Tab::reader_options()
+    };
+    let table = Tab::read_from_reader(Reader::from_bytes_with_options(&data, options)?)?;
+    let mut packet = Packet::from_table(table, b"TAB ");
+    packet.enable_write_verification();
+    let mut written = Vec::new();
+    packet.write_packet(&mut written)?;
+    assert_eq!(&written[16..], data.as_slice());
+    # Ok(())
+    # }
+    ```
+     */
+    pub fn enable_write_verification(&mut self) {
+        self.verify_writes = true;
+    }
+
+    /**
+    Stops packets from being checked after they're written (see
+    [`Packet::enable_write_verification`])
+     */
+    pub fn disable_write_verification(&mut self) {
+        self.verify_writes = false;
     }
 
     /**
@@ -155,6 +1175,37 @@ impl<T: Table> Packet<T> {
     }
 }
 
+impl<T: PrefixedTable> Packet<T> {
+    /**
+    Reads a UTF table packet from the given stream, verifying that it has
+    the table's prefix (see [`PrefixedTable`])
+     */
+    pub fn read(reader: &mut dyn Read) -> Result<Self> {
+        Self::read_packet_with_prefix(reader, T::PREFIX)
+    }
+
+    /**
+    Reads a UTF table packet from a slice of memory, verifying that it has
+    the table's prefix (see [`PrefixedTable`] and
+    [`Packet::read_packet_from_slice`])
+     */
+    pub fn read_from_slice(data: &[u8]) -> Result<Self> {
+        Self::read_packet_from_slice(data, &T::PREFIX)
+    }
+}
+
+impl<T: PrefixedTable> Default for Packet<T> {
+    fn default() -> Self {
+        Self::with_prefix(T::PREFIX)
+    }
+}
+
+impl<T: PrefixedTable> From<T> for Packet<T> {
+    fn from(table: T) -> Self {
+        Self::from_table_with_prefix(table, T::PREFIX)
+    }
+}
+
 impl<T: Table> Deref for Packet<T> {
     type Target = T;
 
@@ -0,0 +1,107 @@
+/**
+A way of encrypting the tables in packets
+
+Packets are normally encrypted with [`CriXorCipher::STANDARD`], which is what
+[`Packet::read_packet`](crate::Packet::read_packet) and
+[`Packet::write_packet`](crate::Packet::write_packet) use. Games that encrypt
+their packets differently can be handled by implementing this trait, and using
+[`Packet::read_packet_with_cipher`](crate::Packet::read_packet_with_cipher)
+and
+[`Packet::write_packet_with_cipher`](crate::Packet::write_packet_with_cipher).
+Packets keep the cipher they were read with, and are written back with it.
+
+A table is known to be decrypted correctly when it starts with `@UTF`.
+
+# Example
+```
+# use criware_utf::CriCipher;
+/// A game that "encrypts" its tables by inverting every byte
+struct Inverted;
+
+impl CriCipher for Inverted {
+    fn decrypt(&self, data: &mut [u8]) {
+        data.iter_mut().for_each(|byte| *byte = !*byte);
+    }
+}
+
+let mut data = *b"@UTF";
+Inverted.encrypt(&mut data);
+Inverted.decrypt(&mut data);
+assert_eq!(&data, b"@UTF");
+```
+*/
+pub trait CriCipher {
+    /// Decrypts a table in place
+    ///
+    fn decrypt(&self, data: &mut [u8]);
+
+    /// Encrypts a table in place
+    ///
+    /// By default, this is the same as decrypting it, which is true of any
+    /// cipher that XORs the data with a mask.
+    ///
+    fn encrypt(&self, data: &mut [u8]) {
+        self.decrypt(data);
+    }
+}
+
+/**
+The XOR cipher CRIWARE encrypts packets with, with adjustable parameters
+
+Each byte is XORed with the lowest byte of a mask, which starts as `seed` and
+is multiplied by `multiplier` after every byte. Official tools use
+[`CriXorCipher::STANDARD`] (which is also the default), but some games use
+other parameters.
+
+# Example
+```no_run
+# use std::{fs::File, sync::Arc};
+# use criware_utf::{CriXorCipher, Packet, utf_table};
+# #[utf_table]
+# struct Header {}
+let cipher = Arc::new(CriXorCipher {
+    seed: 0x5f3d,
+    multiplier: 0x4115,
+});
+let mut file = File::open("game.cpk")?;
+let header = Packet::<Header>::read_packet_with_cipher(&mut file, b"CPK ", cipher)?;
+// Written back with the same cipher
+header.write_packet(&mut File::create("game_header.bin")?)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CriXorCipher {
+    /// The mask the first byte is XORed with
+    pub seed: u32,
+    /// What the mask is multiplied by after every byte
+    pub multiplier: u32,
+}
+
+impl CriXorCipher {
+    /// The parameters official tools use
+    ///
+    pub const STANDARD: CriXorCipher = CriXorCipher {
+        seed: 0x655f,
+        multiplier: 0x4115,
+    };
+}
+
+impl Default for CriXorCipher {
+    fn default() -> Self {
+        CriXorCipher::STANDARD
+    }
+}
+
+impl CriCipher for CriXorCipher {
+    fn decrypt(&self, data: &mut [u8]) {
+        if *self == CriXorCipher::STANDARD {
+            return crate::crypto::decrypt_in_place(data);
+        }
+        let mut mask = self.seed;
+        for byte in data {
+            *byte ^= mask as u8;
+            mask = mask.wrapping_mul(self.multiplier);
+        }
+    }
+}
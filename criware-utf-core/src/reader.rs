@@ -1,227 +1,2097 @@
 use std::{
-    collections::HashMap,
-    io::{Cursor, Read},
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::{Read, Seek},
+    sync::Arc,
 };
 
-use crate::{Error, IOErrorHelper, Result, Value, ValueKind, value::sealed::Primitive};
+mod scan;
+mod source;
+mod span;
+mod table_stream;
+
+pub use scan::{find_utf_tables, find_utf_tables_with_options};
+use source::STREAM_CACHE_SIZE;
+pub use source::{SeekSource, TableSource};
+pub use span::{Span, SpanKind, SpanMap};
+pub use table_stream::TableStream;
+
+use crate::{
+    AnyValue, BlobHandle, CellHook, ColumnStorageFormat, Encoding, Endianness, Error,
+    IOErrorHelper, OriginalLayout, Result, SchemaColumn, TableNamePlacement, TableStats, Value,
+    ValueKind, WriterOptions,
+    value::sealed::{Primitive, primitive_type_flag},
+};
 
 #[inline(always)]
 pub(crate) fn is_valid_value_flag(half: u8) -> bool {
-    half <= 8 || half == 0xa || half == 0xb
+    ValueKind::from_flag(half).is_some()
 }
 #[inline(always)]
 pub(crate) fn is_valid_storage_flag(half: u8) -> bool {
-    half == 0x10 || half == 0x30 || half == 0x50
+    ColumnStorageFormat::try_from(half).is_ok()
+}
+
+/// Reads a value of the given kind as an [`AnyValue`], by calling the given
+/// reader method with the matching primitive type
+macro_rules! read_any_kind {
+    ($kind:expr, $reader:ident.$method:ident($($arg:expr),*)) => {
+        match $kind {
+            ValueKind::U8 => AnyValue::U8($reader.$method::<u8>($($arg),*)?),
+            ValueKind::I8 => AnyValue::I8($reader.$method::<i8>($($arg),*)?),
+            ValueKind::U16 => AnyValue::U16($reader.$method::<u16>($($arg),*)?),
+            ValueKind::I16 => AnyValue::I16($reader.$method::<i16>($($arg),*)?),
+            ValueKind::U32 => AnyValue::U32($reader.$method::<u32>($($arg),*)?),
+            ValueKind::I32 => AnyValue::I32($reader.$method::<i32>($($arg),*)?),
+            ValueKind::U64 => AnyValue::U64($reader.$method::<u64>($($arg),*)?),
+            ValueKind::I64 => AnyValue::I64($reader.$method::<i64>($($arg),*)?),
+            ValueKind::F32 => AnyValue::F32($reader.$method::<f32>($($arg),*)?),
+            ValueKind::F64 => AnyValue::F64($reader.$method::<f64>($($arg),*)?),
+            ValueKind::STR => AnyValue::String($reader.$method::<str>($($arg),*)?),
+            ValueKind::BLOB => AnyValue::Blob($reader.$method::<[u8]>($($arg),*)?),
+        }
+    };
 }
 
 macro_rules! handle_type_flag {
-    ($type_flag:path => $expected:path) => {
-        if $type_flag != $expected as u8 {
-            if is_valid_value_flag($type_flag) {
-                return Err(Error::WrongColumnType($type_flag, $expected as u8));
+    ($type_flag:ident => $expected:expr) => {
+        let expected = $expected;
+        if $type_flag != expected {
+            // any flag is valid where a vendor flag is expected
+            if is_valid_value_flag($type_flag) || !is_valid_value_flag(expected) {
+                return Err(Error::WrongColumnType($type_flag, expected));
             } else {
                 return Err(Error::InvalidColumnType($type_flag));
             }
         }
-    };
-}
-
-/// Abstraction layer for reading UTF tables
-///
-pub struct Reader {
-    column_buffer: Cursor<Vec<u8>>,
-    column_buffer_size: usize,
-    row_buffer: Cursor<Vec<u8>>,
-    row_buffer_size: usize,
-    strings: HashMap<u32, String>,
-    blobs: Vec<u8>,
-    table_name_index: u32,
-    field_count: u16,
-}
+    };
+}
+
+/// Where the contents of a table are read from
+///
+/// All offsets are relative to the end of the `@UTF` magic and table size
+/// (the same as the offsets stored in the header).
+enum Source<'a> {
+    /// The entire table, either loaded into memory or borrowed
+    Bytes(Cow<'a, [u8]>),
+    /// Any other source, read on demand
+    Custom {
+        source: Box<dyn TableSource + 'a>,
+        end: u64,
+    },
+}
+
+impl Source<'_> {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8], message: &str) -> Result<()> {
+        match self {
+            Source::Bytes(data) => {
+                let start = offset as usize;
+                match data.get(start..start + buffer.len()) {
+                    Some(slice) => {
+                        buffer.copy_from_slice(slice);
+                        Ok(())
+                    }
+                    None => Err(Error::EOF(message.to_owned())),
+                }
+            }
+            Source::Custom { source, end } => {
+                if offset + buffer.len() as u64 > *end {
+                    return Err(Error::EOF(message.to_owned()));
+                }
+                source.read_at(8 + offset, buffer).io(message)
+            }
+        }
+    }
+}
+
+/// The contents of a table's header
+#[derive(Clone, Copy)]
+struct Header {
+    table_size: u32,
+    row_offset: u32,
+    string_offset: u32,
+    blob_offset: u32,
+    table_name: u32,
+    field_count: u16,
+    row_size: u16,
+    row_count: u32,
+}
+
+/// Reads just the header of a table (see [`TableStats::read`])
+pub(crate) fn read_stats(reader: &mut dyn Read, options: &ReaderOptions) -> Result<TableStats> {
+    Header::read(reader, options).map(|header| header.stats())
+}
+
+impl Header {
+    /// Returns the sizes the header declares
+    fn stats(&self) -> TableStats {
+        TableStats {
+            row_count: self.row_count,
+            row_size: self.row_size,
+            string_section_size: self.blob_offset - self.string_offset,
+            blob_section_size: self.table_size - self.blob_offset,
+            table_size: self.table_size,
+        }
+    }
+
+    /// Checks that the table can be loaded into memory (see
+    /// [`ReaderOptions::max_table_size`])
+    fn check_table_size(&self, options: &ReaderOptions) -> Result<()> {
+        if self.table_size > options.max_table_size {
+            return Err(Error::LimitExceeded(
+                "table size",
+                self.table_size as u64,
+                options.max_table_size as u64,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads the 8-byte prefix and 24-byte header from a stream
+    fn read(reader: &mut dyn Read, options: &ReaderOptions) -> Result<Header> {
+        let endianness = options.endianness;
+        let table_size = {
+            let mut header = [0u8; 8];
+            reader.read_exact(&mut header).io("@UTF header")?;
+            if &header[0..4] != b"@UTF" {
+                return Err(Error::MalformedHeader);
+            }
+            endianness.u32_from(header[4..8].try_into().unwrap())
+        };
+        if table_size < 24 {
+            return Err(Error::EOF("@UTF header".to_string()));
+        }
+        let mut header = [0u8; 24];
+        reader.read_exact(&mut header).io("@UTF header")?;
+        let header = Header {
+            table_size,
+            row_offset: endianness.u32_from(header[0..4].try_into().unwrap()),
+            string_offset: endianness.u32_from(header[4..8].try_into().unwrap()),
+            blob_offset: endianness.u32_from(header[8..12].try_into().unwrap()),
+            table_name: endianness.u32_from(header[12..16].try_into().unwrap()),
+            field_count: endianness.u16_from(header[16..18].try_into().unwrap()),
+            row_size: endianness.u16_from(header[18..20].try_into().unwrap()),
+            row_count: endianness.u32_from(header[20..24].try_into().unwrap()),
+        };
+        if 24 > header.row_offset
+            || header.row_offset > header.string_offset
+            || header.string_offset > header.blob_offset
+            || header.blob_offset > header.table_size
+        {
+            return Err(Error::MalformedHeader);
+        }
+        let rows_size = header.row_size as u64 * header.row_count as u64;
+        let row_section_size = (header.string_offset - header.row_offset) as u64;
+        if rows_size > row_section_size
+            || (rows_size < row_section_size && !options.allow_row_padding)
+        {
+            return Err(Error::MalformedHeader);
+        }
+        if header.blob_offset - header.string_offset > options.max_string_section_size {
+            return Err(Error::LimitExceeded(
+                "string section size",
+                (header.blob_offset - header.string_offset) as u64,
+                options.max_string_section_size as u64,
+            ));
+        }
+        if header.field_count > options.max_field_count {
+            return Err(Error::LimitExceeded(
+                "field count",
+                header.field_count as u64,
+                options.max_field_count as u64,
+            ));
+        }
+        if header.row_count > options.max_row_count {
+            return Err(Error::LimitExceeded(
+                "row count",
+                header.row_count as u64,
+                options.max_row_count as u64,
+            ));
+        }
+        Ok(header)
+    }
+
+    /// Returns the 8-byte prefix and 24-byte header, as they are stored
+    fn to_bytes(self, endianness: Endianness) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[0..4].copy_from_slice(b"@UTF");
+        bytes[4..8].copy_from_slice(&endianness.u32_to(self.table_size));
+        bytes[8..12].copy_from_slice(&endianness.u32_to(self.row_offset));
+        bytes[12..16].copy_from_slice(&endianness.u32_to(self.string_offset));
+        bytes[16..20].copy_from_slice(&endianness.u32_to(self.blob_offset));
+        bytes[20..24].copy_from_slice(&endianness.u32_to(self.table_name));
+        bytes[24..26].copy_from_slice(&endianness.u16_to(self.field_count));
+        bytes[26..28].copy_from_slice(&endianness.u16_to(self.row_size));
+        bytes[28..32].copy_from_slice(&endianness.u32_to(self.row_count));
+        bytes
+    }
+
+    /// Returns the offset where the last row ends (which may be before the
+    /// string data section, if there is padding)
+    fn rows_end(&self) -> u32 {
+        self.row_offset + self.row_size as u32 * self.row_count
+    }
+}
+
+/// Fills the buffer with data from an asynchronous stream
+#[cfg(feature = "futures-io")]
+async fn read_exact_async(
+    reader: &mut (impl futures_io::AsyncRead + Unpin),
+    mut buffer: &mut [u8],
+) -> std::io::Result<()> {
+    while !buffer.is_empty() {
+        let read =
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut *reader).poll_read(cx, &mut *buffer))
+                .await;
+        match read {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(length) => buffer = &mut buffer[length..],
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+/// Where a column's value in each row comes from (see [`RowCells`])
+#[derive(Debug, Clone, Copy)]
+enum RowCell {
+    /// The value is stored in the rows
+    Rowed,
+    /// The value is stored once, as a constant at the given offset
+    Constant(u32),
+    /// The value is stored in the rows, but was read as a constant (see
+    /// [`Reader::read_constant_column`]), so the given number of bytes are
+    /// skipped in every row
+    Skipped(u32),
+}
+
+/// Where the values of the rowed columns read so far come from, once any of
+/// them turns out not to be stored the way it's read (see
+/// [`Reader::read_rowed_column`] and [`Reader::read_constant_column`])
+struct RowCells {
+    /// Where each column stored in the rows, or read as rowed, comes from
+    sources: Vec<RowCell>,
+    /// Whether any of the sources isn't [`RowCell::Rowed`]
+    irregular: bool,
+    /// The size (in bytes) of the columns stored in the rows so far, which is
+    /// where the next one starts in each row
+    stored_size: u32,
+    /// The column whose value is read next
+    next: usize,
+    /// The row being read, which the row buffer can't tell when the rows are
+    /// empty
+    row: u32,
+}
+
+impl RowCells {
+    /// Moves on to the next column, wrapping around to the next row after the
+    /// last one
+    fn advance(&mut self) {
+        self.next += 1;
+        if self.next >= self.sources.len() {
+            self.next = 0;
+            self.row += 1;
+        }
+    }
+}
+
+/// The strings in the string data section, keyed by their offsets
+struct Strings<'b> {
+    decoded: HashMap<u32, Cow<'b, str>>,
+    /// The strings that can't be decoded (see [`RawString`](crate::RawString))
+    raw: HashMap<u32, Cow<'b, [u8]>>,
+}
+
+impl Strings<'_> {
+    fn into_owned(self) -> Strings<'static> {
+        Strings {
+            decoded: self
+                .decoded
+                .into_iter()
+                .map(|(offset, string)| (offset, Cow::Owned(string.into_owned())))
+                .collect(),
+            raw: self
+                .raw
+                .into_iter()
+                .map(|(offset, string)| (offset, Cow::Owned(string.into_owned())))
+                .collect(),
+        }
+    }
+}
+
+/// Splits the string data section into its strings
+///
+/// `section_offset` is the offset of the string data section in the header,
+/// and is only used for error reporting.
+fn parse_strings<'b>(
+    buffer: &'b [u8],
+    section_offset: u32,
+    options: &ReaderOptions,
+) -> Result<Strings<'b>> {
+    let encoding = options.encoding;
+    let mut strings = HashMap::new();
+    let mut raw = HashMap::new();
+    let mut start = 0;
+    let mut index = 0;
+    while index < buffer.len() {
+        if buffer[index] == 0 {
+            let bytes = &buffer[(start as usize)..index];
+            // strings that can't be decoded only fail once they're read
+            match encoding.decode(bytes) {
+                Ok(value) => {
+                    strings.insert(start, value);
+                }
+                Err(_) => {
+                    raw.insert(start, Cow::Borrowed(bytes));
+                }
+            }
+            start = (index + 1) as u32;
+        }
+        index += 1;
+    }
+    if (start as usize) < buffer.len() {
+        if options.strict && !options.tolerate_unterminated_strings {
+            return Err(Error::UnterminatedString(
+                8 + section_offset as u64 + start as u64,
+            ));
+        }
+        match encoding.decode(&buffer[(start as usize)..]) {
+            Ok(value) => {
+                strings.insert(start, value);
+            }
+            Err(error) if options.strict => return Err(error),
+            // garbage that can't be a string is left alone
+            Err(_) => {}
+        }
+    }
+    Ok(Strings {
+        decoded: strings,
+        raw,
+    })
+}
+
+/**
+Options that control how a [`Reader`] parses a table
+
+The default options match [`Reader::new`]: a big-endian, UTF-8 table where
+harmless quirks are worked around.
+
+The sizes declared in a table's header are used to allocate memory before
+any of the table is read, so tables from untrusted sources (like mod files)
+could otherwise make the reader allocate gigabytes. The default limits are
+far above anything seen in real games, and can be lowered (or raised) as
+needed.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::{Reader, ReaderOptions};
+let options = ReaderOptions {
+    strict: true,
+    max_table_size: 64 * 1024 * 1024,
+    ..Default::default()
+};
+let mut file = File::open("random-table.bin")?;
+let reader = Reader::new_with_options(&mut file, options)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone)]
+pub struct ReaderOptions {
+    /// The byte order of the table
+    pub endianness: Endianness,
+    /// The encoding of the table's strings
+    pub encoding: Encoding,
+    /// If `true`, quirks that would otherwise be worked around (like an
+    /// unterminated string at the end of the string section) are reported as
+    /// errors
+    pub strict: bool,
+    /// The largest table (in bytes) that will be loaded into memory (1 GiB by
+    /// default)
+    ///
+    /// This only applies to constructors that copy the whole table, like
+    /// [`Reader::new`]. Readers that borrow the table or read it on demand
+    /// (like [`Reader::from_bytes`] and [`Reader::new_seek`]) don't allocate
+    /// the table, so they aren't limited.
+    pub max_table_size: u32,
+    /// The largest string section (in bytes) that will be read (256 MiB by
+    /// default)
+    pub max_string_section_size: u32,
+    /// The most columns a table may have (4096 by default)
+    pub max_field_count: u16,
+    /// The most rows a table may have (16777216 by default)
+    pub max_row_count: u32,
+    /// If `true`, the row data section may be larger than the rows declared
+    /// in the header, and the extra bytes (alignment padding) are skipped
+    pub allow_row_padding: bool,
+    /// If `true`, bytes at the end of the string section without a trailing
+    /// NUL are read as a string, even in strict mode
+    ///
+    /// Outside of strict mode, they're always read as a string (unless they
+    /// can't be decoded, in which case they're skipped).
+    pub tolerate_unterminated_strings: bool,
+    /// The number of rows at the start of the table to skip over
+    ///
+    /// Along with `max_rows`, this limits which rows are visited by loops
+    /// using [`Reader::more_row_data`] (like the one in tables generated by
+    /// the `utf_table` macro).
+    pub skip_rows: u32,
+    /// The most rows to read (after the skipped ones), or [`None`] to read
+    /// every row
+    pub max_rows: Option<u32>,
+    /// If `true`, columns with unknown type flags are located by
+    /// [`Reader::find_column`] instead of failing with
+    /// [`Error::InvalidColumnType`], and their values can be read as
+    /// [`AnyValue::Unknown`]
+    ///
+    /// The size of an unknown value is inferred from the rest of the table,
+    /// which only works for one unknown rowed column, and an unknown constant
+    /// column if it's the last column.
+    pub preserve_unknown_types: bool,
+    /// Whether tables read with the `utf_table` macro record their layout, if
+    /// they have a `write_context` field (false by default)
+    ///
+    /// Writing the table back unmodified is then byte-identical to the
+    /// original (see [`RoundTripContext`](crate::RoundTripContext)). This
+    /// keeps a copy of the string and blob data sections in memory.
+    pub record_layout: bool,
+    /// A NULL sentinel (like `<NULL>`) that string values referring to it
+    /// are read as empty strings instead ([`None`] by default)
+    ///
+    /// This reads tables written with
+    /// [`EmptyStrings::Sentinel`](crate::EmptyStrings::Sentinel) back the way
+    /// they were written. Strings that are actually the sentinel can't be
+    /// told apart from empty strings in those tables, so they're read as empty
+    /// strings too. Column names and the table name are never affected.
+    pub empty_sentinel: Option<String>,
+    /// If `true`, a table that ends before the size its header declares is
+    /// read anyway (false by default)
+    ///
+    /// Only the bytes that are there can be read, and reading anything past
+    /// them fails with [`Error::EOF`]. Names cut off from the string data
+    /// section can't be checked, so they're assumed to be right (see
+    /// [`Reader::table_name_matches`]). This only applies to tables read into
+    /// memory, with [`Reader::new_with_options`] or
+    /// [`Reader::from_bytes_with_options`].
+    pub allow_truncated: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            endianness: Endianness::Big,
+            encoding: Encoding::Utf8,
+            strict: false,
+            max_table_size: 1 << 30,
+            max_string_section_size: 1 << 28,
+            max_field_count: 1 << 12,
+            max_row_count: 1 << 24,
+            allow_row_padding: false,
+            tolerate_unterminated_strings: false,
+            skip_rows: 0,
+            max_rows: None,
+            preserve_unknown_types: false,
+            record_layout: false,
+            empty_sentinel: None,
+            allow_truncated: false,
+        }
+    }
+}
+
+/// A section of a table, as passed to [`Reader::inspect_sections`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableSection {
+    /// The `@UTF` magic, table size, and the rest of the header (32 bytes)
+    Header,
+    /// The column data section
+    Columns,
+    /// The row data section
+    Rows,
+    /// The string data section
+    Strings,
+    /// The blob data section
+    Blobs,
+}
+
+/// Where a column of a table is stored, as found by [`Reader::find_column`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnLocation {
+    /// The position of the column in the table (starting from 0)
+    pub index: u16,
+    /// The kind of data the column stores, or [`None`] if its type flag is
+    /// unknown (see [`ReaderOptions::preserve_unknown_types`])
+    pub value_kind: Option<ValueKind>,
+    /// The type flag of the column (the lower half of its flag byte)
+    pub type_flag: u8,
+    /// The space (in bytes) each of the column's values takes up
+    pub size: u32,
+    /// The method in which the column stores data
+    pub storage_format: ColumnStorageFormat,
+    /// The position of the column's value within each row, if the column is
+    /// rowed
+    pub row_offset: Option<u16>,
+    /// The position of the column's value, relative to the start of the table
+    /// (the `@UTF` magic), if the column is constant
+    pub constant_offset: Option<u64>,
+}
+
+/**
+The result of matching a table's columns against the columns that were
+expected, as returned by [`Reader::read_columns_matching`]
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::{ColumnStorageFormat, Reader, SchemaColumn, ValueKind};
+let expected = [SchemaColumn {
+    name: "FileSize".to_owned(),
+    storage_format: ColumnStorageFormat::Rowed,
+    value_kind: ValueKind::U32,
+}];
+let mut file = File::open("random-table.bin")?;
+let mut reader = Reader::new(&mut file)?;
+let report = reader.read_columns_matching(&expected)?;
+for name in &report.extra {
+    println!("unexpected column: {name}");
+}
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone)]
+pub struct MatchReport {
+    /// Where each expected column was found (in the same order as they were
+    /// given), or [`None`] if the table doesn't have it
+    pub columns: Vec<Option<ColumnLocation>>,
+    /// The names of the table's columns that weren't expected
+    pub extra: Vec<String>,
+}
+
+impl MatchReport {
+    /// Returns the indices of the expected columns that the table doesn't
+    /// have
+    ///
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| location.is_none())
+            .map(|(index, _)| index)
+    }
+
+    /// Returns [`true`] if every expected column was found, and there were no
+    /// others
+    ///
+    pub fn is_exact(&self) -> bool {
+        self.extra.is_empty() && self.columns.iter().all(Option::is_some)
+    }
+}
+
+/// Statistics about the string data section of a table, as returned by
+/// [`Reader::string_stats`]
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringStats {
+    /// The number of strings in the section
+    pub count: usize,
+    /// The number of distinct strings in the section
+    pub unique: usize,
+    /// The size (in bytes) of the section
+    pub total_bytes: u64,
+    /// The bytes taken up by strings that are identical to an earlier string
+    /// (including their NULs)
+    pub duplicate_bytes: u64,
+}
+
+impl StringStats {
+    /// Returns the fraction of strings that are identical to an earlier
+    /// string (between 0 and 1)
+    ///
+    pub fn duplicate_ratio(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.count - self.unique) as f64 / self.count as f64
+        }
+    }
+}
+
+/// Abstraction layer for reading UTF tables
+///
+pub struct Reader<'a> {
+    source: Source<'a>,
+    header: Header,
+    column_position: u32,
+    row_position: u32,
+    row_window_end: u32,
+    strings: HashMap<u32, Cow<'a, str>>,
+    /// The strings that can't be decoded (see [`RawString`](crate::RawString))
+    raw_strings: HashMap<u32, Cow<'a, [u8]>>,
+    options: ReaderOptions,
+    bytes_read: u64,
+    progress: Option<Box<dyn FnMut(u64, u64) + 'a>>,
+    columns: Option<Vec<(String, ColumnLocation)>>,
+    spans: Option<SpanMap>,
+    /// Whether the table ends before the size its header declares (see
+    /// [`ReaderOptions::allow_truncated`])
+    truncated: bool,
+    row_cells: RowCells,
+}
+
+impl<'a> Reader<'a> {
+    /**
+    Creates a new `Reader`
+
+    The entire table is loaded into memory, and preliminary validity checks
+    are performed as well.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn new(reader: &mut dyn Read) -> Result<Reader<'a>> {
+        Self::new_with_options(reader, ReaderOptions::default())
+    }
+
+    /**
+    Creates a new `Reader` for a table stored with the given byte order
+
+    Official tables are always big-endian, which is what [`Reader::new`]
+    expects. This only needs to be used for nonstandard tables.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Endianness, Reader};
+    let mut file = File::open("patched-table.bin")?;
+    let reader = Reader::with_endianness(&mut file, Endianness::Little)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn with_endianness(reader: &mut dyn Read, endianness: Endianness) -> Result<Reader<'a>> {
+        Self::new_with_options(
+            reader,
+            ReaderOptions {
+                endianness,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Creates a new `Reader` for a table stored with the given byte order and
+    string encoding
+
+    Modern tables always use UTF-8, which is what [`Reader::new`] expects.
+    This only needs to be used for legacy tables.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Encoding, Endianness, Reader};
+    let mut file = File::open("legacy-table.bin")?;
+    let reader = Reader::with_encoding(&mut file, Endianness::Big, Encoding::ShiftJis)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn with_encoding(
+        reader: &mut dyn Read,
+        endianness: Endianness,
+        encoding: Encoding,
+    ) -> Result<Reader<'a>> {
+        Self::new_with_options(
+            reader,
+            ReaderOptions {
+                endianness,
+                encoding,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Creates a new `Reader` that parses the table with the given options
+
+    See [`ReaderOptions`].
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Reader, ReaderOptions};
+    let mut file = File::open("fan-made-table.bin")?;
+    let reader = Reader::new_with_options(
+        &mut file,
+        ReaderOptions {
+            strict: true,
+            tolerate_unterminated_strings: true,
+            ..Default::default()
+        },
+    )?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn new_with_options(reader: &mut dyn Read, options: ReaderOptions) -> Result<Reader<'a>> {
+        let header = Header::read(reader, &options)?;
+        header.check_table_size(&options)?;
+        let mut data = vec![0u8; header.table_size as usize];
+        if options.allow_truncated {
+            let mut length = 24;
+            loop {
+                match reader.read(&mut data[length..]) {
+                    Ok(0) => break,
+                    Ok(read) => length += read,
+                    Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(error) => return Err(error).io("UTF table data"),
+                }
+            }
+            data.truncate(length);
+            return Self::from_source(Source::Bytes(Cow::Owned(data)), header, options);
+        }
+        let sections = [
+            (24, header.row_offset, "UTF column data"),
+            (header.row_offset, header.string_offset, "UTF row data"),
+            (header.string_offset, header.blob_offset, "UTF string data"),
+            (header.blob_offset, header.table_size, "UTF blob data"),
+        ];
+        for (start, end, message) in sections {
+            reader
+                .read_exact(&mut data[(start as usize)..(end as usize)])
+                .io(message)?;
+        }
+        Self::from_source(Source::Bytes(Cow::Owned(data)), header, options)
+    }
+
+    /**
+    Creates a new `Reader` from a table that is already in memory
+
+    Nothing is copied up front. The string data and blobs are borrowed from
+    the given slice, and are only copied when values are read.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Reader;
+    let data = std::fs::read("random-table.bin")?;
+    let reader = Reader::from_bytes(&data)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn from_bytes(data: &'a [u8]) -> Result<Reader<'a>> {
+        Self::from_bytes_with_options(data, ReaderOptions::default())
+    }
+
+    /**
+    Creates a new `Reader` from a table that is already in memory, and is
+    stored with the given byte order
+
+    See [`Reader::from_bytes`] and [`Reader::with_endianness`].
+     */
+    pub fn from_bytes_with_endianness(
+        data: &'a [u8],
+        endianness: Endianness,
+    ) -> Result<Reader<'a>> {
+        Self::from_bytes_with_options(
+            data,
+            ReaderOptions {
+                endianness,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Creates a new `Reader` from a table that is already in memory, and is
+    stored with the given byte order and string encoding
+
+    See [`Reader::from_bytes`] and [`Reader::with_encoding`].
+     */
+    pub fn from_bytes_with_encoding(
+        data: &'a [u8],
+        endianness: Endianness,
+        encoding: Encoding,
+    ) -> Result<Reader<'a>> {
+        Self::from_bytes_with_options(
+            data,
+            ReaderOptions {
+                endianness,
+                encoding,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Creates a new `Reader` from a table that is already in memory, and parses
+    it with the given options
+
+    See [`Reader::from_bytes`] and [`ReaderOptions`].
+     */
+    pub fn from_bytes_with_options(data: &'a [u8], options: ReaderOptions) -> Result<Reader<'a>> {
+        let header = Header::read(&mut &data[..], &options)?;
+        match data.get(8..(8 + header.table_size as usize)) {
+            Some(data) => Self::from_source(Source::Bytes(Cow::Borrowed(data)), header, options),
+            None if options.allow_truncated => {
+                Self::from_source(Source::Bytes(Cow::Borrowed(&data[8..])), header, options)
+            }
+            None => Err(Error::EOF("UTF table data".to_owned())),
+        }
+    }
+
+    /**
+    Creates a new `Reader` that reads from a seekable stream on demand
+
+    Unlike [`Reader::new`], the table is not loaded into memory all at once.
+    Only the header and the string data are read up front. Everything else
+    is read (and sought to) as it's needed, which keeps memory usage low for
+    very large tables.
+
+    The table must start at the current position of the stream. The position
+    of the stream afterwards is unspecified.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let file = File::open("huge-table.bin")?;
+    let reader = Reader::new_seek(file)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn new_seek(reader: impl Read + Seek + 'a) -> Result<Reader<'a>> {
+        Self::new_seek_with_options(reader, ReaderOptions::default())
+    }
+
+    /**
+    Creates a new `Reader` that reads from a seekable stream on demand, and
+    parses the table with the given options
+
+    See [`Reader::new_seek`] and [`ReaderOptions`].
+     */
+    pub fn new_seek_with_options(
+        mut reader: impl Read + Seek + 'a,
+        options: ReaderOptions,
+    ) -> Result<Reader<'a>> {
+        let start = reader.stream_position().io("@UTF header")?;
+        let header = Header::read(&mut reader, &options)?;
+        let source = Source::Custom {
+            source: Box::new(SeekSource::new(reader, start)),
+            end: header.table_size as u64,
+        };
+        Self::from_source(source, header, options)
+    }
+
+    /**
+    Creates a new `Reader` that reads from the given [`TableSource`] on demand
+
+    Like [`Reader::new_seek`], only the header and the string data are read up
+    front.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Reader;
+    let data: Vec<u8> = std::fs::read("random-table.bin")?;
+    let reader = Reader::from_table_source(data)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn from_table_source(source: impl TableSource + 'a) -> Result<Reader<'a>> {
+        Self::from_table_source_with_options(source, ReaderOptions::default())
+    }
+
+    /**
+    Creates a new `Reader` that reads from the given [`TableSource`] on
+    demand, and parses the table with the given options
+
+    See [`Reader::from_table_source`] and [`ReaderOptions`].
+     */
+    pub fn from_table_source_with_options(
+        mut source: impl TableSource + 'a,
+        options: ReaderOptions,
+    ) -> Result<Reader<'a>> {
+        let mut header = [0u8; 32];
+        source.read_at(0, &mut header).io("@UTF header")?;
+        let header = Header::read(&mut &header[..], &options)?;
+        let source = Source::Custom {
+            source: Box::new(source),
+            end: header.table_size as u64,
+        };
+        Self::from_source(source, header, options)
+    }
+
+    /**
+    Creates a new `Reader` from an asynchronous stream
+
+    Like [`Reader::new`], the entire table is loaded into memory. Reading the
+    values afterwards doesn't touch the stream, so it doesn't need to be
+    asynchronous. Requires the `futures-io` feature.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Reader;
+    # async fn example(mut stream: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let reader = Reader::new_async(&mut stream).await?;
+    # Ok(())
+    # }
+    ```
+     */
+    #[cfg(feature = "futures-io")]
+    pub async fn new_async(
+        reader: &mut (impl futures_io::AsyncRead + Unpin),
+    ) -> Result<Reader<'a>> {
+        Self::new_async_with_options(reader, ReaderOptions::default()).await
+    }
+
+    /**
+    Creates a new `Reader` from an asynchronous stream, and parses the table
+    with the given options
+
+    See [`Reader::new_async`] and [`ReaderOptions`].
+     */
+    #[cfg(feature = "futures-io")]
+    pub async fn new_async_with_options(
+        reader: &mut (impl futures_io::AsyncRead + Unpin),
+        options: ReaderOptions,
+    ) -> Result<Reader<'a>> {
+        let mut header = [0u8; 32];
+        read_exact_async(reader, &mut header[0..8])
+            .await
+            .io("@UTF header")?;
+        read_exact_async(reader, &mut header[8..32])
+            .await
+            .io("@UTF header")?;
+        let header = Header::read(&mut &header[..], &options)?;
+        header.check_table_size(&options)?;
+        let mut data = vec![0u8; header.table_size as usize];
+        let sections = [
+            (24, header.row_offset, "UTF column data"),
+            (header.row_offset, header.string_offset, "UTF row data"),
+            (header.string_offset, header.blob_offset, "UTF string data"),
+            (header.blob_offset, header.table_size, "UTF blob data"),
+        ];
+        for (start, end, message) in sections {
+            read_exact_async(reader, &mut data[(start as usize)..(end as usize)])
+                .await
+                .io(message)?;
+        }
+        Self::from_source(Source::Bytes(Cow::Owned(data)), header, options)
+    }
+
+    fn from_source(
+        mut source: Source<'a>,
+        header: Header,
+        options: ReaderOptions,
+    ) -> Result<Reader<'a>> {
+        let available = match &source {
+            Source::Bytes(data) => data.len() as u32,
+            Source::Custom { end, .. } => *end as u32,
+        };
+        let truncated = available < header.table_size;
+        let range = (header.string_offset.min(available) as usize)
+            ..(header.blob_offset.min(available) as usize);
+        // a string cut off by the end of the table is incomplete
+        let complete = |buffer: &[u8]| {
+            if available >= header.blob_offset {
+                buffer.len()
+            } else {
+                buffer
+                    .iter()
+                    .rposition(|&byte| byte == 0)
+                    .map_or(0, |end| end + 1)
+            }
+        };
+        let Strings {
+            decoded: strings,
+            raw: raw_strings,
+        } = match source {
+            Source::Bytes(Cow::Borrowed(data)) => {
+                let buffer = &data[range];
+                parse_strings(&buffer[..complete(buffer)], header.string_offset, &options)?
+            }
+            _ => {
+                let mut buffer = vec![0u8; range.len()];
+                source.read_at(range.start as u64, &mut buffer, "UTF string data")?;
+                buffer.truncate(complete(&buffer));
+                parse_strings(&buffer, header.string_offset, &options)?.into_owned()
+            }
+        };
+        if !strings.contains_key(&header.table_name) && !truncated {
+            return Err(Error::MalformedHeader);
+        }
+        let first_row = options.skip_rows.min(header.row_count);
+        let last_row = match options.max_rows {
+            Some(max_rows) => first_row.saturating_add(max_rows).min(header.row_count),
+            None => header.row_count,
+        };
+        Ok(Reader {
+            source,
+            header,
+            column_position: 24,
+            row_position: header.row_offset + first_row * header.row_size as u32,
+            row_window_end: header.row_offset + last_row * header.row_size as u32,
+            strings,
+            raw_strings,
+            options,
+            bytes_read: 32 + (header.blob_offset - header.string_offset) as u64,
+            progress: None,
+            columns: None,
+            spans: None,
+            truncated,
+            row_cells: RowCells {
+                sources: Vec::new(),
+                irregular: false,
+                stored_size: 0,
+                next: 0,
+                row: first_row,
+            },
+        })
+    }
+
+    /**
+    Returns the number of columns in the table being read
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    assert_eq!(reader.field_count(), 7u16);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn field_count(&self) -> u16 {
+        self.header.field_count
+    }
+
+    /**
+    Returns the name of the table being read
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    assert_eq!(reader.table_name(), "ImportantTable");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn table_name(&self) -> &str {
+        self.strings
+            .get(&self.header.table_name)
+            .map_or("", |name| name)
+    }
+
+    /**
+    Returns whether or not the table being read has the given name
+
+    If the table is truncated (see [`ReaderOptions::allow_truncated`]) and
+    its name was cut off, any name matches, and [`Reader::table_name`] is
+    empty.
+     */
+    pub fn table_name_matches(&self, name: &str) -> bool {
+        self.is_cut_off(self.header.table_name) || self.table_name() == name
+    }
+
+    /**
+    Returns whether or not the table ends before the size its header declares
+    (see [`ReaderOptions::allow_truncated`])
+     */
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /**
+    Returns where the table name is stored in the string data section
+
+    The placement is relative to the first string (usually the NULL
+    sentinel) if the table name is the first or second string, or to the
+    column names if it's right after the last one. Otherwise, its position
+    among the strings is returned. Writing a table with this placement puts
+    the table name in the same place (see [`WriterOptions::table_name_placement`]).
+
+    Returns [`None`] if the table name doesn't start any of the strings (so
+    it isn't placed anywhere in particular).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Reader, TableNamePlacement};
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    assert_eq!(
+        reader.table_name_placement()?,
+        Some(TableNamePlacement::AfterSentinel)
+    );
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn table_name_placement(&mut self) -> Result<Option<TableNamePlacement>> {
+        let mut offsets: Vec<u32> = self
+            .strings
+            .keys()
+            .chain(self.raw_strings.keys())
+            .copied()
+            .collect();
+        offsets.sort_unstable();
+        let table_name = self.header.table_name;
+        let index = match offsets.iter().position(|offset| *offset == table_name) {
+            Some(0) => return Ok(Some(TableNamePlacement::BeforeSentinel)),
+            Some(1) => return Ok(Some(TableNamePlacement::AfterSentinel)),
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        // each column name refers to the first copy of it
+        self.columns()?;
+        let mut last_name = None;
+        for (name, _) in self.columns.as_deref().unwrap_or_default() {
+            let first = offsets
+                .iter()
+                .position(|offset| self.strings.get(offset).is_some_and(|s| s == name));
+            last_name = last_name.max(first);
+        }
+        if last_name.is_some_and(|last| last + 1 == index) {
+            return Ok(Some(TableNamePlacement::AfterColumnNames));
+        }
+        Ok(Some(TableNamePlacement::Position(index)))
+    }
+
+    /**
+    Returns the number of rows in the table being read
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    assert_eq!(reader.row_count(), 1000u32);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn row_count(&self) -> u32 {
+        self.header.row_count
+    }
+
+    /**
+    Returns the size (in bytes) of each row in the table being read
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    assert_eq!(reader.row_size(), 12u16);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn row_size(&self) -> u16 {
+        self.header.row_size
+    }
+
+    /// Returns the offset of the row data section
+    ///
+    /// Like every offset in the header, this is relative to the end of the
+    /// `@UTF` magic and table size (8 bytes into the table).
+    ///
+    pub fn rows_offset(&self) -> u32 {
+        self.header.row_offset
+    }
+
+    /// Returns the offset of the string data section
+    ///
+    /// Like every offset in the header, this is relative to the end of the
+    /// `@UTF` magic and table size (8 bytes into the table).
+    ///
+    pub fn strings_offset(&self) -> u32 {
+        self.header.string_offset
+    }
+
+    /// Returns the offset of the blob data section
+    ///
+    /// Like every offset in the header, this is relative to the end of the
+    /// `@UTF` magic and table size (8 bytes into the table).
+    ///
+    pub fn data_offset(&self) -> u32 {
+        self.header.blob_offset
+    }
+
+    /**
+    Returns the size of the table being read, as declared in its header
+
+    This doesn't include the `@UTF` magic and the size itself, so the table
+    takes up 8 more bytes than this in total.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    let total_size = reader.table_size() as u64 + 8;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn table_size(&self) -> u32 {
+        self.header.table_size
+    }
+
+    /// Returns the size and number of rows of the table being read (see
+    /// [`TableStats`])
+    ///
+    pub fn stats(&self) -> TableStats {
+        self.header.stats()
+    }
+
+    /**
+    Returns statistics about the strings stored in the table
+
+    Every string in the string data section is counted, whether or not it's
+    used by a column or value, and whether or not it can be decoded. Consecutive NULs (like the padding at the end
+    of the section) are counted as a single empty string.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    let stats = reader.string_stats();
+    println!(
+        "{} strings, {:.1}% duplicates ({} bytes)",
+        stats.count,
+        stats.duplicate_ratio() * 100.0,
+        stats.duplicate_bytes
+    );
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn string_stats(&self) -> StringStats {
+        // strings that can't be decoded are compared by their bytes, and
+        // never equal a decoded string
+        let string_key = |offset: &u32| match self.strings.get(offset) {
+            Some(string) => (false, string.as_bytes()),
+            None => (true, &*self.raw_strings[offset]),
+        };
+        let is_empty = |offset: u32| self.strings.get(&offset).is_some_and(|s| s.is_empty());
+        // runs of NULs (like the padding before the blob section) only count
+        // as one empty string
+        let mut offsets: Vec<u32> = self
+            .strings
+            .keys()
+            .chain(self.raw_strings.keys())
+            .copied()
+            .filter(|&offset| !(is_empty(offset) && offset > 0 && is_empty(offset - 1)))
+            .collect();
+        offsets.sort_unstable();
+        let mut seen = HashSet::with_capacity(offsets.len());
+        let mut stats = StringStats {
+            count: offsets.len(),
+            unique: 0,
+            total_bytes: (self.header.blob_offset - self.header.string_offset) as u64,
+            duplicate_bytes: 0,
+        };
+        for (index, offset) in offsets.iter().enumerate() {
+            if seen.insert(string_key(offset)) {
+                stats.unique += 1;
+            } else {
+                // the string's size includes its NUL (and anything skipped
+                // before the next string)
+                let end = match offsets.get(index + 1) {
+                    Some(next) => *next,
+                    None => self.header.blob_offset - self.header.string_offset,
+                };
+                stats.duplicate_bytes += (end - offset) as u64;
+            }
+        }
+        stats
+    }
+
+    /// Returns the size (in bytes) of the blob data section
+    ///
+    pub fn blob_bytes(&self) -> u64 {
+        (self.header.table_size - self.header.blob_offset) as u64
+    }
+
+    /**
+    Returns options that write the table back exactly as it's laid out
+
+    The byte order and string encoding are copied from the reader, and the
+    string and blob data sections are recorded in an [`OriginalLayout`]. If
+    the same values are written in the same order (like when a table read
+    with the `utf_table` macro is written back unmodified), the result is
+    byte-identical to the original table. Otherwise, only the strings and
+    blobs that changed are added.
+
+    This doesn't affect the position of the column or row buffers.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Reader, Table, utf_table};
+    # #[utf_table]
+    # struct Tab { value: u32 }
+    let data = std::fs::read("random-table.bin")?;
+    let mut reader = Reader::from_bytes(&data)?;
+    let options = reader.round_trip_options()?;
+    let mut table = Tab::read_from_reader(reader)?;
+    table.rows[0].value = 100;
+    let mut patched = Vec::new();
+    table.write_with_options(&mut patched, options)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn round_trip_options(&mut self) -> Result<WriterOptions> {
+        let header = self.header;
+        let mut string_data = vec![0u8; (header.blob_offset - header.string_offset) as usize];
+        self.source.read_at(
+            header.string_offset as u64,
+            &mut string_data,
+            "UTF string data",
+        )?;
+        let mut blob_data = vec![0u8; (header.table_size - header.blob_offset) as usize];
+        self.source
+            .read_at(header.blob_offset as u64, &mut blob_data, "UTF blob data")?;
+        if self.columns.is_none() {
+            self.columns = Some(self.scan_columns()?);
+        }
+        let columns: Vec<ColumnLocation> = self
+            .columns
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|(_, location)| *location)
+            .collect();
+        // references are recorded in the order a writer makes them: the table
+        // name, each column's name (and constant value), then each row
+        let mut string_refs = vec![header.table_name];
+        let mut blob_refs = Vec::new();
+        let mut buffer = [0u8; 8];
+        let mut record = |reader: &mut Self, offset: u32, kind: Option<ValueKind>| {
+            match kind {
+                Some(ValueKind::STR) => {
+                    reader
+                        .source
+                        .read_at(offset as u64, &mut buffer[..4], "UTF table data")?;
+                    let endianness = reader.options.endianness;
+                    string_refs.push(endianness.u32_from(buffer[..4].try_into().unwrap()));
+                }
+                Some(ValueKind::BLOB) => {
+                    reader
+                        .source
+                        .read_at(offset as u64, &mut buffer, "UTF table data")?;
+                    let endianness = reader.options.endianness;
+                    blob_refs.push((
+                        endianness.u32_from(buffer[0..4].try_into().unwrap()),
+                        endianness.u32_from(buffer[4..8].try_into().unwrap()),
+                    ));
+                }
+                _ => {}
+            }
+            Ok::<(), Error>(())
+        };
+        let mut offset = 24u32;
+        for column in &columns {
+            record(self, offset + 1, Some(ValueKind::STR))?;
+            offset += 5;
+            if column.constant_offset.is_some() {
+                record(self, offset, column.value_kind)?;
+                offset += column.size;
+            }
+        }
+        for row in 0..header.row_count {
+            for column in &columns {
+                if let Some(row_offset) = column.row_offset {
+                    let position =
+                        header.row_offset + row * header.row_size as u32 + row_offset as u32;
+                    record(self, position, column.value_kind)?;
+                }
+            }
+        }
+        // anything after the last NUL is padding (or garbage)
+        let strings_end = string_data
+            .iter()
+            .rposition(|b| *b == 0)
+            .map_or(0, |p| p + 1);
+        let string_padding = string_data.split_off(strings_end);
+        let strings = self
+            .strings
+            .iter()
+            .filter(|(offset, _)| (**offset as usize) < strings_end)
+            .map(|(offset, string)| (*offset, string.to_string()))
+            .collect();
+        Ok(WriterOptions {
+            endianness: self.options.endianness,
+            encoding: self.options.encoding,
+            table_name_placement: self.table_name_placement()?.unwrap_or_default(),
+            original_layout: Some(Arc::new(OriginalLayout {
+                string_data,
+                string_padding,
+                strings,
+                string_refs,
+                blob_data,
+                blob_refs,
+            })),
+            ..Default::default()
+        })
+    }
+
+    /**
+    Returns the number of bytes of the table that have been decoded so far
+
+    This includes the header and the string data section (which are decoded
+    up front), and every value and blob read since. Once the whole table has
+    been read, it will be close to (but may not exactly match) the table's
+    total size, since unused bytes like padding are never decoded.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = File::open("random-table.bin")?;
+    let reader = Reader::new(&mut file)?;
+    // ... table reading code ...
+    println!("read {} of {} bytes", reader.bytes_read(), reader.table_size() + 8);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /**
+    Sets a callback which is called every time a full row has been read
+
+    The callback receives the number of bytes decoded so far (see
+    [`Reader::bytes_read`]) and the total size of the table, which is enough
+    to drive a progress bar. The reader can then be passed to
+    [`Table::read_from_reader`](crate::Table::read_from_reader).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let file = File::open("huge-table.bin")?;
+    let mut reader = Reader::new_seek(file)?;
+    reader.on_progress(|read, total| {
+        println!("{:.1}%", read as f64 / total as f64 * 100.0);
+    });
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn on_progress(&mut self, callback: impl FnMut(u64, u64) + 'a) {
+        self.progress = Some(Box::new(callback));
+    }
+
+    /**
+    Passes the raw bytes of every section of the table to `hook`, in the
+    order they are stored
+
+    This is meant for computing checksums or hashes of a table while it's
+    being read. If the table has already been loaded into memory (which is
+    the case for every `Reader` except [`Reader::new_seek`]), the sections are
+    passed as-is. Otherwise, they're read from the stream, and a section may
+    be passed in multiple consecutive pieces.
+
+    This doesn't affect the position of the column or row buffers.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Reader, TableSection};
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    let mut checksum = 0u32;
+    reader.inspect_sections(|section, bytes| {
+        if section != TableSection::Blobs {
+            checksum = bytes.iter().fold(checksum, |sum, b| sum.wrapping_add(*b as u32));
+        }
+    })?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn inspect_sections(&mut self, mut hook: impl FnMut(TableSection, &[u8])) -> Result<()> {
+        let header = self.header;
+        hook(
+            TableSection::Header,
+            &header.to_bytes(self.options.endianness),
+        );
+        let sections = [
+            (
+                TableSection::Columns,
+                24,
+                header.row_offset,
+                "UTF column data",
+            ),
+            (
+                TableSection::Rows,
+                header.row_offset,
+                header.string_offset,
+                "UTF row data",
+            ),
+            (
+                TableSection::Strings,
+                header.string_offset,
+                header.blob_offset,
+                "UTF string data",
+            ),
+            (
+                TableSection::Blobs,
+                header.blob_offset,
+                header.table_size,
+                "UTF blob data",
+            ),
+        ];
+        for (section, start, end, message) in sections {
+            match &self.source {
+                Source::Bytes(data) => match data.get((start as usize)..(end as usize)) {
+                    Some(data) => hook(section, data),
+                    None => return Err(Error::EOF(message.to_owned())),
+                },
+                Source::Custom { .. } => {
+                    let mut buffer = vec![0u8; STREAM_CACHE_SIZE];
+                    let mut position = start;
+                    while position < end {
+                        let length = STREAM_CACHE_SIZE.min((end - position) as usize);
+                        self.source
+                            .read_at(position as u64, &mut buffer[..length], message)?;
+                        hook(section, &buffer[..length]);
+                        position += length as u32;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    Starts recording the byte range of every column header and value read
+    from now on
+
+    The returned [`SpanMap`] keeps receiving spans as the reader is used, so
+    it can be inspected after the reader has been passed to something else
+    (like [`Table::read_from_reader`](crate::Table::read_from_reader)) to see
+    exactly where a bad value is located.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Reader, Table, utf_table};
+    # #[utf_table]
+    # struct Tab { value: u32 }
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    let spans = reader.record_spans();
+    if let Err(error) = Tab::read_from_reader(reader) {
+        if let Some(span) = spans.last() {
+            eprintln!("{error} (at 0x{:x})", span.offset);
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn record_spans(&mut self) -> SpanMap {
+        self.spans.get_or_insert_with(SpanMap::default).clone()
+    }
+
+    /**
+    Returns [`true`] if there is more data in the column data section, or
+    [`false`] otherwise.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = std::fs::File::open("random-table.bin")?;
+    let reader = criware_utf_core::Reader::new(&mut file)?;
+    // ... column reading code ...
+    if reader.more_column_data() {
+        panic!();
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn more_column_data(&self) -> bool {
+        self.column_position < self.header.row_offset
+    }
+
+    /**
+    Returns [`true`] if there is more data in the row data section, or
+    [`false`] otherwise.
+
+    If `max_rows` was set in the [`ReaderOptions`], this returns [`false`] once
+    that many rows have been read.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    let mut file = std::fs::File::open("random-table.bin")?;
+    let reader = criware_utf_core::Reader::new(&mut file)?;
+    // ... column reading code ...
+    while reader.more_row_data() {
+        // ... row reading code ...
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn more_row_data(&self) -> bool {
+        if self.header.row_size == 0 && self.row_cells.irregular {
+            return self.row_cells.row < self.row_window().end;
+        }
+        self.row_position < self.row_window_end
+    }
+
+    /**
+    Finds the column with the given name
+
+    The first time this is called, the entire column data section is scanned
+    (without affecting the position of the column buffer), and the results are
+    kept for any later calls. Returns [`None`] if there is no such column.
+
+    Combined with [`Reader::seek_row`] and [`Reader::read_row_cells`], this
+    allows a single column to be read out of a wide table, regardless of the
+    order of its columns.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{ColumnStorageFormat, Reader};
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    if let Some(column) = reader.find_column("FileSize")? {
+        if let Some(offset) = column.row_offset {
+            let cells = reader.read_row_cells(10)?;
+            let size = &cells[(offset as usize)..(offset as usize + 4)];
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn find_column(&mut self, name: &str) -> Result<Option<ColumnLocation>> {
+        if self.columns.is_none() {
+            self.columns = Some(self.scan_columns()?);
+        }
+        Ok(self
+            .columns
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|(column_name, _)| column_name == name)
+            .map(|(_, location)| *location))
+    }
 
-impl Reader {
     /**
-    Creates a new `Reader`
+    Matches the table's columns against the expected columns by name
+
+    Unlike the column reading methods, the columns may be in any order, and
+    expected columns that are missing or unexpected columns that are present
+    are tolerated (see [`MatchReport`]). A column stored as zero matches any
+    expected storage format, since that's how optional columns are stored.
+    A column that is found but stores the wrong kind of data, or uses a
+    different storage format, is an error.
 
-    Preliminary validity checks are performed as well.
+    Like [`Reader::find_column`], this doesn't affect the position of the
+    column buffer. Values can then be read with [`Reader::read_column_value`].
 
     # Example
     ```no_run
     # use std::fs::File;
-    # use criware_utf_core::Reader;
+    # use criware_utf_core::{ColumnStorageFormat, Reader, SchemaColumn, ValueKind};
+    # let expected: Vec<SchemaColumn> = Vec::new();
     let mut file = File::open("random-table.bin")?;
-    let reader = Reader::new(&mut file)?;
+    let mut reader = Reader::new(&mut file)?;
+    let report = reader.read_columns_matching(&expected)?;
+    if report.missing().next().is_some() {
+        println!("some columns are missing");
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn new(reader: &mut dyn Read) -> Result<Reader> {
-        let table_size = {
-            let mut header = [0u8; 8];
-            reader.read_exact(&mut header).io("@UTF header")?;
-            if &header[0..4] != b"@UTF" {
-                return Err(Error::MalformedHeader);
-            }
-            u32::from_be_bytes(header[4..8].try_into().unwrap())
-        };
-        if table_size < 24 {
-            return Err(Error::EOF("@UTF header".to_string()));
-        }
-        let mut header = [0u8; 24];
-        reader.read_exact(&mut header).io("@UTF header")?;
-        let row_offset = u32::from_be_bytes(header[0..4].try_into().unwrap());
-        let string_offset = u32::from_be_bytes(header[4..8].try_into().unwrap());
-        let blob_offset = u32::from_be_bytes(header[8..12].try_into().unwrap());
-        let table_name = u32::from_be_bytes(header[12..16].try_into().unwrap());
-        let field_count = u16::from_be_bytes(header[16..18].try_into().unwrap());
-        let row_size = u16::from_be_bytes(header[18..20].try_into().unwrap());
-        let row_count = u32::from_be_bytes(header[20..24].try_into().unwrap());
-        if 24 > row_offset
-            || row_offset > string_offset
-            || string_offset > blob_offset
-            || blob_offset > table_size
-            || (row_size as u32 * row_count) != string_offset - row_offset
-        {
-            return Err(Error::MalformedHeader);
+    pub fn read_columns_matching(&mut self, expected: &[SchemaColumn]) -> Result<MatchReport> {
+        if self.columns.is_none() {
+            self.columns = Some(self.scan_columns()?);
         }
-        let (column_buffer, column_buffer_size) = {
-            let mut buffer = vec![0u8; row_offset as usize - 24];
-            reader.read_exact(&mut buffer).io("UTF column data")?;
-            let len = buffer.len();
-            (Cursor::new(buffer), len)
-        };
-        let (row_buffer, row_buffer_size) = {
-            let mut buffer = vec![0u8; (string_offset - row_offset) as usize];
-            reader.read_exact(&mut buffer).io("UTF row data")?;
-            let len = buffer.len();
-            (Cursor::new(buffer), len)
+        let columns = self.columns.as_ref().unwrap();
+        let mut report = MatchReport {
+            columns: Vec::with_capacity(expected.len()),
+            extra: columns
+                .iter()
+                .filter(|(name, _)| !expected.iter().any(|column| &column.name == name))
+                .map(|(name, _)| name.clone())
+                .collect(),
         };
-        let strings = {
-            let mut buffer = vec![0u8; (blob_offset - string_offset) as usize];
-            reader.read_exact(&mut buffer).io("UTF string data")?;
-            let mut strings = HashMap::new();
-            let mut start = 0;
-            let mut index = 0;
-            while index < buffer.len() {
-                if buffer[index] == 0 {
-                    match std::str::from_utf8(&buffer[(start as usize)..index]) {
-                        Ok(value) => strings.insert(start, value.to_owned()),
-                        Err(error) => return Err(Error::StringMalformed(error)),
+        for column in expected {
+            let location = columns
+                .iter()
+                .find(|(name, _)| name == &column.name)
+                .map(|(_, location)| *location);
+            if let Some(location) = location {
+                if location.value_kind != Some(column.value_kind) {
+                    return Err(Error::WrongColumnType(
+                        location.type_flag,
+                        column.value_kind as u8,
+                    ));
+                }
+                if location.storage_format != column.storage_format
+                    && location.storage_format != ColumnStorageFormat::Zero
+                {
+                    let expected = match column.storage_format {
+                        ColumnStorageFormat::Zero => "0x10",
+                        ColumnStorageFormat::Constant => "0x30",
+                        ColumnStorageFormat::Rowed => "0x50",
                     };
-                    start = (index + 1) as u32;
+                    return Err(Error::WrongColumnStorage(
+                        location.storage_format.flag(),
+                        expected,
+                    ));
                 }
-                index += 1;
             }
-            strings
-        };
-        if !strings.contains_key(&table_name) {
-            return Err(Error::MalformedHeader);
+            report.columns.push(location);
         }
-        let mut blobs = vec![0u8; (table_size - blob_offset) as usize];
-        reader.read_exact(&mut blobs).io("UTF blob data")?;
-        Ok(Reader {
-            column_buffer,
-            column_buffer_size,
-            row_buffer,
-            row_buffer_size,
-            strings,
-            blobs,
-            table_name_index: table_name,
-            field_count,
-        })
+        Ok(report)
     }
 
     /**
-    Returns the number of columns in the table being read
+    Reads the value of a column found with [`Reader::find_column`] or
+    [`Reader::read_columns_matching`]
+
+    For a rowed column, the value in the given row is read. For a constant
+    column, `row` is ignored. If the column is stored as zero, there is no
+    value, and [`None`] is returned.
+
+    This doesn't affect the position of the column or row buffers.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
     let mut file = File::open("random-table.bin")?;
-    let reader = Reader::new(&mut file)?;
-    assert_eq!(reader.field_count(), 7u16);
+    let mut reader = Reader::new(&mut file)?;
+    if let Some(column) = reader.find_column("FileName")? {
+        let name: Option<String> = reader.read_column_value(&column, 42)?;
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn field_count(&self) -> u16 {
-        self.field_count
+    pub fn read_column_value<T: Value>(
+        &mut self,
+        column: &ColumnLocation,
+        row: u32,
+    ) -> Result<Option<T>> {
+        let type_flag = const { primitive_type_flag::<T::Primitive>() };
+        if column.type_flag != type_flag {
+            return Err(Error::WrongColumnType(column.type_flag, type_flag));
+        }
+        let (offset, kind) = match self.column_value_position(column, row)? {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        let value = self.read_primitive_at::<T::Primitive>(offset, kind, None)?;
+        match T::from_primitive(value) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => {
+                let error = Error::ValueConversion(
+                    std::any::type_name::<T::Primitive>(),
+                    std::any::type_name::<T>(),
+                    error,
+                );
+                let name = match self.columns() {
+                    Ok(columns) => columns.get(column.index as usize).map(|(name, _)| name),
+                    Err(_) => None,
+                };
+                Err(match name {
+                    Some(name) => Error::InColumn(
+                        name.clone(),
+                        column.row_offset.map(|_| row),
+                        Box::new(error),
+                    ),
+                    None => error,
+                })
+            }
+        }
+    }
+
+    fn scan_columns(&mut self) -> Result<Vec<(String, ColumnLocation)>> {
+        let mut columns = Vec::with_capacity(self.header.field_count as usize);
+        let mut offset = 24u32;
+        let mut row_offset = 0u32;
+        let mut unknown_rowed = None;
+        while offset < self.header.row_offset {
+            let mut buffer = [0u8; 5];
+            if offset as u64 + 5 > self.header.row_offset as u64 {
+                return Err(Error::EOF("UTF column data".to_owned()));
+            }
+            self.source
+                .read_at(offset as u64, &mut buffer, "UTF column data")?;
+            offset += 5;
+            let flag = buffer[0];
+            let name = self.string_at(
+                self.options
+                    .endianness
+                    .u32_from(buffer[1..5].try_into().unwrap()),
+            )?;
+            let type_flag = flag & 0x0f;
+            let value_kind = ValueKind::from_flag(type_flag);
+            if value_kind.is_none() && !self.options.preserve_unknown_types {
+                return Err(Error::InvalidColumnType(type_flag));
+            }
+            let index = columns.len() as u16;
+            let mut size = value_kind.map_or(0, ValueKind::size);
+            let (storage_format, column_row_offset, constant_offset) = match flag & 0xf0 {
+                0x10 => (ColumnStorageFormat::Zero, None, None),
+                0x30 => {
+                    if value_kind.is_none() {
+                        // only the last column's size can be inferred
+                        if index + 1 != self.header.field_count {
+                            return Err(Error::InvalidColumnType(type_flag));
+                        }
+                        size = self.header.row_offset.saturating_sub(offset);
+                    }
+                    let position = 8 + offset as u64;
+                    offset += size;
+                    (ColumnStorageFormat::Constant, None, Some(position))
+                }
+                0x50 => {
+                    if value_kind.is_none() {
+                        if unknown_rowed.is_some() {
+                            return Err(Error::InvalidColumnType(type_flag));
+                        }
+                        unknown_rowed = Some(columns.len());
+                    }
+                    // rowed columns can't reach past the 16-bit row size
+                    let position = u16::try_from(row_offset).map_err(|_| Error::MalformedHeader)?;
+                    row_offset += size;
+                    (ColumnStorageFormat::Rowed, Some(position), None)
+                }
+                v => return Err(Error::InvalidColumnStorage(v)),
+            };
+            columns.push((
+                name,
+                ColumnLocation {
+                    index,
+                    value_kind,
+                    type_flag,
+                    size,
+                    storage_format,
+                    row_offset: column_row_offset,
+                    constant_offset,
+                },
+            ));
+        }
+        if offset > self.header.row_offset {
+            return Err(Error::EOF("UTF column data".to_owned()));
+        }
+        if let Some(unknown) = unknown_rowed {
+            // the unknown column takes up whatever the others don't
+            let size = match (self.header.row_size as u32).checked_sub(row_offset) {
+                Some(size) => size,
+                None => return Err(Error::MalformedHeader),
+            };
+            columns[unknown].1.size = size;
+            for (_, location) in &mut columns[(unknown + 1)..] {
+                if let Some(position) = &mut location.row_offset {
+                    *position += size as u16;
+                }
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Reads the flag and name of every column, without checking the flags
+    ///
+    /// Constant values are skipped over, so this stops after a column whose
+    /// storage or (constant) type is undefined, since the size of what it
+    /// stores can't be known.
+    pub(crate) fn column_flags(&mut self) -> Result<Vec<(u8, String)>> {
+        let mut columns = Vec::with_capacity(self.header.field_count as usize);
+        let mut offset = 24u32;
+        while (offset as u64) + 5 <= self.header.row_offset as u64 {
+            let mut buffer = [0u8; 5];
+            self.source
+                .read_at(offset as u64, &mut buffer, "UTF column data")?;
+            offset += 5;
+            let name = self.string_at(
+                self.options
+                    .endianness
+                    .u32_from(buffer[1..5].try_into().unwrap()),
+            )?;
+            let flag = buffer[0];
+            columns.push((flag, name));
+            match (flag & 0xf0, ValueKind::from_flag(flag & 0x0f)) {
+                (0x10 | 0x50, _) => {}
+                (0x30, Some(kind)) => offset += kind.size(),
+                _ => break,
+            }
+        }
+        Ok(columns)
     }
 
     /**
-    Returns the name of the table being read
+    Reads the value of a column found with [`Reader::find_column`] or
+    [`Reader::read_columns_matching`], whatever kind it is
+
+    This works like [`Reader::read_column_value`], except values with
+    unknown type flags are read as [`AnyValue::Unknown`].
 
     # Example
     ```no_run
     # use std::fs::File;
-    # use criware_utf_core::Reader;
+    # use criware_utf_core::{AnyValue, Reader};
     let mut file = File::open("random-table.bin")?;
-    let reader = Reader::new(&mut file)?;
-    assert_eq!(reader.table_name(), "ImportantTable");
+    let mut reader = Reader::new(&mut file)?;
+    if let Some(column) = reader.find_column("FileName")? {
+        if let Some(AnyValue::String(name)) = reader.read_any(&column, 42)? {
+            println!("{name}");
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn table_name<'a>(&'a self) -> &'a str {
-        self.strings.get(&self.table_name_index).unwrap().as_str()
+    pub fn read_any(&mut self, column: &ColumnLocation, row: u32) -> Result<Option<AnyValue>> {
+        let (offset, kind) = match self.column_value_position(column, row)? {
+            Some(position) => position,
+            None => return Ok(None),
+        };
+        Ok(Some(match column.value_kind {
+            Some(value_kind) => {
+                read_any_kind!(value_kind, self.read_primitive_at(offset, kind, None))
+            }
+            None => {
+                let mut bytes = vec![0u8; column.size as usize];
+                self.source
+                    .read_at(offset as u64, &mut bytes, "UTF table data")?;
+                self.bytes_read += column.size as u64;
+                if let Some(spans) = &self.spans {
+                    spans.push(Span {
+                        offset: 8 + offset as u64,
+                        length: column.size,
+                        kind,
+                    });
+                }
+                AnyValue::Unknown {
+                    flag: column.type_flag,
+                    bytes,
+                }
+            }
+        }))
+    }
+
+    /// Returns the position of a column's value in the given row (or its
+    /// constant value), or [`None`] if the column is stored as zero
+    fn column_value_position(
+        &self,
+        column: &ColumnLocation,
+        row: u32,
+    ) -> Result<Option<(u32, SpanKind)>> {
+        Ok(match (column.constant_offset, column.row_offset) {
+            (Some(offset), _) => Some(((offset - 8) as u32, SpanKind::ConstantValue)),
+            (None, Some(offset)) => {
+                if row >= self.header.row_count {
+                    return Err(Error::RowOutOfRange(row, self.header.row_count));
+                }
+                Some((
+                    self.header.row_offset + row * self.header.row_size as u32 + offset as u32,
+                    SpanKind::RowCell { row },
+                ))
+            }
+            (None, None) => None,
+        })
     }
 
     /**
-    Returns [`true`] if there is more data in the column data section, or
-    [`false`] otherwise.
+    Moves the row buffer to the start of the given row, so the next values
+    read from it belong to that row
+
+    Since every row is the same size, this doesn't need to decode any of the
+    rows before it. Seeking to the row count (one past the last row) is
+    allowed, and leaves no more row data.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
-    let mut file = std::fs::File::open("random-table.bin")?;
-    let reader = criware_utf_core::Reader::new(&mut file)?;
-    // ... column reading code ...
-    if reader.more_column_data() {
-        panic!();
-    }
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    reader.read_rowed_column::<u32>("Id")?;
+    reader.read_rowed_column::<String>("Name")?;
+    reader.seek_row(500)?;
+    let id: u32 = reader.read_value(true)?;
+    let name: String = reader.read_value(true)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn more_column_data(&self) -> bool {
-        (self.column_buffer.position() as usize) < self.column_buffer_size
+    pub fn seek_row(&mut self, index: u32) -> Result<()> {
+        if index > self.header.row_count {
+            return Err(Error::RowOutOfRange(index, self.header.row_count));
+        }
+        self.row_position = self.header.row_offset + index * self.header.row_size as u32;
+        self.row_cells.next = 0;
+        self.row_cells.row = index;
+        Ok(())
     }
 
     /**
-    Returns [`true`] if there is more data in the row data section, or
-    [`false`] otherwise.
+    Returns the raw bytes of the given row (each of its cells, back to back)
+
+    This doesn't affect the position of the row buffer.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
-    let mut file = std::fs::File::open("random-table.bin")?;
-    let reader = criware_utf_core::Reader::new(&mut file)?;
-    // ... column reading code ...
-    while reader.more_row_data() {
-        // ... row reading code ...
-    }
+    let mut file = File::open("random-table.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    let cells = reader.read_row_cells(500)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn more_row_data(&self) -> bool {
-        (self.row_buffer.position() as usize) < self.row_buffer_size
+    pub fn read_row_cells(&mut self, index: u32) -> Result<Vec<u8>> {
+        if index >= self.header.row_count {
+            return Err(Error::RowOutOfRange(index, self.header.row_count));
+        }
+        let row_size = self.header.row_size as u32;
+        let mut cells = vec![0u8; row_size as usize];
+        self.source.read_at(
+            (self.header.row_offset + index * row_size) as u64,
+            &mut cells,
+            "UTF row data",
+        )?;
+        Ok(cells)
     }
 
     fn read_constant_column_private<T: Value>(
         &mut self,
         name: &'static str,
         optional: bool,
+        hook: Option<&CellHook>,
     ) -> Result<Option<T>> {
-        let flag = self.read_primitive::<u8>(false)?;
-        let column_name = self.read_primitive::<str>(false)?;
-        if column_name != name {
+        let (flag, column_name) = self.read_column_header_private()?;
+        if let Some(column_name) = column_name
+            && column_name != name
+        {
             return Err(Error::WrongColumnName(column_name, name));
         }
         let type_flag = flag & 0x0f;
         let storage_flag = flag & 0xf0;
-        handle_type_flag!(type_flag => T::Primitive::TYPE_FLAG);
+        handle_type_flag!(type_flag => const { primitive_type_flag::<T::Primitive>() });
         if storage_flag == 0x30 {
-            Ok(Some(self.read_value(false)?))
+            Ok(Some(self.read_value_private(false, hook)?))
+        } else if storage_flag == 0x50 {
+            Ok(Some(self.read_rowed_constant(name, hook)?))
         } else if optional && storage_flag == 0x10 {
             Ok(None)
         } else if is_valid_storage_flag(storage_flag) {
-            return Err(Error::WrongColumnStorage(storage_flag, "0x30"));
+            Err(Error::WrongColumnStorage(storage_flag, "0x30"))
         } else {
-            return Err(Error::InvalidColumnStorage(storage_flag));
+            Err(Error::InvalidColumnStorage(storage_flag))
         }
     }
 
@@ -229,20 +2099,56 @@ impl Reader {
     Attempts to read a constant column with the given name and type.
 
     If the column matches, the column's value is returned. If the next column
-    stored does not match, an error is returned.
+    stored does not match, an error is returned. A rowed column (like one
+    promoted by
+    [`ColumnPromotion::ConstantToRowed`](crate::ColumnPromotion::ConstantToRowed))
+    also matches if its value is the same in every row, and its cells are
+    skipped over when the rows are read.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
     # let mut file = std::fs::File::open("random-table.bin")?;
-    # let reader = criware_utf_core::Reader::new(&mut file)?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
     let file_count: u64 = reader.read_constant_column("FileCount")?;
     let version: String = reader.read_constant_column("Version")?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read_constant_column<T: Value>(&mut self, name: &'static str) -> Result<T> {
-        Ok(self.read_constant_column_private(name, false)?.unwrap())
+        Ok(self
+            .read_constant_column_private(name, false, None)?
+            .unwrap())
+    }
+
+    /**
+    Attempts to read a constant column with the given name and type, passing
+    the raw bytes of its value through `hook` before they are decoded.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    # let mut file = std::fs::File::open("random-table.bin")?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
+    // this game uses 0xFFFFFFFF where 0 is meant
+    let file_count: u32 = reader.read_constant_column_with_hook("FileCount", &|bytes| {
+        if bytes == [0xff; 4] {
+            bytes.fill(0);
+        }
+    })?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_constant_column_with_hook<T: Value>(
+        &mut self,
+        name: &'static str,
+        hook: &CellHook,
+    ) -> Result<T> {
+        Ok(self
+            .read_constant_column_private(name, false, Some(hook))?
+            .unwrap())
     }
 
     /**
@@ -250,65 +2156,100 @@ impl Reader {
 
     If the name and type of value of the next column stored does not match, this
     function will return an error. The storage method of the column may be
-    constant or zero. If it's constant, the column's value is returned.
+    constant or zero. If it's constant, the column's value is returned. Like
+    [`Reader::read_constant_column`], a rowed column whose value is the same in
+    every row counts as constant.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
     # let mut file = std::fs::File::open("random-table.bin")?;
-    # let reader = criware_utf_core::Reader::new(&mut file)?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
     let file_count: u64 = reader.read_constant_column("FileCount")?;
     let version: String = reader.read_constant_column("Version")?;
     let crc32: Option<u32> = reader.read_constant_column_opt::<u32>("Crc")?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read_constant_column_opt<T: Value>(&mut self, name: &'static str) -> Result<Option<T>> {
-        self.read_constant_column_private(name, true)
+        self.read_constant_column_private(name, true, None)
+    }
+
+    /**
+    Attempts to read an optional constant column with the given name and type,
+    passing the raw bytes of its value (if there is one) through `hook` before
+    they are decoded.
+
+    See [`Reader::read_constant_column_with_hook`] and
+    [`Reader::read_constant_column_opt`].
+     */
+    pub fn read_constant_column_opt_with_hook<T: Value>(
+        &mut self,
+        name: &'static str,
+        hook: &CellHook,
+    ) -> Result<Option<T>> {
+        self.read_constant_column_private(name, true, Some(hook))
     }
 
-    fn read_rowed_column_private(
+    fn read_rowed_column_private<P: Primitive + ?Sized>(
         &mut self,
         name: &'static str,
-        kind: ValueKind,
         optional: bool,
     ) -> Result<bool> {
-        let flag = self.read_primitive::<u8>(false)?;
-        let column_name = self.read_primitive::<str>(false)?;
-        if column_name != name {
+        let (flag, column_name) = self.read_column_header_private()?;
+        if let Some(column_name) = column_name
+            && column_name != name
+        {
             return Err(Error::WrongColumnName(column_name, name));
         }
         let type_flag = flag & 0x0f;
         let storage_flag = flag & 0xf0;
-        handle_type_flag!(type_flag => kind);
+        handle_type_flag!(type_flag => const { primitive_type_flag::<P>() });
+        let size = P::SIZE_IN_UTF as u32;
         if storage_flag == 0x50 {
+            self.row_cells.sources.push(RowCell::Rowed);
+            self.row_cells.stored_size += size;
+            Ok(true)
+        } else if storage_flag == 0x30 {
+            let offset = self.column_position;
+            if offset as u64 + size as u64 > self.header.row_offset as u64 {
+                return Err(Error::EOF("UTF column data".to_owned()));
+            }
+            self.column_position += size;
+            self.row_cells.sources.push(RowCell::Constant(offset));
+            self.row_cells.irregular = true;
             Ok(true)
         } else if optional && storage_flag == 0x10 {
             Ok(false)
         } else if is_valid_storage_flag(storage_flag) {
-            return Err(Error::WrongColumnStorage(storage_flag, "0x50"));
+            Err(Error::WrongColumnStorage(storage_flag, "0x50"))
         } else {
-            return Err(Error::InvalidColumnStorage(storage_flag));
+            Err(Error::InvalidColumnStorage(storage_flag))
         }
     }
 
     /**
     Attempts to read a rowed column with the given name and type.
 
-    If the next column stored does not match, an error is returned.
+    If the next column stored does not match, an error is returned. A column
+    stored as a constant (like one promoted by
+    [`ColumnPromotion::RowedToConstant`](crate::ColumnPromotion::RowedToConstant))
+    also matches, and its value is read in every row.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
     # let mut file = std::fs::File::open("random-table.bin")?;
-    # let reader = criware_utf_core::Reader::new(&mut file)?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
     reader.read_constant_column::<i32>("ID")?;
     reader.read_constant_column::<String>("Name")?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read_rowed_column<T: Value>(&mut self, name: &'static str) -> Result<()> {
-        self.read_rowed_column_private(name, T::Primitive::TYPE_FLAG, false)?;
+        self.read_rowed_column_private::<T::Primitive>(name, false)?;
         Ok(())
     }
 
@@ -318,51 +2259,343 @@ impl Reader {
     If the name and type of value of the next column stored does not match, this
     function will return an error. The storage method of the column may be
     rowed or zero. [`true`] denotes that the column is rowed, [`false`] denotes
-    the column is zero.
+    the column is zero. Like [`Reader::read_rowed_column`], a column stored as
+    a constant counts as rowed.
 
     # Example
     ```no_run
     # use std::fs::File;
     # use criware_utf_core::Reader;
     # let mut file = std::fs::File::open("random-table.bin")?;
-    # let reader = criware_utf_core::Reader::new(&mut file)?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
     let crc_included: bool = reader.read_rowed_column_opt::<u32>("Crc")?;
     if crc_included {
         println!("CRC32 checksums are included with each file!");
     } else {
         println!("No checksums found");
     }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read_rowed_column_opt<T: Value>(&mut self, name: &'static str) -> Result<bool> {
-        self.read_rowed_column_private(name, T::Primitive::TYPE_FLAG, true)
+        self.read_rowed_column_private::<T::Primitive>(name, true)
     }
 
-    fn read_primitive<T: Primitive + ?Sized>(&mut self, row: bool) -> Result<T::Owned> {
-        let mut buffer: T::Buffer = Default::default();
-        let reader = if row {
-            &mut self.row_buffer
+    fn read_primitive<T: Primitive + ?Sized>(
+        &mut self,
+        row: bool,
+        hook: Option<&CellHook>,
+    ) -> Result<T::Owned> {
+        let irregular = row && self.row_cells.irregular;
+        if irregular {
+            self.skip_row_cells(false);
+            let source = self.row_cells.sources.get(self.row_cells.next).copied();
+            self.row_cells.advance();
+            if let Some(RowCell::Constant(offset)) = source {
+                let value = self.read_primitive_at::<T>(offset, SpanKind::ConstantValue, hook)?;
+                self.skip_row_cells(true);
+                return Ok(value);
+            }
+        }
+        let size = T::SIZE_IN_UTF as u32;
+        let (position, end) = if row {
+            (&mut self.row_position, self.header.rows_end())
         } else {
-            &mut self.column_buffer
+            (&mut self.column_position, self.header.row_offset)
         };
-        match reader.read_exact(buffer.as_mut()) {
-            Ok(()) => (),
-            Err(error) => match error.kind() {
-                std::io::ErrorKind::UnexpectedEof => {
-                    return Err(Error::EOF(format!(
-                        "reading {} value",
-                        std::any::type_name::<T>()
-                    )));
-                }
-                _ => return Err(Error::IOError(error)),
-            },
+        let offset = *position;
+        if offset as u64 + size as u64 > end as u64 {
+            return Err(Error::EOF(format!(
+                "reading {} value",
+                std::any::type_name::<T>()
+            )));
+        }
+        *position += size;
+        let kind = if row {
+            SpanKind::RowCell {
+                row: (offset - self.header.row_offset) / self.header.row_size as u32,
+            }
+        } else {
+            SpanKind::ConstantValue
+        };
+        let value = self.read_primitive_at::<T>(offset, kind, hook)?;
+        if irregular {
+            self.skip_row_cells(true);
+        }
+        if row
+            && (self.row_position - self.header.row_offset)
+                .is_multiple_of(self.header.row_size as u32)
+            && let Some(progress) = &mut self.progress
+        {
+            progress(self.bytes_read, self.header.table_size as u64 + 8);
+        }
+        Ok(value)
+    }
+
+    /// Skips over the next cells in the row that belong to columns read as
+    /// constants, stopping at the end of the row if `stop_at_row_end` is set
+    fn skip_row_cells(&mut self, stop_at_row_end: bool) {
+        let cells = &mut self.row_cells;
+        for _ in 0..cells.sources.len() {
+            let Some(RowCell::Skipped(size)) = cells.sources.get(cells.next) else {
+                break;
+            };
+            self.row_position += size;
+            cells.advance();
+            if stop_at_row_end && cells.next == 0 {
+                break;
+            }
+        }
+    }
+
+    /// Reads the value of a rowed column that's read as a constant, which has
+    /// to be the same in every row
+    ///
+    /// The column's cells are skipped over when the rows are read.
+    fn read_rowed_constant<T: Value>(
+        &mut self,
+        name: &'static str,
+        hook: Option<&CellHook>,
+    ) -> Result<T> {
+        let size = T::Primitive::SIZE_IN_UTF as u32;
+        let row_size = self.header.row_size as u32;
+        let offset = self.row_cells.stored_size;
+        if offset + size > row_size {
+            return Err(Error::EOF("UTF row data".to_owned()));
+        }
+        if self.header.row_count == 0 {
+            return Err(Error::WrongColumnStorage(0x50, "0x30"));
+        }
+        let position = |row: u32| (self.header.row_offset + row * row_size + offset) as u64;
+        let mut first = vec![0u8; size as usize];
+        let mut cell = vec![0u8; size as usize];
+        self.source
+            .read_at(position(0), &mut first, "UTF row data")?;
+        for row in 1..self.header.row_count {
+            self.source
+                .read_at(position(row), &mut cell, "UTF row data")?;
+            if cell != first {
+                return Err(Error::WrongColumnStorage(0x50, "0x30"));
+            }
+        }
+        self.row_cells.sources.push(RowCell::Skipped(size));
+        self.row_cells.stored_size += size;
+        self.row_cells.irregular = true;
+        let primitive = self.read_primitive_at::<T::Primitive>(
+            self.header.row_offset + offset,
+            SpanKind::RowCell { row: 0 },
+            hook,
+        )?;
+        T::from_primitive(primitive).map_err(|error| {
+            Error::InColumn(
+                name.to_owned(),
+                None,
+                Box::new(Error::ValueConversion(
+                    std::any::type_name::<T::Primitive>(),
+                    std::any::type_name::<T>(),
+                    error,
+                )),
+            )
+        })
+    }
+
+    /// Reads a value at the given offset, without checking its bounds
+    fn read_primitive_at<T: Primitive + ?Sized>(
+        &mut self,
+        offset: u32,
+        kind: SpanKind,
+        hook: Option<&CellHook>,
+    ) -> Result<T::Owned> {
+        let mut buffer: T::Buffer = Default::default();
+        let size = buffer.as_ref().len() as u32;
+        self.source
+            .read_at(offset as u64, buffer.as_mut(), "UTF table data")?;
+        self.bytes_read += size as u64;
+        if let Some(spans) = &self.spans {
+            spans.push(Span {
+                offset: 8 + offset as u64,
+                length: size,
+                kind,
+            });
+        }
+        if let Some(hook) = hook {
+            hook(buffer.as_mut());
+        }
+        <T as Primitive>::parse(buffer, self)
+    }
+
+    /// Reads the flag and name of the next column in the column buffer
+    pub(crate) fn read_column_header(&mut self) -> Result<(u8, String)> {
+        let (flag, name) = self.read_column_header_private()?;
+        match name {
+            Some(name) => Ok((flag, name)),
+            None => Err(Error::EOF("UTF string data".to_owned())),
+        }
+    }
+
+    /// Reads the flag and name of the next column in the column buffer, or
+    /// just its flag if the name was cut off from a truncated table
+    fn read_column_header_private(&mut self) -> Result<(u8, Option<String>)> {
+        let offset = self.column_position;
+        if offset as u64 + 5 > self.header.row_offset as u64 {
+            return Err(Error::EOF("reading column header".to_owned()));
+        }
+        let mut buffer = [0u8; 5];
+        self.source
+            .read_at(offset as u64, &mut buffer, "UTF column data")?;
+        self.column_position += 5;
+        self.bytes_read += 5;
+        if let Some(spans) = &self.spans {
+            spans.push(Span {
+                offset: 8 + offset as u64,
+                length: 5,
+                kind: SpanKind::ColumnHeader,
+            });
+        }
+        let offset = self
+            .options
+            .endianness
+            .u32_from(buffer[1..5].try_into().unwrap());
+        if self.is_cut_off(offset) {
+            return Ok((buffer[0], None));
+        }
+        Ok((buffer[0], Some(self.string_at(offset)?)))
+    }
+
+    /// Returns the options the table is being read with
+    pub(crate) fn options(&self) -> &ReaderOptions {
+        &self.options
+    }
+
+    /// Returns the rows visited by [`Reader::more_row_data`] loops (see
+    /// [`ReaderOptions::skip_rows`] and [`ReaderOptions::max_rows`])
+    pub(crate) fn row_window(&self) -> std::ops::Range<u32> {
+        let first_row = self.options.skip_rows.min(self.header.row_count);
+        let last_row = match self.options.max_rows {
+            Some(max_rows) => first_row
+                .saturating_add(max_rows)
+                .min(self.header.row_count),
+            None => self.header.row_count,
         };
-        match <T as Primitive>::parse(buffer, &self.strings, &self.blobs) {
-            Some(prim) => Ok(prim),
+        first_row..last_row
+    }
+
+    /// Returns every column in the table, along with its name
+    pub(crate) fn columns(&mut self) -> Result<&[(String, ColumnLocation)]> {
+        if self.columns.is_none() {
+            self.columns = Some(self.scan_columns()?);
+        }
+        Ok(self.columns.as_deref().unwrap())
+    }
+
+    /// Returns the byte order of the table being read
+    pub(crate) fn endianness(&self) -> Endianness {
+        self.options.endianness
+    }
+
+    /// Returns whether or not the string at the given offset in the string
+    /// data section was cut off from a truncated table
+    fn is_cut_off(&self, offset: u32) -> bool {
+        self.truncated
+            && !self.strings.contains_key(&offset)
+            && !self.raw_strings.contains_key(&offset)
+    }
+
+    /// Returns the string at the given offset in the string data section
+    pub(crate) fn string_at(&self, offset: u32) -> Result<String> {
+        if self.is_cut_off(offset) {
+            return Err(Error::EOF("UTF string data".to_owned()));
+        }
+        match self.strings.get(&offset) {
+            Some(string) => Ok(string.to_string()),
+            // decoding it again reports why it can't be decoded
+            None => match self.raw_strings.get(&offset) {
+                Some(string) => Ok(self.options.encoding.decode(string)?.into_owned()),
+                None => Err(Error::DataNotFound),
+            },
+        }
+    }
+
+    /// Returns whether or not string values at the given offset in the string
+    /// data section are read as empty strings (see
+    /// [`ReaderOptions::empty_sentinel`])
+    pub(crate) fn is_empty_sentinel(&self, offset: u32) -> bool {
+        match &self.options.empty_sentinel {
+            Some(sentinel) => self.strings.get(&offset).is_some_and(|s| s == sentinel),
+            None => false,
+        }
+    }
+
+    /// Returns the bytes of the string at the given offset in the string data
+    /// section, whether or not they can be decoded
+    pub(crate) fn raw_string_at(&self, offset: u32) -> Result<Vec<u8>> {
+        if let Some(string) = self.raw_strings.get(&offset) {
+            return Ok(string.to_vec());
+        }
+        match self.strings.get(&offset) {
+            Some(string) => Ok(self.options.encoding.encode(string)?.into_owned()),
             None => Err(Error::DataNotFound),
         }
     }
 
+    /// Reads a blob stored in the blob data section
+    pub(crate) fn blob_at(&mut self, offset: u32, length: u32) -> Result<Vec<u8>> {
+        let blob_section_size = self.header.table_size - self.header.blob_offset;
+        if offset as u64 + length as u64 > blob_section_size as u64 {
+            return Err(Error::DataNotFound);
+        }
+        let mut blob = vec![0u8; length as usize];
+        self.source.read_at(
+            self.header.blob_offset as u64 + offset as u64,
+            &mut blob,
+            "UTF blob data",
+        )?;
+        self.bytes_read += length as u64;
+        Ok(blob)
+    }
+
+    /// Validates a blob stored in the blob data section, without reading it
+    pub(crate) fn blob_handle(&self, offset: u32, length: u32) -> Result<BlobHandle> {
+        let blob_section_size = self.header.table_size - self.header.blob_offset;
+        if offset as u64 + length as u64 > blob_section_size as u64 {
+            return Err(Error::DataNotFound);
+        }
+        Ok(BlobHandle::new(
+            8 + self.header.blob_offset as u64 + offset as u64,
+            length,
+        ))
+    }
+
+    /**
+    Reads the contents of a blob from the table being read
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{BlobHandle, Reader};
+    let file = File::open("huge-table.bin")?;
+    let mut reader = Reader::new_seek(file)?;
+    let mut handles = Vec::new();
+    while reader.more_row_data() {
+        handles.push(reader.read_value::<BlobHandle>(true)?);
+    }
+    // only load the blobs that are needed
+    let first = reader.load_blob(&handles[0])?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn load_blob(&mut self, handle: &BlobHandle) -> Result<Vec<u8>> {
+        let offset = handle.offset().wrapping_sub(8);
+        if offset < self.header.blob_offset as u64
+            || offset + handle.len() as u64 > self.header.table_size as u64
+        {
+            return Err(Error::DataNotFound);
+        }
+        let mut blob = vec![0u8; handle.len()];
+        self.source.read_at(offset, &mut blob, "UTF blob data")?;
+        Ok(blob)
+    }
+
     /**
     Attempts to read a value from the column or row buffer.
 
@@ -371,21 +2604,111 @@ impl Reader {
     # use std::fs::File;
     # use criware_utf_core::Reader;
     # let mut file = std::fs::File::open("random-table.bin")?;
-    # let reader = criware_utf_core::Reader::new(&mut file)?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
     while reader.more_row_data() {
         let name: String = reader.read_value(true)?;
         let crc32: u32 = reader.read_value(true)?;
         // ...
     }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read_value<T: Value>(&mut self, row: bool) -> Result<T> {
-        T::from_primitive(self.read_primitive::<T::Primitive>(row)?).map_err(|error| {
-            Error::ValueConversion(
-                std::any::type_name::<T::Primitive>(),
-                std::any::type_name::<T>(),
-                error,
-            )
-        })
+        self.read_value_private(row, None)
+    }
+
+    /**
+    Attempts to read a value from the column or row buffer, passing its raw
+    bytes through `hook` before they are decoded.
+
+    This allows game-specific quirks (sentinel values, garbage padding) to be
+    normalized without writing a custom [`Value`] type.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Reader;
+    # let mut file = std::fs::File::open("random-table.bin")?;
+    # let mut reader = criware_utf_core::Reader::new(&mut file)?;
+    while reader.more_row_data() {
+        let id: u16 = reader.read_value_with_hook(true, &|bytes| bytes.reverse())?;
+        // ...
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_value_with_hook<T: Value>(&mut self, row: bool, hook: &CellHook) -> Result<T> {
+        self.read_value_private(row, Some(hook))
+    }
+
+    /**
+    Attempts to read a value of the given kind from the column or row buffer
+
+    This is the dynamic counterpart of [`Reader::read_value`], for when the
+    kind of value is only known at runtime (like from a
+    [`Schema`](crate::Schema)). Values are read exactly as they are with
+    [`Reader::read_value`], and can be written back with
+    [`Writer::write_any`](crate::Writer::write_any).
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{Reader, ValueKind};
+    # let data: Vec<u8> = Vec::new();
+    # let kinds: Vec<ValueKind> = Vec::new();
+    let mut reader = Reader::from_bytes(&data)?;
+    while reader.more_row_data() {
+        for kind in &kinds {
+            let value = reader.read_value_any(true, *kind)?;
+            // ...
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_value_any(&mut self, row: bool, kind: ValueKind) -> Result<AnyValue> {
+        Ok(read_any_kind!(kind, self.read_primitive(row, None)))
+    }
+
+    fn read_value_private<T: Value>(&mut self, row: bool, hook: Option<&CellHook>) -> Result<T> {
+        if row {
+            self.skip_row_cells(false);
+        }
+        let position = if row {
+            self.row_position
+        } else {
+            self.column_position
+        };
+        match T::from_primitive(self.read_primitive::<T::Primitive>(row, hook)?) {
+            Ok(value) => Ok(value),
+            Err(error) => {
+                let error = Error::ValueConversion(
+                    std::any::type_name::<T::Primitive>(),
+                    std::any::type_name::<T>(),
+                    error,
+                );
+                Err(match self.column_at(row, position) {
+                    Some((name, row)) => Error::InColumn(name, row, Box::new(error)),
+                    None => error,
+                })
+            }
+        }
+    }
+
+    /// Returns the name of the column whose value is at the given position in
+    /// the column or row buffer, along with its row (if it's rowed)
+    fn column_at(&mut self, row: bool, position: u32) -> Option<(String, Option<u32>)> {
+        let row_size = (self.header.row_size as u32).max(1);
+        let (row, position) = if row {
+            let position = position.checked_sub(self.header.row_offset)?;
+            (Some(position / row_size), position % row_size)
+        } else {
+            (None, position)
+        };
+        let columns = self.columns().ok()?;
+        let (name, _) = columns.iter().find(|(_, location)| match row {
+            Some(_) => location.row_offset.map(u32::from) == Some(position),
+            None => location.constant_offset == Some(8 + position as u64),
+        })?;
+        Some((name.clone(), row))
     }
 }
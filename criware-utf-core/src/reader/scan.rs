@@ -0,0 +1,64 @@
+use super::Header;
+use crate::ReaderOptions;
+
+/**
+Finds every table embedded in the given data, and returns their positions
+
+Every `@UTF` magic is checked for a valid header (see
+[`find_utf_tables_with_options`]), and a table that fits within the data.
+Tables stored inside other tables (like in the blobs of a CPK's table of
+contents) are found too.
+
+Big-endian tables are expected. For little-endian tables, use
+[`find_utf_tables_with_options`].
+
+# Example
+```no_run
+# use criware_utf_core::{Reader, find_utf_tables};
+let data = std::fs::read("game.exe")?;
+for position in find_utf_tables(&data) {
+    let reader = Reader::from_bytes(&data[position..])?;
+    println!("0x{position:x}: {}", reader.table_name());
+}
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub fn find_utf_tables(data: &[u8]) -> Vec<usize> {
+    find_utf_tables_with_options(data, &ReaderOptions::default())
+}
+
+/**
+Finds every table embedded in the given data that can be read with the given
+options, and returns their positions
+
+A candidate is only kept if its header passes the same checks as
+[`Reader::new_with_options`](crate::Reader::new_with_options) (including the
+limits), its column data is large enough for its columns, its name is inside
+its string data section, and the entire table fits within the data.
+
+See [`find_utf_tables`].
+ */
+pub fn find_utf_tables_with_options(data: &[u8], options: &ReaderOptions) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut start = 0;
+    while let Some(found) = data[start..]
+        .windows(4)
+        .position(|window| window == b"@UTF")
+    {
+        let position = start + found;
+        if is_valid_table(&data[position..], options) {
+            positions.push(position);
+        }
+        start = position + 1;
+    }
+    positions
+}
+
+fn is_valid_table(data: &[u8], options: &ReaderOptions) -> bool {
+    let Ok(header) = Header::read(&mut &data[..], options) else {
+        return false;
+    };
+    header.row_offset as u64 >= 24 + 5 * header.field_count as u64
+        && header.string_offset as u64 + (header.table_name as u64) < header.blob_offset as u64
+        && 8 + header.table_size as u64 <= data.len() as u64
+}
@@ -0,0 +1,147 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Size of the read-ahead buffer used by [`SeekSource`]
+pub(crate) const STREAM_CACHE_SIZE: usize = 0x4000;
+
+/**
+Random access to the bytes of a table
+
+A [`Reader`](crate::Reader) only ever asks its source for a range of bytes at
+a given offset, so anything that can provide that (a memory-mapped file, a
+region of an archive, a network resource, ...) can be read from with
+[`Reader::from_table_source`](crate::Reader::from_table_source).
+
+Offsets are relative to the start of the table (the `@UTF` magic). Reads past
+the end of the table are never requested.
+
+Implementations are provided for in-memory tables (`&[u8]`, `Vec<u8>` and
+`Box<[u8]>`) and for seekable streams (through [`SeekSource`]).
+
+# Example
+```no_run
+# use criware_utf_core::{Reader, TableSource};
+struct Mapped(Vec<u8>);
+
+impl TableSource for Mapped {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> std::io::Result<()> {
+        let start = offset as usize;
+        buffer.copy_from_slice(&self.0[start..start + buffer.len()]);
+        Ok(())
+    }
+}
+
+let reader = Reader::from_table_source(Mapped(std::fs::read("random-table.bin")?))?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub trait TableSource {
+    /// Fills `buffer` with the bytes starting at the given offset (relative
+    /// to the start of the table)
+    ///
+    /// If there aren't enough bytes, an error of kind
+    /// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) should be returned.
+    ///
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> io::Result<()>;
+}
+
+fn read_slice_at(data: &[u8], offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+    let start = offset as usize;
+    match data.get(start..start + buffer.len()) {
+        Some(slice) => {
+            buffer.copy_from_slice(slice);
+            Ok(())
+        }
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+impl TableSource for &[u8] {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        read_slice_at(self, offset, buffer)
+    }
+}
+
+impl TableSource for Vec<u8> {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        read_slice_at(self, offset, buffer)
+    }
+}
+
+impl TableSource for Box<[u8]> {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        read_slice_at(self, offset, buffer)
+    }
+}
+
+/**
+A [`TableSource`] that reads from a seekable stream on demand
+
+Small reads are served from a read-ahead buffer, so reading the values of a
+table one by one doesn't seek for every value.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::{Reader, SeekSource};
+let file = File::open("huge-table.bin")?;
+let reader = Reader::from_table_source(SeekSource::new(file, 0))?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub struct SeekSource<R: Read + Seek> {
+    stream: R,
+    start: u64,
+    cache: Vec<u8>,
+    cache_offset: u64,
+}
+
+impl<R: Read + Seek> SeekSource<R> {
+    /// Creates a new `SeekSource` for a table that starts at the given
+    /// position of the stream
+    ///
+    pub fn new(stream: R, start: u64) -> Self {
+        SeekSource {
+            stream,
+            start,
+            cache: Vec::new(),
+            cache_offset: 0,
+        }
+    }
+
+    /// Returns the underlying stream
+    ///
+    pub fn into_inner(self) -> R {
+        self.stream
+    }
+}
+
+impl<R: Read + Seek> TableSource for SeekSource<R> {
+    fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        let length = buffer.len() as u64;
+        if offset >= self.cache_offset
+            && offset + length <= self.cache_offset + self.cache.len() as u64
+        {
+            let position = (offset - self.cache_offset) as usize;
+            buffer.copy_from_slice(&self.cache[position..position + buffer.len()]);
+            return Ok(());
+        }
+        self.stream.seek(SeekFrom::Start(self.start + offset))?;
+        if buffer.len() >= STREAM_CACHE_SIZE {
+            return self.stream.read_exact(buffer);
+        }
+        // the stream may end before the cache is full, which is only a
+        // problem if it ends before the requested bytes
+        self.cache.clear();
+        (&mut self.stream)
+            .take(STREAM_CACHE_SIZE as u64)
+            .read_to_end(&mut self.cache)?;
+        self.cache_offset = offset;
+        match self.cache.get(0..buffer.len()) {
+            Some(slice) => {
+                buffer.copy_from_slice(slice);
+                Ok(())
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::{cell::RefCell, rc::Rc};
+
+/// What a [`Span`] of a table contains
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    /// The flag and name of a column
+    ColumnHeader,
+    /// The value of a constant column
+    ConstantValue,
+    /// A value in the given row
+    RowCell {
+        /// The row the value belongs to (starting from 0)
+        row: u32,
+    },
+}
+
+/// A range of bytes in a table that was decoded by a [`Reader`](crate::Reader)
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// The position of the first byte, relative to the start of the table
+    /// (the `@UTF` magic)
+    pub offset: u64,
+    /// The number of bytes
+    pub length: u32,
+    /// What the bytes contain
+    pub kind: SpanKind,
+}
+
+impl Span {
+    /// Returns [`true`] if the given offset (relative to the start of the
+    /// table) is within this span
+    ///
+    pub fn contains(&self, offset: u64) -> bool {
+        offset >= self.offset && offset < self.offset + self.length as u64
+    }
+}
+
+/**
+The byte ranges of every column header and value a [`Reader`](crate::Reader)
+has decoded, in the order they were decoded
+
+A `SpanMap` is returned by [`Reader::record_spans`](crate::Reader::record_spans),
+and keeps receiving spans while the reader is in use (even after the reader has
+been passed to something else, like
+[`Table::read_from_reader`](crate::Table::read_from_reader)).
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::Reader;
+let mut file = File::open("random-table.bin")?;
+let mut reader = Reader::new(&mut file)?;
+let spans = reader.record_spans();
+// ... table reading code ...
+if let Some(span) = spans.last() {
+    println!("last value read was at 0x{:x}", span.offset);
+}
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct SpanMap(Rc<RefCell<Vec<Span>>>);
+
+impl SpanMap {
+    pub(crate) fn push(&self, span: Span) {
+        self.0.borrow_mut().push(span);
+    }
+
+    /// Returns every span recorded so far
+    ///
+    pub fn spans(&self) -> Vec<Span> {
+        self.0.borrow().clone()
+    }
+
+    /// Returns the number of spans recorded so far
+    ///
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Returns [`true`] if no spans have been recorded
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Returns the most recently recorded span
+    ///
+    /// When reading fails, this is usually the value that caused it.
+    ///
+    pub fn last(&self) -> Option<Span> {
+        self.0.borrow().last().copied()
+    }
+
+    /// Returns the span containing the given offset (relative to the start of
+    /// the table), if that part of the table was decoded
+    ///
+    pub fn find(&self, offset: u64) -> Option<Span> {
+        self.0
+            .borrow()
+            .iter()
+            .find(|span| span.contains(offset))
+            .copied()
+    }
+}
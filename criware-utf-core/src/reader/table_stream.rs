@@ -0,0 +1,96 @@
+use std::io::{ErrorKind, Read};
+
+use crate::{Error, IOErrorHelper, Reader, ReaderOptions, Result};
+
+/**
+An iterator over tables stored back-to-back in a stream
+
+Each table is loaded into memory like with [`Reader::new`], and only the
+bytes declared in its header are read, so the stream is left at the start of
+the next table. Iteration ends when the stream ends between two tables. If a
+table can't be read, the error is returned, and iteration ends.
+
+# Example
+```no_run
+# use std::{fs::File, io::BufReader};
+# use criware_utf_core::TableStream;
+let file = BufReader::new(File::open("tables.bin")?);
+for reader in TableStream::new(file) {
+    let reader = reader?;
+    println!("{} ({} rows)", reader.table_name(), reader.row_count());
+}
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub struct TableStream<R: Read> {
+    reader: R,
+    options: ReaderOptions,
+    done: bool,
+}
+
+impl<R: Read> TableStream<R> {
+    /// Creates a new `TableStream` that reads tables from the given stream
+    ///
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ReaderOptions::default())
+    }
+
+    /// Creates a new `TableStream` that reads tables from the given stream,
+    /// and parses each of them with the given options
+    ///
+    pub fn with_options(reader: R, options: ReaderOptions) -> Self {
+        TableStream {
+            reader,
+            options,
+            done: false,
+        }
+    }
+
+    /// Returns the underlying stream
+    ///
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the first few bytes of the next table, or returns [`None`] if
+    /// the stream has ended
+    fn read_prefix(&mut self) -> Result<Option<[u8; 8]>> {
+        let mut prefix = [0u8; 8];
+        let mut length = 0;
+        while length < prefix.len() {
+            match self.reader.read(&mut prefix[length..]) {
+                Ok(0) if length == 0 => return Ok(None),
+                Ok(0) => return Err(Error::EOF("@UTF header".to_owned())),
+                Ok(read) => length += read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error).io("@UTF header"),
+            }
+        }
+        Ok(Some(prefix))
+    }
+}
+
+impl<R: Read> Iterator for TableStream<R> {
+    type Item = Result<Reader<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.read_prefix() {
+            Ok(Some(prefix)) => Reader::new_with_options(
+                &mut (&prefix[..]).chain(&mut self.reader),
+                self.options.clone(),
+            ),
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(error) => Err(error),
+        };
+        if result.is_err() {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
@@ -1,8 +1,21 @@
-use crate::{Error, Reader, Result, ValueKind};
+use std::{
+    fmt,
+    io::{Read, Seek, SeekFrom},
+    ops::Index,
+};
+
+use crate::{
+    AnyValue, CriCipher, DescribedTable, Error, IOErrorHelper, Reader, ReaderOptions, Result,
+    TableLayout, ValueKind, Writer, packet::read_packet_data,
+};
+
+mod codegen;
 
 /// The possible ways a column can store data
 ///
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ColumnStorageFormat {
     /// No data is stored currently, but may have data in the future
     Zero,
@@ -12,9 +25,84 @@ pub enum ColumnStorageFormat {
     Rowed,
 }
 
+impl ColumnStorageFormat {
+    /// Returns the storage flag of the format (the upper half of a column's
+    /// flag byte)
+    ///
+    pub const fn flag(self) -> u8 {
+        match self {
+            ColumnStorageFormat::Zero => 0x10,
+            ColumnStorageFormat::Constant => 0x30,
+            ColumnStorageFormat::Rowed => 0x50,
+        }
+    }
+
+    /// Returns the flag byte of a column with this format and the given kind
+    /// of value
+    ///
+    pub const fn column_flag(self, kind: ValueKind) -> u8 {
+        self.flag() | kind.flag()
+    }
+
+    /// Returns the space (in bytes) a value of the given kind takes up in
+    /// each row, if it's stored in this format
+    ///
+    pub const fn size_in_row(self, kind: ValueKind) -> usize {
+        match self {
+            ColumnStorageFormat::Rowed => kind.size_in_row(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the name of the format, as used by [`Schema`] listings (like
+    /// `"rowed"`)
+    ///
+    pub const fn name(self) -> &'static str {
+        match self {
+            ColumnStorageFormat::Zero => "zero",
+            ColumnStorageFormat::Constant => "constant",
+            ColumnStorageFormat::Rowed => "rowed",
+        }
+    }
+}
+
+impl fmt::Display for ColumnStorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/**
+Returns the format with the storage flag in the upper half of the given flag
+byte (the lower half is ignored)
+
+# Example
+```
+# use criware_utf::{ColumnStorageFormat, ValueKind};
+let format = ColumnStorageFormat::try_from(0x5a)?;
+assert_eq!(format, ColumnStorageFormat::Rowed);
+assert_eq!(format.column_flag(ValueKind::STR), 0x5a);
+assert!(ColumnStorageFormat::try_from(0x2a).is_err());
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+impl TryFrom<u8> for ColumnStorageFormat {
+    type Error = Error;
+
+    fn try_from(flag: u8) -> Result<Self> {
+        match flag & 0xf0 {
+            0x10 => Ok(ColumnStorageFormat::Zero),
+            0x30 => Ok(ColumnStorageFormat::Constant),
+            0x50 => Ok(ColumnStorageFormat::Rowed),
+            v => Err(Error::InvalidColumnStorage(v)),
+        }
+    }
+}
+
 /// Representation of a column of a table (data not included)
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SchemaColumn {
     /// The name of the column
     pub name: String,
@@ -24,11 +112,28 @@ pub struct SchemaColumn {
     pub value_kind: ValueKind,
 }
 
-/// Representation of a table's schema
-///
-/// This is meant to be immutable.
-///
+/**
+Representation of a table's schema
+
+This is meant to be immutable.
+
+With the `serde` feature, it can be serialized and deserialized (along with
+[`SchemaColumn`], [`ValueKind`], and the other types it holds). Columns are
+described the same way [`Schema::to_json`] describes them, so its output can
+be deserialized too.
+
+# Example
+```
+# use criware_utf_core::{Schema, ValueKind};
+let json = r#"{"table_name":"Files","columns":[{"name":"FileSize","value_kind":"u32","storage_format":"rowed"}]}"#;
+let schema: Schema = serde_json::from_str(json)?;
+assert_eq!(schema["FileSize"].value_kind, ValueKind::U32);
+assert_eq!(serde_json::from_str::<Schema>(&schema.to_json())?.table_name, "Files");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Schema {
     /// The name of the table
     pub table_name: String,
@@ -36,30 +141,559 @@ pub struct Schema {
     pub columns: Box<[SchemaColumn]>,
 }
 
-impl Reader {
-    fn get_column(&mut self) -> Result<SchemaColumn> {
-        let flag: u8 = self.read_value(false)?;
-        let column_name: String = self.read_value(false)?;
-        let value_kind = match flag & 0x0f {
-            0 => ValueKind::U8,
-            1 => ValueKind::I8,
-            2 => ValueKind::U16,
-            3 => ValueKind::I16,
-            4 => ValueKind::U32,
-            5 => ValueKind::I32,
-            6 => ValueKind::U64,
-            7 => ValueKind::I64,
-            8 => ValueKind::F32,
-            0xa => ValueKind::STR,
-            0xb => ValueKind::BLOB,
-            v => return Err(Error::InvalidColumnType(v)),
-        };
-        match flag & 0xf0 {
-            0x10 => Ok(SchemaColumn {
-                name: column_name,
-                storage_format: ColumnStorageFormat::Zero,
+/**
+The size and number of rows of a table, as read from its header
+
+These are read along with a table's schema by [`SchemaWithConstants`]. They
+can also be read on their own with [`TableStats::read`], which only reads the
+32-byte header, or from a [`Reader`] that's already open (see
+[`Reader::stats`]).
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::SchemaWithConstants;
+let mut file = File::open("random-table.bin")?;
+let table = SchemaWithConstants::read_seek(&mut file)?;
+let stats = &table.stats;
+println!(
+    "{}: {} rows of {} bytes, {} bytes of strings, {} bytes of blobs",
+    table.schema.table_name,
+    stats.row_count,
+    stats.row_size,
+    stats.string_section_size,
+    stats.blob_section_size
+);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableStats {
+    /// The number of rows
+    pub row_count: u32,
+    /// The size (in bytes) of each row
+    pub row_size: u16,
+    /// The size (in bytes) of the string data section
+    pub string_section_size: u32,
+    /// The size (in bytes) of the blob data section
+    pub blob_section_size: u32,
+    /// The size (in bytes) of the table, as declared in its header (not
+    /// including the `@UTF` magic and the size itself)
+    pub table_size: u32,
+}
+
+impl TableStats {
+    /// Reads the header of a table from the given stream, leaving the stream
+    /// just after it
+    ///
+    /// Tables are expected to be big-endian, like official tables are.
+    ///
+    pub fn read(reader: &mut dyn Read) -> Result<Self> {
+        crate::reader::read_stats(reader, &ReaderOptions::default())
+    }
+}
+
+/// A column that a [`DescribedTable`] expects to read (see [`TableSchema`])
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableColumn {
+    /// The name of the column
+    pub name: &'static str,
+    /// The method in which the column stores data (either constant or rowed)
+    pub storage_format: ColumnStorageFormat,
+    /// The kind of data the column stores, or [`None`] if it's stored with a
+    /// vendor type flag (which no [`Schema`] can have)
+    pub value_kind: Option<ValueKind>,
+    /// If `true`, the column may also have no data (be stored as
+    /// [`ColumnStorageFormat::Zero`])
+    pub optional: bool,
+}
+
+/**
+The schema that a [`DescribedTable`] expects to read
+
+Tables generated by the `utf_table` macro describe their struct with this
+(see [`DescribedTable::table_schema`]).
+
+# Example
+```
+# use criware_utf::{ColumnStorageFormat, DescribedTable, ValueKind, utf_table};
+#[utf_table(table_name = "Files")]
+struct FileTable {
+    #[constant]
+    version: u32,
+    #[optional]
+    crc: u32,
+}
+
+let schema = FileTable::table_schema();
+assert_eq!(schema.table_name, "Files");
+assert_eq!(schema.columns[1].name, "Crc");
+assert_eq!(schema.columns[1].storage_format, ColumnStorageFormat::Rowed);
+assert_eq!(schema.columns[1].value_kind, Some(ValueKind::U32));
+assert!(schema.columns[1].optional);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableSchema {
+    /// The name of the table
+    pub table_name: &'static str,
+    /// The columns, listed in the order they're read
+    pub columns: &'static [TableColumn],
+}
+
+/// How well a [`Schema`] matches the schema of a [`DescribedTable`] (see
+/// [`Schema::matches`])
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMatch {
+    /// Every column matches, and every optional column has data
+    Exact,
+    /// The table can be read, but the given optional columns have no data
+    Compatible(Vec<&'static str>),
+    /// The table can't be read, for the given reasons
+    Mismatch(Vec<SchemaMismatch>),
+}
+
+impl SchemaMatch {
+    /// Returns `true` if the table can be read (the match is exact or
+    /// compatible)
+    ///
+    pub fn is_readable(&self) -> bool {
+        !matches!(self, SchemaMatch::Mismatch(_))
+    }
+}
+
+/// A reason a [`Schema`] doesn't match the schema of a [`DescribedTable`]
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    /// The table has a different name (the value is the name it has)
+    TableName(String),
+    /// The table doesn't have the column with the given name (it ran out of
+    /// columns)
+    MissingColumn(&'static str),
+    /// The table has a column with the given name after all of the expected
+    /// columns
+    ExtraColumn(String),
+    /// The column at the given position has a different name
+    ColumnName {
+        index: usize,
+        expected: &'static str,
+        found: String,
+    },
+    /// The column stores a different kind of value (the expected kind is
+    /// [`None`] if it's a vendor type)
+    ValueKind {
+        column: &'static str,
+        expected: Option<ValueKind>,
+        found: ValueKind,
+    },
+    /// The column stores its data differently
+    StorageFormat {
+        column: &'static str,
+        expected: ColumnStorageFormat,
+        found: ColumnStorageFormat,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaMismatch::TableName(name) => write!(f, "table is named {name:?}"),
+            SchemaMismatch::MissingColumn(name) => write!(f, "column \"{name}\" is missing"),
+            SchemaMismatch::ExtraColumn(name) => write!(f, "unexpected column {name:?}"),
+            SchemaMismatch::ColumnName {
+                index,
+                expected,
+                found,
+            } => write!(f, "column {index} is {found:?} (expected \"{expected}\")"),
+            SchemaMismatch::ValueKind {
+                column,
+                expected: Some(expected),
+                found,
+            } => write!(
+                f,
+                "column \"{column}\" stores {found} (expected {expected})"
+            ),
+            SchemaMismatch::ValueKind {
+                column,
+                expected: None,
+                found,
+            } => write!(
+                f,
+                "column \"{column}\" stores {found} (expected a vendor type)"
+            ),
+            SchemaMismatch::StorageFormat {
+                column,
+                expected,
+                found,
+            } => write!(f, "column \"{column}\" is {found} (expected {expected})"),
+        }
+    }
+}
+
+/// Something unusual about a schema, which official tools never produce
+/// (see [`Schema::validate`])
+///
+/// None of these stop the table from being read, but they often mean the
+/// table was made (or damaged) by something else.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaWarning {
+    /// More than one column has the given name (the values are the name and
+    /// the positions of the first two columns with it)
+    ///
+    /// Columns are looked up by name, so only the first is ever found.
+    DuplicateColumn {
+        name: String,
+        first: usize,
+        second: usize,
+    },
+    /// The column at the given position has an empty name
+    EmptyColumnName(usize),
+    /// The constant blob column with the given name is empty
+    ///
+    /// Official tools store empty columns as zero instead.
+    EmptyConstantBlob(String),
+    /// The column with the given name has a storage flag (its upper nibble)
+    /// that isn't zero, constant, or rowed
+    ///
+    /// Only found by [`Schema::validate_bytes`], since a schema can't be read
+    /// from the table at all.
+    UndefinedStorage { column: String, flag: u8 },
+    /// The column with the given name has a type flag (its lower nibble)
+    /// above `0xb`, which isn't any [`ValueKind`]
+    ///
+    /// Only found by [`Schema::validate_bytes`], since a schema can't be read
+    /// from the table at all (unless
+    /// [`ReaderOptions::preserve_unknown_types`] is set).
+    UndefinedType { column: String, flag: u8 },
+}
+
+impl fmt::Display for SchemaWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaWarning::DuplicateColumn {
+                name,
+                first,
+                second,
+            } => write!(f, "columns {first} and {second} are both named {name:?}"),
+            SchemaWarning::EmptyColumnName(index) => write!(f, "column {index} has no name"),
+            SchemaWarning::EmptyConstantBlob(name) => {
+                write!(f, "constant column {name:?} is an empty blob")
+            }
+            SchemaWarning::UndefinedStorage { column, flag } => {
+                write!(
+                    f,
+                    "column {column:?} has undefined storage flag 0x{flag:02x}"
+                )
+            }
+            SchemaWarning::UndefinedType { column, flag } => {
+                write!(f, "column {column:?} has undefined type flag 0x{flag:02x}")
+            }
+        }
+    }
+}
+
+/// A column whose kind of value or storage format changed between two
+/// schemas (see [`SchemaDiff`])
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnChange<T> {
+    /// The name of the column
+    pub name: String,
+    /// What it was in the old schema
+    pub old: T,
+    /// What it is in the new schema
+    pub new: T,
+}
+
+/**
+The differences between two schemas (see [`Schema::diff`])
+
+Columns are matched up by name, so renamed columns show up as one removed and
+one added column. Changes in the order of the columns aren't reported.
+
+Lists every change on its own line when displayed, like `+ Crc: u32 (rowed)`
+for an added column, `- Crc: u32 (rowed)` for a removed one, and
+`~ FileSize: u32 -> u64` for a changed one.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// The old and new name of the table, if it was renamed
+    pub table_name: Option<(String, String)>,
+    /// The columns only in the new schema
+    pub added: Vec<SchemaColumn>,
+    /// The columns only in the old schema
+    pub removed: Vec<SchemaColumn>,
+    /// The columns that store a different kind of value
+    pub retyped: Vec<ColumnChange<ValueKind>>,
+    /// The columns that store their data differently
+    pub storage_changed: Vec<ColumnChange<ColumnStorageFormat>>,
+}
+
+impl SchemaDiff {
+    /// Returns `true` if the schemas are the same (apart from the order of
+    /// their columns)
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.table_name.is_none()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.retyped.is_empty()
+            && self.storage_changed.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((old, new)) = &self.table_name {
+            writeln!(f, "~ table: {old} -> {new}")?;
+        }
+        for column in &self.removed {
+            let SchemaColumn {
+                name,
+                storage_format,
+                value_kind,
+            } = column;
+            writeln!(f, "- {name}: {value_kind} ({storage_format})")?;
+        }
+        for column in &self.added {
+            let SchemaColumn {
+                name,
+                storage_format,
                 value_kind,
-            }),
+            } = column;
+            writeln!(f, "+ {name}: {value_kind} ({storage_format})")?;
+        }
+        for ColumnChange { name, old, new } in &self.retyped {
+            writeln!(f, "~ {name}: {old} -> {new}")?;
+        }
+        for ColumnChange { name, old, new } in &self.storage_changed {
+            writeln!(f, "~ {name}: {old} -> {new}")?;
+        }
+        Ok(())
+    }
+}
+
+/**
+Settings for [`Schema::to_ascii_table`]
+
+# Example
+```
+# use criware_utf_core::AsciiTableOptions;
+let options = AsciiTableOptions {
+    max_cell_width: Some(16),
+    max_rows: Some(20),
+};
+```
+*/
+#[derive(Debug, Clone)]
+pub struct AsciiTableOptions {
+    /// The most characters shown in a cell (40 by default), or [`None`] to
+    /// show every value in full
+    ///
+    /// Longer values are cut off, and end with `…`.
+    pub max_cell_width: Option<usize>,
+    /// The most rows shown (after which the number of rows left out is
+    /// given), or [`None`] to show every row
+    pub max_rows: Option<usize>,
+}
+
+impl Default for AsciiTableOptions {
+    fn default() -> Self {
+        AsciiTableOptions {
+            max_cell_width: Some(40),
+            max_rows: None,
+        }
+    }
+}
+
+/**
+Builds a [`Schema`] column by column
+
+# Example
+```
+# use criware_utf::{SchemaBuilder, ValueKind};
+let schema = SchemaBuilder::new("Files")
+    .constant_column("Version", ValueKind::U32)
+    .rowed_column("FileName", ValueKind::STR)
+    .rowed_column("FileSize", ValueKind::U64)
+    .zero_column("Crc", ValueKind::U32)
+    .build();
+assert_eq!(schema.columns.len(), 4);
+assert!(schema.has_column("FileSize"));
+```
+*/
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    table_name: String,
+    columns: Vec<SchemaColumn>,
+}
+
+impl SchemaBuilder {
+    /// Creates a new `SchemaBuilder` for a table with the given name and no
+    /// columns
+    ///
+    pub fn new(table_name: impl Into<String>) -> Self {
+        SchemaBuilder {
+            table_name: table_name.into(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds a new column
+    ///
+    pub fn column(
+        &mut self,
+        name: impl Into<String>,
+        storage_format: ColumnStorageFormat,
+        value_kind: ValueKind,
+    ) -> &mut Self {
+        self.columns.push(SchemaColumn {
+            name: name.into(),
+            storage_format,
+            value_kind,
+        });
+        self
+    }
+
+    /// Adds a new column that doesn't store any data
+    ///
+    pub fn zero_column(&mut self, name: impl Into<String>, value_kind: ValueKind) -> &mut Self {
+        self.column(name, ColumnStorageFormat::Zero, value_kind)
+    }
+
+    /// Adds a new constant column
+    ///
+    pub fn constant_column(&mut self, name: impl Into<String>, value_kind: ValueKind) -> &mut Self {
+        self.column(name, ColumnStorageFormat::Constant, value_kind)
+    }
+
+    /// Adds a new rowed column
+    ///
+    pub fn rowed_column(&mut self, name: impl Into<String>, value_kind: ValueKind) -> &mut Self {
+        self.column(name, ColumnStorageFormat::Rowed, value_kind)
+    }
+
+    /// Returns the schema, with the columns in the order they were added
+    ///
+    pub fn build(&self) -> Schema {
+        Schema {
+            table_name: self.table_name.clone(),
+            columns: self.columns.clone().into_boxed_slice(),
+        }
+    }
+}
+
+/**
+Returns the column with the given name
+
+# Panics
+
+Panics if the table has no column with that name (see [`Schema::column`]).
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::Schema;
+# let mut file = File::open("random-table.bin")?;
+let schema = Schema::read(&mut file)?;
+println!("{:?}", schema["FileSize"].storage_format);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+impl Index<&str> for Schema {
+    type Output = SchemaColumn;
+
+    fn index(&self, name: &str) -> &SchemaColumn {
+        match self.column(name) {
+            Some(column) => column,
+            None => panic!("no column named {name:?} in table {:?}", self.table_name),
+        }
+    }
+}
+
+/**
+Lists the table name, followed by each column on its own line (with its kind
+of value and storage format)
+
+This is meant to be read by people, and compared between tables with a
+line-based diff. Use [`Schema::to_json`] for a machine-readable description.
+
+# Example
+```
+# use criware_utf::{ColumnStorageFormat, Schema, SchemaColumn, ValueKind};
+let schema = Schema {
+    table_name: "Files".to_owned(),
+    columns: Box::new([SchemaColumn {
+        name: "FileSize".to_owned(),
+        storage_format: ColumnStorageFormat::Rowed,
+        value_kind: ValueKind::U32,
+    }]),
+};
+assert_eq!(schema.to_string(), "Files\n  FileSize: u32 (rowed)\n");
+```
+*/
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.table_name)?;
+        for column in &self.columns {
+            writeln!(
+                f,
+                "  {}: {} ({})",
+                column.name, column.value_kind, column.storage_format
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends a string to some JSON, as a quoted and escaped JSON string
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+impl Reader<'_> {
+    /// Reads the next column header, along with the column's value if it's
+    /// constant and `keep_value` is `true`
+    fn get_column(&mut self, keep_value: bool) -> Result<(SchemaColumn, Option<AnyValue>)> {
+        let (flag, column_name) = self.read_column_header()?;
+        let value_kind = ValueKind::try_from(flag)?;
+        match flag & 0xf0 {
+            0x10 => Ok((
+                SchemaColumn {
+                    name: column_name,
+                    storage_format: ColumnStorageFormat::Zero,
+                    value_kind,
+                },
+                None,
+            )),
+            0x30 if keep_value => {
+                let value = self.read_value_any(false, value_kind)?;
+                Ok((
+                    SchemaColumn {
+                        name: column_name,
+                        storage_format: ColumnStorageFormat::Constant,
+                        value_kind,
+                    },
+                    Some(value),
+                ))
+            }
             0x30 => {
                 match value_kind {
                     ValueKind::U8 | ValueKind::I8 => {
@@ -71,21 +705,27 @@ impl Reader {
                     ValueKind::U32 | ValueKind::I32 | ValueKind::F32 | ValueKind::STR => {
                         self.read_value::<u32>(false)?;
                     }
-                    ValueKind::U64 | ValueKind::I64 | ValueKind::BLOB => {
+                    ValueKind::U64 | ValueKind::I64 | ValueKind::F64 | ValueKind::BLOB => {
                         self.read_value::<u64>(false)?;
                     }
                 };
-                Ok(SchemaColumn {
+                Ok((
+                    SchemaColumn {
+                        name: column_name,
+                        storage_format: ColumnStorageFormat::Constant,
+                        value_kind,
+                    },
+                    None,
+                ))
+            }
+            0x50 => Ok((
+                SchemaColumn {
                     name: column_name,
-                    storage_format: ColumnStorageFormat::Constant,
+                    storage_format: ColumnStorageFormat::Rowed,
                     value_kind,
-                })
-            }
-            0x50 => Ok(SchemaColumn {
-                name: column_name,
-                storage_format: ColumnStorageFormat::Rowed,
-                value_kind,
-            }),
+                },
+                None,
+            )),
             v => Err(Error::InvalidColumnStorage(v)),
         }
     }
@@ -106,16 +746,660 @@ impl Schema {
     } else {
         println!("could not find important data");
     }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn has_column(&self, name: &str) -> bool {
-        for column in &self.columns {
-            if column.name == name {
-                return true;
+        self.column_index(name).is_some()
+    }
+
+    /**
+    Returns the column with the given name, if it exists
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Schema;
+    # let mut file = File::open("random-table.bin")?;
+    # let schema = Schema::read(&mut file)?;
+    if let Some(column) = schema.column("FileSize") {
+        println!("file sizes are {:?}", column.value_kind);
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn column(&self, name: &str) -> Option<&SchemaColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Returns the position of the column with the given name among the
+    /// table's columns, if it exists
+    ///
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|column| column.name == name)
+    }
+
+    /**
+    Returns every column that stores the given kind of value, in the order
+    they appear
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Schema, ValueKind};
+    # let mut file = File::open("random-table.bin")?;
+    # let schema = Schema::read(&mut file)?;
+    for column in schema.columns_of_kind(ValueKind::BLOB) {
+        println!("{} holds blobs", column.name);
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn columns_of_kind(&self, kind: ValueKind) -> impl Iterator<Item = &SchemaColumn> {
+        self.columns
+            .iter()
+            .filter(move |column| column.value_kind == kind)
+    }
+
+    /**
+    Compares this (old) schema with another (new) one
+
+    See [`SchemaDiff`].
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Schema;
+    let old = Schema::read_seek(File::open("v1/files.bin")?)?;
+    let new = Schema::read_seek(File::open("v2/files.bin")?)?;
+    let diff = old.diff(&new);
+    if !diff.is_empty() {
+        print!("{diff}");
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn diff(&self, other: &Schema) -> SchemaDiff {
+        let mut diff = SchemaDiff::default();
+        if self.table_name != other.table_name {
+            diff.table_name = Some((self.table_name.clone(), other.table_name.clone()));
+        }
+        for old in &self.columns {
+            let Some(new) = other.column(&old.name) else {
+                diff.removed.push(old.clone());
+                continue;
+            };
+            if old.value_kind != new.value_kind {
+                diff.retyped.push(ColumnChange {
+                    name: old.name.clone(),
+                    old: old.value_kind,
+                    new: new.value_kind,
+                });
+            }
+            if old.storage_format != new.storage_format {
+                diff.storage_changed.push(ColumnChange {
+                    name: old.name.clone(),
+                    old: old.storage_format,
+                    new: new.storage_format,
+                });
             }
         }
-        false
+        for new in &other.columns {
+            if !self.has_column(&new.name) {
+                diff.added.push(new.clone());
+            }
+        }
+        diff
+    }
+
+    /**
+    Reads every row of a table, interpreting its columns as this schema
+    describes them
+
+    Each row holds the value of every column of the schema, in the same order.
+    Constant columns have the same value in every row. Columns that are stored
+    as zero, or that the table doesn't have, are [`None`]. The columns of the
+    table may be in any order, and columns the schema doesn't list are
+    ignored. A column that stores a different kind of value than the schema
+    says (or uses a different storage format, other than zero) is an error.
+
+    Since the schema can be built at runtime (see [`SchemaBuilder`]), this
+    allows tables to be read without declaring their layout at compile time.
+    The rows skipped or cut off with [`ReaderOptions`](crate::ReaderOptions)
+    aren't read. The position of the reader's column and row buffers isn't
+    affected.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{AnyValue, Reader, SchemaBuilder, ValueKind};
+    let schema = SchemaBuilder::new("Files")
+        .rowed_column("Name", ValueKind::STR)
+        .rowed_column("Size", ValueKind::U32)
+        .build();
+    let mut file = File::open("files.bin")?;
+    let mut reader = Reader::new(&mut file)?;
+    for row in schema.read_rows(&mut reader)? {
+        if let [Some(AnyValue::String(name)), Some(AnyValue::U32(size))] = &row[..] {
+            println!("{name}: {size} bytes");
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_rows(&self, reader: &mut Reader) -> Result<Vec<Vec<Option<AnyValue>>>> {
+        let report = reader.read_columns_matching(&self.columns)?;
+        let constants = report
+            .columns
+            .iter()
+            .map(|location| match location {
+                Some(location) if location.row_offset.is_none() => reader.read_any(location, 0),
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let window = reader.row_window();
+        let mut rows = Vec::with_capacity(window.len());
+        for row in window {
+            let values = report
+                .columns
+                .iter()
+                .zip(&constants)
+                .map(|(location, constant)| match location {
+                    Some(location) if location.row_offset.is_some() => {
+                        reader.read_any(location, row)
+                    }
+                    _ => Ok(constant.clone()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(values);
+        }
+        Ok(rows)
     }
+
+    /**
+    Renders rows read with [`Schema::read_rows`] as a grid of text, with a
+    header naming each column
+
+    Numbers are aligned to the right, and everything else to the left.
+    Strings are shown as-is (with control characters escaped), blobs by their
+    size, and cells without a value are left empty. Widths are measured in
+    characters, so the grid may be misaligned by characters that are wider
+    than others (like most Japanese text).
+
+    # Example
+    ```
+    # use criware_utf_core::{AnyValue, AsciiTableOptions, SchemaBuilder, ValueKind};
+    let schema = SchemaBuilder::new("Files")
+        .rowed_column("Name", ValueKind::STR)
+        .rowed_column("Size", ValueKind::U32)
+        .build();
+    let rows = vec![
+        vec![Some(AnyValue::String("bgm.acb".into())), Some(AnyValue::U32(1024))],
+        vec![Some(AnyValue::String("se.acb".into())), Some(AnyValue::U32(64))],
+    ];
+    assert_eq!(
+        schema.to_ascii_table(&rows, &AsciiTableOptions::default()),
+        "\
+    +---------+------+
+    | Name    | Size |
+    +---------+------+
+    | bgm.acb | 1024 |
+    | se.acb  |   64 |
+    +---------+------+
+    "
+    );
+    ```
+     */
+    pub fn to_ascii_table(
+        &self,
+        rows: &[Vec<Option<AnyValue>>],
+        options: &AsciiTableOptions,
+    ) -> String {
+        let shown = options.max_rows.unwrap_or(usize::MAX).min(rows.len());
+        let truncate = |text: String| match options.max_cell_width {
+            Some(width) if text.chars().count() > width => {
+                let mut text: String = text.chars().take(width.saturating_sub(1)).collect();
+                text.push('…');
+                text
+            }
+            _ => text,
+        };
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| truncate(column.name.clone()))
+            .collect();
+        let cells: Vec<Vec<String>> = rows[..shown]
+            .iter()
+            .map(|row| {
+                (0..self.columns.len())
+                    .map(|index| truncate(cell_text(row.get(index).and_then(Option::as_ref))))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|index| {
+                cells
+                    .iter()
+                    .map(|row| row[index].chars().count())
+                    .chain([header[index].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut border = String::from("+");
+        for width in &widths {
+            border.push_str(&"-".repeat(width + 2));
+            border.push('+');
+        }
+        border.push('\n');
+        let push_row = |table: &mut String, row: &[String], numeric: &dyn Fn(usize) -> bool| {
+            table.push('|');
+            for (index, text) in row.iter().enumerate() {
+                let padding = " ".repeat(widths[index] - text.chars().count());
+                table.push(' ');
+                if numeric(index) {
+                    table.push_str(&padding);
+                    table.push_str(text);
+                } else {
+                    table.push_str(text);
+                    table.push_str(&padding);
+                }
+                table.push_str(" |");
+            }
+            table.push('\n');
+        };
+        let mut table = border.clone();
+        push_row(&mut table, &header, &|_| false);
+        table.push_str(&border);
+        for row in &cells {
+            push_row(&mut table, row, &|index| {
+                !matches!(
+                    self.columns[index].value_kind,
+                    ValueKind::STR | ValueKind::BLOB
+                )
+            });
+        }
+        table.push_str(&border);
+        if shown < rows.len() {
+            table.push_str(&format!("({} more rows)\n", rows.len() - shown));
+        }
+        table
+    }
+
+    /**
+    Writes a table with this schema and no rows
+
+    Constant columns are given the default value of their kind (see
+    [`AnyValue::default_of`]). The table is written with the default
+    [`WriterOptions`](crate::WriterOptions), and where each section went is
+    returned (see [`TableLayout`]).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{SchemaBuilder, ValueKind};
+    let schema = SchemaBuilder::new("Files")
+        .constant_column("Version", ValueKind::U32)
+        .rowed_column("FileName", ValueKind::STR)
+        .build();
+    schema.write_empty(&mut File::create("files.bin")?)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_empty(&self, writer: &mut dyn std::io::Write) -> Result<TableLayout> {
+        let defaults: Vec<AnyValue> = self
+            .columns
+            .iter()
+            .map(|column| AnyValue::default_of(column.value_kind))
+            .collect();
+        let mut table_writer = Writer::new(self.table_name.as_str());
+        for (column, default) in self.columns.iter().zip(&defaults) {
+            let name = column.name.as_str();
+            let flag = column.value_kind.flag();
+            match column.storage_format {
+                ColumnStorageFormat::Zero => table_writer.push_zero_column_any(name, flag),
+                ColumnStorageFormat::Constant => {
+                    table_writer.push_constant_column_any(name, default)?
+                }
+                ColumnStorageFormat::Rowed => table_writer.push_rowed_column_any(name, flag),
+            }
+        }
+        let row_size: usize = self
+            .columns
+            .iter()
+            .map(|column| column.storage_format.size_in_row(column.value_kind))
+            .sum();
+        let row_size = u16::try_from(row_size).map_err(|_| Error::TableTooLarge("row size"))?;
+        table_writer.end(writer, row_size, 0)
+    }
+
+    /**
+    Returns a JSON description of the schema
+
+    The description is an object with the table's name (`table_name`) and
+    an array of its columns (`columns`), each with a `name`, `value_kind`,
+    and `storage_format` (named like [`ValueKind::name`] and
+    [`ColumnStorageFormat::name`]). It's written on one line, without any
+    extra whitespace.
+
+    # Example
+    ```
+    # use criware_utf::{ColumnStorageFormat, Schema, SchemaColumn, ValueKind};
+    let schema = Schema {
+        table_name: "Files".to_owned(),
+        columns: Box::new([SchemaColumn {
+            name: "FileSize".to_owned(),
+            storage_format: ColumnStorageFormat::Rowed,
+            value_kind: ValueKind::U32,
+        }]),
+    };
+    assert_eq!(
+        schema.to_json(),
+        r#"{"table_name":"Files","columns":[{"name":"FileSize","value_kind":"u32","storage_format":"rowed"}]}"#
+    );
+    ```
+     */
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"table_name\":");
+        push_json_string(&mut json, &self.table_name);
+        json.push_str(",\"columns\":[");
+        for (index, column) in self.columns.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"name\":");
+            push_json_string(&mut json, &column.name);
+            json.push_str(&format!(
+                ",\"value_kind\":\"{}\",\"storage_format\":\"{}\"}}",
+                column.value_kind, column.storage_format
+            ));
+        }
+        json.push_str("]}");
+        json
+    }
+
+    /**
+    Checks whether a table with this schema can be read as `T`
+
+    Every reason the table can't be read is reported, not just the first.
+
+    Constant columns are accepted where rowed columns are expected, and the
+    other way around, since the reader accepts them too (like in tables
+    written with [`ColumnPromotion`]). A rowed column read as constant still
+    needs the same value in every row, which the schema can't tell.
+
+    [`ColumnPromotion`]: crate::ColumnPromotion
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{Schema, SchemaMatch};
+    # use criware_utf::utf_table;
+    #[utf_table(table_name = "Files")]
+    struct FileTable {
+        name: String,
+        #[optional]
+        crc: u32,
+    }
+
+    let mut file = File::open("random-table.bin")?;
+    let schema = Schema::read(&mut file)?;
+    match schema.matches::<FileTable>() {
+        SchemaMatch::Exact => println!("it's a file table"),
+        SchemaMatch::Compatible(missing) => println!("it's a file table without {missing:?}"),
+        SchemaMatch::Mismatch(reasons) => {
+            for reason in reasons {
+                println!("{reason}");
+            }
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    A table with promoted columns still matches:
+    ```
+    # use criware_utf_core::{ColumnPromotion, Schema, SchemaMatch, Table, WriterOptions};
+    # use criware_utf::utf_table;
+    #[utf_table(table_name = "Files")]
+    struct FileTable {
+        name: String,
+        version: u32,
+    }
+
+    let mut table = FileTable::new();
+    for name in ["a.bin", "b.bin"] {
+        table.rows.push(FileTableRow { name: name.to_owned(), version: 1 });
+    }
+    let mut data = Vec::new();
+    let options = WriterOptions {
+        column_promotion: ColumnPromotion::RowedToConstant,
+        ..FileTable::writer_options()
+    };
+    table.write_with_options(&mut data, options)?;
+
+    let schema = Schema::from_bytes(&data)?;
+    assert_eq!(schema.matches::<FileTable>(), SchemaMatch::Exact);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn matches<T: DescribedTable>(&self) -> SchemaMatch {
+        self.matches_schema(&T::table_schema())
+    }
+
+    /// Checks whether a table with this schema can be read as the table
+    /// described by `expected` (see [`Schema::matches`])
+    ///
+    pub fn matches_schema(&self, expected: &TableSchema) -> SchemaMatch {
+        let mut reasons = Vec::new();
+        let mut missing = Vec::new();
+        if self.table_name != expected.table_name {
+            reasons.push(SchemaMismatch::TableName(self.table_name.clone()));
+        }
+        for (index, column) in expected.columns.iter().enumerate() {
+            let Some(found) = self.columns.get(index) else {
+                reasons.push(SchemaMismatch::MissingColumn(column.name));
+                continue;
+            };
+            if found.name != column.name {
+                reasons.push(SchemaMismatch::ColumnName {
+                    index,
+                    expected: column.name,
+                    found: found.name.clone(),
+                });
+                continue;
+            }
+            if Some(found.value_kind) != column.value_kind {
+                reasons.push(SchemaMismatch::ValueKind {
+                    column: column.name,
+                    expected: column.value_kind,
+                    found: found.value_kind,
+                });
+            }
+            // the reader accepts constant columns in place of rowed ones, and
+            // the other way around (see `Reader::read_rowed_column` and
+            // `Reader::read_constant_column`)
+            let swapped = matches!(
+                (found.storage_format, column.storage_format),
+                (ColumnStorageFormat::Constant, ColumnStorageFormat::Rowed)
+                    | (ColumnStorageFormat::Rowed, ColumnStorageFormat::Constant)
+            );
+            if found.storage_format == ColumnStorageFormat::Zero && column.optional {
+                missing.push(column.name);
+            } else if found.storage_format != column.storage_format && !swapped {
+                reasons.push(SchemaMismatch::StorageFormat {
+                    column: column.name,
+                    expected: column.storage_format,
+                    found: found.storage_format,
+                });
+            }
+        }
+        for column in self.columns.iter().skip(expected.columns.len()) {
+            reasons.push(SchemaMismatch::ExtraColumn(column.name.clone()));
+        }
+        if !reasons.is_empty() {
+            SchemaMatch::Mismatch(reasons)
+        } else if !missing.is_empty() {
+            SchemaMatch::Compatible(missing)
+        } else {
+            SchemaMatch::Exact
+        }
+    }
+
+    /**
+    Checks the schema for anything official tools never produce, like two
+    columns with the same name (see [`SchemaWarning`])
+
+    Reading a schema only fails if a table can't be read at all. This is an
+    extra, stricter pass for tools that want to point out anything unusual.
+    Use [`SchemaWithConstants::validate`] to check the constant values too.
+
+    # Example
+    ```
+    # use criware_utf_core::{SchemaBuilder, SchemaWarning, ValueKind};
+    let schema = SchemaBuilder::new("Files")
+        .rowed_column("Name", ValueKind::STR)
+        .rowed_column("Name", ValueKind::U32)
+        .build();
+    assert_eq!(
+        schema.validate(),
+        [SchemaWarning::DuplicateColumn {
+            name: "Name".to_owned(),
+            first: 0,
+            second: 1,
+        }]
+    );
+    ```
+     */
+    pub fn validate(&self) -> Vec<SchemaWarning> {
+        let mut warnings = Vec::new();
+        for (index, column) in self.columns.iter().enumerate() {
+            if column.name.is_empty() {
+                warnings.push(SchemaWarning::EmptyColumnName(index));
+            } else if let Some(first) = self.column_index(&column.name)
+                && first != index
+                && !self.columns[first + 1..index]
+                    .iter()
+                    .any(|other| other.name == column.name)
+            {
+                warnings.push(SchemaWarning::DuplicateColumn {
+                    name: column.name.clone(),
+                    first,
+                    second: index,
+                });
+            }
+        }
+        warnings
+    }
+
+    /**
+    Checks a table that is in memory for anything official tools never
+    produce, including column flags that stop its schema from being read
+
+    If every column's storage and type flags are defined, the table's schema
+    is read and checked with [`Schema::validate`]. Otherwise, the undefined
+    flags are reported (see [`SchemaWarning::UndefinedStorage`] and
+    [`SchemaWarning::UndefinedType`]). Columns after a constant one with an
+    undefined flag can't be found, so they aren't checked.
+
+    # Example
+    ```
+    # use criware_utf_core::{Schema, SchemaBuilder, SchemaWarning, ValueKind};
+    let schema = SchemaBuilder::new("Files")
+        .rowed_column("Name", ValueKind::STR)
+        .zero_column("Crc", ValueKind::U32)
+        .build();
+    let mut data = Vec::new();
+    schema.write_empty(&mut data)?;
+    assert!(Schema::validate_bytes(&data)?.is_empty());
+
+    // the column flags start 32 bytes in, 5 bytes apart
+    data[32] = 0x5c;
+    data[37] = 0x76;
+    assert_eq!(
+        Schema::validate_bytes(&data)?,
+        [
+            SchemaWarning::UndefinedType {
+                column: "Name".to_owned(),
+                flag: 0x0c,
+            },
+            SchemaWarning::UndefinedStorage {
+                column: "Crc".to_owned(),
+                flag: 0x70,
+            },
+        ]
+    );
+    assert!(Schema::from_bytes(&data).is_err());
+    # Ok::<(), criware_utf_core::Error>(())
+    ```
+     */
+    pub fn validate_bytes(data: &[u8]) -> Result<Vec<SchemaWarning>> {
+        let mut reader = Reader::from_bytes(data)?;
+        let mut warnings = Vec::new();
+        for (flag, column) in reader.column_flags()? {
+            if ColumnStorageFormat::try_from(flag & 0xf0).is_err() {
+                warnings.push(SchemaWarning::UndefinedStorage {
+                    column: column.clone(),
+                    flag: flag & 0xf0,
+                });
+            }
+            if ValueKind::from_flag(flag & 0x0f).is_none() {
+                warnings.push(SchemaWarning::UndefinedType {
+                    column,
+                    flag: flag & 0x0f,
+                });
+            }
+        }
+        if warnings.is_empty() {
+            warnings = Self::from_reader(reader)?.validate();
+        }
+        Ok(warnings)
+    }
+
+    /**
+    Picks which of the candidates a table with this schema is best read as
+
+    Candidates that can't read the table at all are ruled out (see
+    [`Schema::matches_schema`]). Of the rest, an exact match is preferred,
+    followed by the candidates with the fewest optional columns missing. If
+    there's a tie, the candidate listed first wins. Returns the index of the
+    candidate, or [`None`] if none of them can read the table.
+
+    The [`select_schema!`](crate::select_schema) macro picks a table struct
+    this way, and reads the table with it.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{DescribedTable, Schema};
+    # use criware_utf::utf_table;
+    # #[utf_table(table_name = "Files")]
+    # struct FilesV1 { name: String }
+    # #[utf_table(table_name = "Files")]
+    # struct FilesV2 { name: String, crc: u32 }
+    let mut file = File::open("files.bin")?;
+    let schema = Schema::read(&mut file)?;
+    let candidates = [FilesV1::table_schema(), FilesV2::table_schema()];
+    match schema.best_match(&candidates) {
+        Some(index) => println!("version {}", index + 1),
+        None => println!("unknown version"),
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn best_match(&self, candidates: &[TableSchema]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| match self.matches_schema(candidate) {
+                SchemaMatch::Exact => Some((index, 0)),
+                SchemaMatch::Compatible(missing) => Some((index, missing.len())),
+                SchemaMatch::Mismatch(_) => None,
+            })
+            .min_by_key(|(_, missing)| *missing)
+            .map(|(index, _)| index)
+    }
+
     /**
     Reads a table and extracts its schema.
 
@@ -128,17 +1412,450 @@ impl Schema {
     let mut file = File::open("random-table.bin")?;
     let schema = Schema::read(&mut file)?;
     println!("{}", schema.table_name);
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn read(reader: &mut dyn std::io::Read) -> Result<Self> {
-        let mut reader = Reader::new(reader)?;
-        let mut columns = Vec::new();
-        while reader.more_column_data() {
-            columns.push(reader.get_column()?);
-        }
-        Ok(Schema {
-            table_name: reader.table_name().to_owned(),
-            columns: columns.into_boxed_slice(),
-        })
+        Self::from_reader(Reader::new(reader)?)
+    }
+
+    /**
+    Reads only the parts of a table needed to extract its schema, seeking past
+    the rest
+
+    Only the header, the column data, and the string data (which holds the
+    column names) are read. The rows and blobs are skipped, which makes this
+    much faster than [`Schema::read`] for large tables.
+
+    The table must start at the current position of the stream. Afterwards,
+    the stream is left at the end of the table, so tables stored back to back
+    can be scanned one after another.
+
+    To get the number of rows and the size of each section in the same pass,
+    use [`SchemaWithConstants::read_seek`] (see [`TableStats`]).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Schema;
+    let mut file = File::open("huge-table.bin")?;
+    let schema = Schema::read_seek(&mut file)?;
+    println!("{schema}");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_seek(stream: impl Read + Seek) -> Result<Self> {
+        read_seek_with(stream, Self::from_reader)
+    }
+
+    /**
+    Extracts the schema of a table that is already in memory
+
+    Like [`Schema::read_seek`], the rows and blobs aren't touched.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Schema;
+    let data = std::fs::read("random-table.bin")?;
+    let schema = Schema::from_bytes(&data)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_reader(Reader::from_bytes(data)?)
+    }
+
+    /**
+    Reads a UTF table packet (like the header of a CPK or ACB file) and
+    extracts the schema of its table, verifying that it has the given 4-byte
+    prefix
+
+    The table is decrypted first if it's encrypted, just like it is by
+    [`Packet::read_packet`](crate::Packet::read_packet).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Schema;
+    let mut file = File::open("game.cpk")?;
+    let schema = Schema::read_packet(&mut file, b"CPK ")?;
+    println!("{schema}");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet(reader: &mut dyn std::io::Read, prefix: &[u8; 4]) -> Result<Self> {
+        Self::read_packet_with_options(reader, prefix, ReaderOptions::default())
+    }
+
+    /**
+    Reads a UTF table packet and extracts the schema of its table, verifying
+    that it has the given 4-byte prefix, and reading its table with the given
+    options (like a different byte order)
+
+    The options' size limits apply to the packet too (see
+    [`Packet::read_packet_with_options`](crate::Packet::read_packet_with_options)).
+
+    # Example
+    ```
+    # use criware_utf::{Endianness, ReaderOptions, Schema, Table, utf_table};
+    #[utf_table(endian = "little")]
+    struct Tab {
+        row_value: i64,
+    }
+
+    # let mut data = Vec::new();
+    # Tab::new().write_packet(&mut data, b"TAB ", true)?;
+    let options = ReaderOptions {
+        endianness: Endianness::Little,
+        ..Default::default()
+    };
+    let schema = Schema::read_packet_with_options(&mut data.as_slice(), b"TAB ", options)?;
+    assert_eq!(schema.table_name, "Tab");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_with_options(
+        reader: &mut dyn std::io::Read,
+        prefix: &[u8; 4],
+        options: ReaderOptions,
+    ) -> Result<Self> {
+        let data = read_packet_data(reader, Some(prefix), None, &options)?;
+        Self::from_reader(Reader::from_bytes_with_options(&data.table, options)?)
+    }
+
+    /**
+    Reads a UTF table packet and extracts the schema of its table, verifying
+    that it has the given 4-byte prefix, and decrypting it with the given
+    cipher if it's encrypted (see [`CriCipher`](crate::CriCipher))
+
+    # Example
+    ```
+    # use criware_utf::{CriXorCipher, Packet, Schema, utf_table};
+    # #[utf_table]
+    # struct Header {}
+    let cipher = CriXorCipher {
+        seed: 0x5f3d,
+        multiplier: 0x4115,
+    };
+    # let mut data = Vec::new();
+    # let mut packet = Packet::<Header>::new(b"CPK ");
+    # packet.enable_encryption();
+    # packet.write_packet_with_cipher(&mut data, &cipher)?;
+    let schema = Schema::read_packet_with_cipher(&mut data.as_slice(), b"CPK ", &cipher)?;
+    assert_eq!(schema.table_name, "Header");
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn read_packet_with_cipher(
+        reader: &mut dyn std::io::Read,
+        prefix: &[u8; 4],
+        cipher: &dyn CriCipher,
+    ) -> Result<Self> {
+        let data = read_packet_data(
+            reader,
+            Some(prefix),
+            Some(cipher),
+            &ReaderOptions::default(),
+        )?;
+        Self::from_bytes(&data.table)
+    }
+
+    fn from_reader(reader: Reader) -> Result<Self> {
+        read_columns(reader, false).map(|table| table.schema)
+    }
+}
+
+/**
+Reads a table as whichever of several table structs matches its schema best
+
+Takes the table's [`Schema`], something to read the table from (a
+`&mut dyn Read`, positioned at the start of the table), and a list of
+candidate [`DescribedTable`] types, each mapped to a function that wraps it in a common
+type (usually an enum variant). The candidate is picked with
+[`Schema::best_match`], and the result is a [`Result`](crate::Result) of the
+common type. If none of the candidates can read the table,
+[`Error::WrongTableSchema`] is returned.
+
+# Example
+```no_run
+use std::io::{Seek, SeekFrom};
+
+use criware_utf::{Schema, select_schema, utf_table};
+
+#[utf_table(table_name = "CoolTable")]
+struct CoolTableV1 {
+    id: i64,
+    name: String,
+}
+
+#[utf_table(table_name = "CoolTable")]
+struct CoolTableV2 {
+    id: i64,
+    name: String,
+    #[column_name = "Crc32"]
+    crc: u32,
+}
+
+enum CoolTable {
+    V1(CoolTableV1),
+    V2(CoolTableV2),
+}
+
+let mut file = std::fs::File::open("table.bin")?;
+let schema = Schema::read(&mut file)?;
+file.seek(SeekFrom::Start(0))?;
+let table = select_schema!(&schema, &mut file, {
+    CoolTableV1 => CoolTable::V1,
+    CoolTableV2 => CoolTable::V2,
+})?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[macro_export]
+macro_rules! select_schema {
+    ($schema:expr, $reader:expr, { $($table:ty => $wrap:expr),+ $(,)? }) => {{
+        let candidates = [$(<$table as $crate::DescribedTable>::table_schema()),+];
+        let readers: &[&dyn Fn(&mut dyn ::std::io::Read) -> $crate::Result<_>] = &[$(
+            &|reader| <$table as $crate::Table>::read(reader).map($wrap)
+        ),+];
+        match $crate::Schema::best_match($schema, &candidates) {
+            Some(index) => readers[index]($reader),
+            None => Err($crate::Error::WrongTableSchema),
+        }
+    }};
+}
+
+/// Reads the schema and stats of a table, along with the values of its
+/// constant columns if `keep_constants` is `true`
+fn read_columns(mut reader: Reader, keep_constants: bool) -> Result<SchemaWithConstants> {
+    let stats = reader.stats();
+    let mut columns = Vec::new();
+    let mut constants = Vec::new();
+    while reader.more_column_data() {
+        let (column, value) = reader.get_column(keep_constants)?;
+        if let Some(value) = value {
+            constants.push((column.name.clone(), value));
+        }
+        columns.push(column);
+    }
+    let schema = Schema {
+        table_name: reader.table_name().to_owned(),
+        columns: columns.into_boxed_slice(),
+    };
+    Ok(SchemaWithConstants {
+        schema,
+        constants,
+        stats,
+    })
+}
+
+/// Reads from a table at the current position of a stream (see
+/// [`Schema::read_seek`]), and leaves the stream at the end of the table
+fn read_seek_with<T>(
+    mut stream: impl Read + Seek,
+    read: impl FnOnce(Reader) -> Result<T>,
+) -> Result<T> {
+    let start = stream.stream_position().io("@UTF header")?;
+    let reader = Reader::new_seek(&mut stream)?;
+    let end = start + 8 + reader.table_size() as u64;
+    let value = read(reader)?;
+    stream.seek(SeekFrom::Start(end)).io("end of UTF table")?;
+    Ok(value)
+}
+
+/**
+The schema of a table, along with the values of its constant columns and the
+sizes declared in its header
+
+Constant values are stored alongside the column definitions, so they're read
+with the schema at little extra cost. This is useful for showing the settings
+a table holds (like the `Align` of a CPK header) without knowing its full
+layout. The header is read first either way, so its [`TableStats`] come for
+free.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::{AnyValue, SchemaWithConstants};
+let mut file = File::open("cpk-header.bin")?;
+let schema = SchemaWithConstants::read_seek(&mut file)?;
+if let Some(AnyValue::U16(align)) = schema.constant("Align") {
+    println!("files are aligned to {align} bytes");
+}
+print!("{schema}");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone)]
+pub struct SchemaWithConstants {
+    /// The schema of the table
+    pub schema: Schema,
+    /// The name and value of every constant column, listed in the order they
+    /// appear
+    pub constants: Vec<(String, AnyValue)>,
+    /// The number of rows and the size of each section of the table
+    pub stats: TableStats,
+}
+
+impl SchemaWithConstants {
+    /// Returns the value of the constant column with the given name, if it
+    /// exists
+    ///
+    pub fn constant(&self, name: &str) -> Option<&AnyValue> {
+        self.constants
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Checks the schema and constant values for anything official tools
+    /// never produce (see [`Schema::validate`])
+    ///
+    /// Along with the schema's warnings, empty constant blobs are reported.
+    ///
+    pub fn validate(&self) -> Vec<SchemaWarning> {
+        let mut warnings = self.schema.validate();
+        for (name, value) in &self.constants {
+            if let AnyValue::Blob(blob) = value
+                && blob.is_empty()
+            {
+                warnings.push(SchemaWarning::EmptyConstantBlob(name.clone()));
+            }
+        }
+        warnings
+    }
+
+    /// Reads a table and extracts its schema and constant values (see
+    /// [`Schema::read`])
+    ///
+    pub fn read(reader: &mut dyn std::io::Read) -> Result<Self> {
+        Self::from_reader(Reader::new(reader)?)
+    }
+
+    /// Reads only the parts of a table needed to extract its schema and
+    /// constant values, seeking past the rest (see [`Schema::read_seek`])
+    ///
+    pub fn read_seek(stream: impl Read + Seek) -> Result<Self> {
+        read_seek_with(stream, Self::from_reader)
+    }
+
+    /// Extracts the schema and constant values of a table that is already in
+    /// memory (see [`Schema::from_bytes`])
+    ///
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_reader(Reader::from_bytes(data)?)
+    }
+
+    /// Reads a UTF table packet and extracts the schema and constant values
+    /// of its table (see [`Schema::read_packet`])
+    ///
+    pub fn read_packet(reader: &mut dyn std::io::Read, prefix: &[u8; 4]) -> Result<Self> {
+        Self::read_packet_with_options(reader, prefix, ReaderOptions::default())
+    }
+
+    /// Reads a UTF table packet and extracts the schema and constant values
+    /// of its table, with the given options (see
+    /// [`Schema::read_packet_with_options`])
+    ///
+    pub fn read_packet_with_options(
+        reader: &mut dyn std::io::Read,
+        prefix: &[u8; 4],
+        options: ReaderOptions,
+    ) -> Result<Self> {
+        let data = read_packet_data(reader, Some(prefix), None, &options)?;
+        Self::from_reader(Reader::from_bytes_with_options(&data.table, options)?)
+    }
+
+    /// Reads a UTF table packet and extracts the schema and constant values
+    /// of its table, decrypting it with the given cipher if it's encrypted
+    /// (see [`Schema::read_packet_with_cipher`])
+    ///
+    pub fn read_packet_with_cipher(
+        reader: &mut dyn std::io::Read,
+        prefix: &[u8; 4],
+        cipher: &dyn CriCipher,
+    ) -> Result<Self> {
+        let data = read_packet_data(
+            reader,
+            Some(prefix),
+            Some(cipher),
+            &ReaderOptions::default(),
+        )?;
+        Self::from_bytes(&data.table)
+    }
+
+    fn from_reader(reader: Reader) -> Result<Self> {
+        read_columns(reader, true)
+    }
+}
+
+/// Writes a constant value the way [`SchemaWithConstants`] lists it
+fn fmt_constant(value: &AnyValue, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match value {
+        AnyValue::U8(value) => write!(f, "{value}"),
+        AnyValue::I8(value) => write!(f, "{value}"),
+        AnyValue::U16(value) => write!(f, "{value}"),
+        AnyValue::I16(value) => write!(f, "{value}"),
+        AnyValue::U32(value) => write!(f, "{value}"),
+        AnyValue::I32(value) => write!(f, "{value}"),
+        AnyValue::U64(value) => write!(f, "{value}"),
+        AnyValue::I64(value) => write!(f, "{value}"),
+        AnyValue::F32(value) => write!(f, "{value}"),
+        AnyValue::F64(value) => write!(f, "{value}"),
+        AnyValue::String(value) => write!(f, "{value:?}"),
+        AnyValue::Blob(value) => write!(f, "<{} bytes>", value.len()),
+        AnyValue::Unknown { bytes, .. } => write!(f, "{bytes:02x?}"),
+    }
+}
+
+/// Returns the text of a cell in [`Schema::to_ascii_table`]
+fn cell_text(value: Option<&AnyValue>) -> String {
+    struct Constant<'a>(&'a AnyValue);
+
+    impl fmt::Display for Constant<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt_constant(self.0, f)
+        }
+    }
+
+    match value {
+        None => String::new(),
+        Some(AnyValue::String(value)) => {
+            let mut text = String::with_capacity(value.len());
+            for c in value.chars() {
+                if c.is_control() {
+                    text.extend(c.escape_default());
+                } else {
+                    text.push(c);
+                }
+            }
+            text
+        }
+        Some(value) => Constant(value).to_string(),
+    }
+}
+
+/// Lists the schema like [`Schema`] does, with the value of each constant
+/// column after it (like `Align: u16 (constant) = 2048`)
+///
+impl fmt::Display for SchemaWithConstants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.schema.table_name)?;
+        let mut constants = self.constants.iter();
+        for column in &self.schema.columns {
+            write!(
+                f,
+                "  {}: {} ({})",
+                column.name, column.value_kind, column.storage_format
+            )?;
+            if column.storage_format == ColumnStorageFormat::Constant
+                && let Some((_, value)) = constants.next()
+            {
+                f.write_str(" = ")?;
+                fmt_constant(value, f)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
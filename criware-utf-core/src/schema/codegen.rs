@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+use super::{ColumnStorageFormat, Schema};
+use crate::ValueKind;
+
+/// Words that can't be used as field names
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Returns the Rust type a column of the given kind is read as
+fn rust_type(kind: ValueKind) -> &'static str {
+    match kind {
+        ValueKind::U8 => "u8",
+        ValueKind::I8 => "i8",
+        ValueKind::U16 => "u16",
+        ValueKind::I16 => "i16",
+        ValueKind::U32 => "u32",
+        ValueKind::I32 => "i32",
+        ValueKind::U64 => "u64",
+        ValueKind::I64 => "i64",
+        ValueKind::F32 => "f32",
+        ValueKind::F64 => "f64",
+        ValueKind::STR => "String",
+        ValueKind::BLOB => "Vec<u8>",
+    }
+}
+
+/// Converts a column name to snake case (e.g. "FileSize" => "file_size",
+/// "TocCRC" => "toc_crc", "CueIDs" => "cue_ids")
+///
+/// Anything that isn't a letter or digit becomes an underscore, so the result
+/// is always a valid field name (apart from keywords and leading digits).
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !result.is_empty() && !result.ends_with('_') {
+                result.push('_');
+            }
+            continue;
+        }
+        if c.is_uppercase() && index > 0 && !result.ends_with('_') {
+            let previous = chars[index - 1];
+            // the end of an acronym, unless it's just plural (like "CueIDs")
+            let is_lower = |index: usize| chars.get(index).is_some_and(|c| c.is_lowercase());
+            let next_is_lower =
+                is_lower(index + 1) && (chars[index + 1] != 's' || is_lower(index + 2));
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    while result.ends_with('_') {
+        result.pop();
+    }
+    result
+}
+
+/// Converts a field name to the column name the `utf_table` macro gives it by
+/// default (e.g. "file_size" => "FileSize")
+fn upper_camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for word in name.split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.extend(chars);
+        }
+    }
+    result
+}
+
+/// Returns a string literal holding the given text
+fn string_literal(text: &str) -> String {
+    format!("{text:?}")
+}
+
+impl Schema {
+    /**
+    Generates a `#[utf_table]` struct definition that reads tables with this
+    schema
+
+    Field names are the column names converted to snake case. Where the
+    macro wouldn't convert a field name back to the same column name (like
+    "TocCRC", which would become "TocCrc"), a `#[column_name]` attribute is
+    added. Columns stored as zero are made `#[optional]`, and are assumed to
+    be rowed, since there's no telling how they're stored when they do have a
+    value.
+
+    Strings are read as [`String`] and blobs as [`Vec<u8>`], so the struct is
+    a starting point: fields can be changed to more specific [`Value`](crate::Value)
+    types afterwards. The `utf_table` macro is expected to be in scope.
+
+    # Example
+    ```
+    # use criware_utf_core::{SchemaBuilder, ValueKind};
+    let schema = SchemaBuilder::new("CpkHeader")
+        .constant_column("Version", ValueKind::U16)
+        .rowed_column("FileName", ValueKind::STR)
+        .rowed_column("TocCRC", ValueKind::U32)
+        .zero_column("Comment", ValueKind::STR)
+        .build();
+    assert_eq!(
+        schema.to_rust_struct("Header"),
+        r#"#[utf_table(table_name = "CpkHeader")]
+    struct Header {
+        #[constant]
+        version: u16,
+        file_name: String,
+        #[column_name = "TocCRC"]
+        toc_crc: u32,
+        #[optional]
+        comment: String,
+    }
+    "#
+    );
+    ```
+     */
+    pub fn to_rust_struct(&self, name: &str) -> String {
+        let mut code = if self.table_name == name {
+            String::from("#[utf_table]\n")
+        } else {
+            format!(
+                "#[utf_table(table_name = {})]\n",
+                string_literal(&self.table_name)
+            )
+        };
+        code.push_str(&format!("struct {name} {{\n"));
+        let mut used = HashSet::new();
+        for column in &self.columns {
+            let mut field = snake_case(&column.name);
+            if field.is_empty() || field.starts_with(|c: char| c.is_ascii_digit()) {
+                field.insert_str(0, "column_");
+            }
+            if KEYWORDS.contains(&field.as_str()) {
+                field.push('_');
+            }
+            if used.contains(&field) {
+                let mut suffix = 2;
+                while used.contains(&format!("{field}_{suffix}")) {
+                    suffix += 1;
+                }
+                field = format!("{field}_{suffix}");
+            }
+            used.insert(field.clone());
+            if upper_camel_case(&field) != column.name {
+                code.push_str(&format!(
+                    "    #[column_name = {}]\n",
+                    string_literal(&column.name)
+                ));
+            }
+            match column.storage_format {
+                ColumnStorageFormat::Zero => code.push_str("    #[optional]\n"),
+                ColumnStorageFormat::Constant => code.push_str("    #[constant]\n"),
+                ColumnStorageFormat::Rowed => {}
+            }
+            code.push_str(&format!("    {field}: {},\n", rust_type(column.value_kind)));
+        }
+        code.push_str("}\n");
+        code
+    }
+}
@@ -1,7 +1,47 @@
-use crate::{Result, packet::Packet};
+use crate::{Error, Reader, ReaderOptions, Result, TableSchema, WriterOptions, packet::Packet};
 
-/// A UTF table that can be read, written, and constructed from nothing
-///
+/**
+A UTF table that can be read, written, and constructed from nothing
+
+This is usually implemented by the `utf_table` macro, but it can be
+implemented by hand too. Everything without a default is required, along
+with at least one of [`Table::read`] and [`Table::read_from_reader`] (each
+one is implemented with the other by default).
+
+# Example
+```
+# use criware_utf::{Reader, Result, Table, Writer, WriterOptions};
+struct Settings {
+    version: u32,
+}
+
+impl Table for Settings {
+    fn new() -> Self {
+        Settings { version: 0 }
+    }
+    fn read_from_reader(mut reader: Reader<'_>) -> Result<Self> {
+        let version = reader.read_constant_column("Version")?;
+        Ok(Settings { version })
+    }
+    fn write(&self, writer: &mut dyn std::io::Write) -> Result<()> {
+        let mut table_writer = Writer::new("Settings");
+        table_writer.push_constant_column("Version", &self.version)?;
+        table_writer.end(writer, 0, 0)?;
+        Ok(())
+    }
+}
+
+let data = Settings { version: 3 }.write_to_vec()?;
+assert_eq!(Settings::read_from_slice(&data)?.version, 3);
+// only the options from `Table::writer_options` are supported
+let options = WriterOptions {
+    table_align: 16,
+    ..Settings::writer_options()
+};
+assert!(Settings::new().write_with_options(&mut Vec::new(), options).is_err());
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+ */
 pub trait Table: Sized {
     /**
     Creates a new table with default constant values and no rows
@@ -25,15 +65,310 @@ pub trait Table: Sized {
      */
     fn new() -> Self;
 
+    /**
+    Returns the options used to read tables of this type
+
+    Tables generated by the `utf_table` macro set the byte order and string
+    encoding given to the macro. This is used when a [`Reader`] is created on
+    the table's behalf, like in [`AsyncTable::read_async`].
+
+    # Example
+    ```
+    # use criware_utf::{Endianness, Table, utf_table};
+    #[utf_table(endian = "little")]
+    struct Tab {
+        row_value: i64,
+    }
+
+    fn main() {
+        assert_eq!(Tab::reader_options().endianness, Endianness::Little);
+    }
+    ```
+     */
+    fn reader_options() -> ReaderOptions {
+        ReaderOptions::default()
+    }
+
+    /**
+    Returns the options used to write tables of this type
+
+    Tables generated by the `utf_table` macro set the byte order and string
+    encoding given to the macro. This is used by [`Table::write`].
+
+    # Example
+    ```
+    # use criware_utf::{Encoding, Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        row_value: String,
+    }
+
+    fn main() {
+        assert_eq!(Tab::writer_options().encoding, Encoding::Utf8);
+    }
+    ```
+     */
+    fn writer_options() -> WriterOptions {
+        WriterOptions::default()
+    }
+
     /**
     Reads a table from the given stream
 
     If the table is malformed, or if the table's schema does not match this
     type, then this function will fail.
 
+    By default, this creates a [`Reader`] with [`Table::reader_options`] and
+    passes it to [`Table::read_from_reader`].
+
     # Example
     ```
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        # let data = Tab::new().write_to_vec()?;
+        let mut stream = data.as_slice();
+        let table = Tab::read(&mut stream)?;
+        assert_eq!(table.rows.len(), 0);
+        Ok(())
+    }
+    ```
+     */
+    fn read(reader: &mut dyn std::io::Read) -> Result<Self> {
+        Self::read_from_reader(Reader::new_with_options(reader, Self::reader_options())?)
+    }
+
+    /**
+    Reads a table using an existing [`Reader`]
+
+    This allows for tables to be read with a `Reader` that was created in a
+    special way, like one that reads from a seekable stream on demand.
+
+    If the table is malformed, or if the table's schema does not match this
+    type, then this function will fail.
+
+    By default, the whole table is copied out of the reader and read again
+    with [`Table::read`], so options that only the reader knows about (like
+    [`ReaderOptions::skip_rows`]) are ignored. Tables generated by the
+    `utf_table` macro read straight from the reader.
+
+    # Example
+    ```no_run
     # use std::fs::File;
+    # use criware_utf::{Reader, Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::open("huge-table.bin")?;
+        let table = Tab::read_from_reader(Reader::new_seek(file)?)?;
+        // ... do something ...
+        Ok(())
+    }
+    ```
+
+    A table that only implements [`Table::read`] can still be read from a
+    `Reader`:
+    ```
+    # use std::io::{Read, Write};
+    # use criware_utf::{Error, Reader, Result, Table, utf_table};
+    struct Raw(Vec<u8>);
+
+    impl Table for Raw {
+        fn new() -> Self {
+            Raw(Vec::new())
+        }
+        fn read(reader: &mut dyn Read) -> Result<Self> {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).map_err(Error::IOError)?;
+            Ok(Raw(data))
+        }
+        fn write(&self, writer: &mut dyn Write) -> Result<()> {
+            writer.write_all(&self.0).map_err(Error::IOError)
+        }
+    }
+
+    # #[utf_table]
+    # struct Tab { id: u32 }
+    # let data = Tab::new().write_to_vec()?;
+    let table = Raw::read_from_reader(Reader::from_bytes(&data)?)?;
+    assert_eq!(table.0, data);
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    fn read_from_reader(mut reader: Reader<'_>) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.inspect_sections(|_, bytes| data.extend_from_slice(bytes))?;
+        Self::read(&mut data.as_slice())
+    }
+
+    /**
+    Reads as much of a table as possible using an existing [`Reader`]
+
+    Reading stops at the first error, which is returned along with every row
+    that was read before it. The failing row is not included, so its index is
+    the number of rows in the returned table. If the error happens before any
+    rows are read (e.g. the schema doesn't match), the table is the same as
+    [`Table::new`].
+
+    Only tables generated by the `utf_table` macro keep the rows read before
+    an error. By default, this returns [`Table::new`] along with the error if
+    [`Table::read_from_reader`] fails.
+
+    To recover rows from a truncated table, the `Reader` has to allow it (see
+    [`ReaderOptions::allow_truncated`]), which
+    [`Table::read_salvage_from_slice`] does.
+
+    # Example
+    ```
+    # use criware_utf::{Reader, ReaderOptions, Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        # let mut table = Tab::new();
+        # table.rows.extend((0..10).map(|row_value| TabRow { row_value }));
+        # let mut data = table.write_to_vec()?;
+        # data.truncate(200);
+        let options = ReaderOptions {
+            allow_truncated: true,
+            ..Tab::reader_options()
+        };
+        let (table, error) = Tab::read_salvage(Reader::from_bytes_with_options(&data, options)?);
+        if let Some(error) = error {
+            println!("row {} is damaged: {error}", table.rows.len());
+        }
+        Ok(())
+    }
+    ```
+     */
+    fn read_salvage(reader: Reader<'_>) -> (Self, Option<Error>) {
+        match Self::read_from_reader(reader) {
+            Ok(table) => (table, None),
+            Err(error) => (Self::new(), Some(error)),
+        }
+    }
+
+    /**
+    Reads as much of a table as possible from a slice of memory, even if the
+    table is truncated
+
+    See [`Table::read_salvage`] and [`ReaderOptions::allow_truncated`]. Names
+    cut off from the end of a truncated table aren't checked, so only the
+    number of columns and their types and storage tell whether the table's
+    schema matches this type.
+
+    # Example
+    ```
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let mut table = Tab::new();
+        table.rows.extend((0..10).map(|row_value| TabRow { row_value }));
+        let data = table.write_to_vec()?;
+        // the rows start 46 bytes in and are 8 bytes each, so this cuts the
+        // table off in the middle of the fifth row
+        let (table, error) = Tab::read_salvage_from_slice(&data[..46 + 8 * 4 + 4]);
+        assert_eq!(table.rows.len(), 4);
+        assert!(error.is_some());
+        Ok(())
+    }
+    ```
+     */
+    fn read_salvage_from_slice(data: &[u8]) -> (Self, Option<Error>) {
+        let options = ReaderOptions {
+            allow_truncated: true,
+            ..Self::reader_options()
+        };
+        match Reader::from_bytes_with_options(data, options) {
+            Ok(reader) => Self::read_salvage(reader),
+            Err(error) => (Self::new(), Some(error)),
+        }
+    }
+
+    /**
+    Reads part of a table from the given seekable stream
+
+    The first `skip_rows` rows are skipped over, and at most `max_rows` rows
+    after them are read. The table is read on demand (see
+    [`Reader::new_seek`]): only the header, the column data, the string data,
+    and the rows being read (with their blobs) are read from the stream, which
+    makes this much faster than [`Table::read`] for previews of large tables.
+
+    The table must start at the current position of the stream. The position
+    of the stream afterwards is unspecified. Tables that only implement
+    [`Table::read`] are read whole (see [`Table::read_from_reader`]).
+
+    If the table is malformed, or if the table's schema does not match this
+    type, then this function will fail.
+
+    # Example
+    ```
+    # use std::io::Cursor;
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    # let mut table = Tab::new();
+    # table.constants.constant = 7;
+    # table.rows = (0..1000).map(|row_value| TabRow { row_value }).collect();
+    # let mut data = Vec::new();
+    # table.write(&mut data)?;
+    // show rows 10 to 14
+    let preview = Tab::read_partial(Cursor::new(data), 10, 5)?;
+    assert_eq!(preview.constants.constant, 7);
+    let values: Vec<i64> = preview.rows.iter().map(|row| row.row_value).collect();
+    assert_eq!(values, [10, 11, 12, 13, 14]);
+    # Ok::<(), criware_utf::Error>(())
+    ```
+     */
+    fn read_partial(
+        reader: impl std::io::Read + std::io::Seek,
+        skip_rows: u32,
+        max_rows: u32,
+    ) -> Result<Self> {
+        Self::read_from_reader(Reader::new_seek_with_options(
+            reader,
+            ReaderOptions {
+                skip_rows,
+                max_rows: Some(max_rows),
+                ..Self::reader_options()
+            },
+        )?)
+    }
+
+    /**
+    Reads a table from a slice of memory
+
+    Tables generated by the `utf_table` macro read directly from the slice,
+    without copying it first.
+
+    # Example
+    ```no_run
     # use criware_utf::{Table, utf_table};
     #[utf_table]
     struct Tab {
@@ -43,21 +378,22 @@ pub trait Table: Sized {
     }
 
     fn main() -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = File::open("table.bin")?;
-        let table = Tab::read(&mut file)?;
+        let data = std::fs::read("table.bin")?;
+        let table = Tab::read_from_slice(&data)?;
         // ... do something ...
         Ok(())
     }
     ```
      */
-    fn read(reader: &mut dyn std::io::Read) -> Result<Self>;
+    fn read_from_slice(data: &[u8]) -> Result<Self> {
+        Self::read(&mut std::io::Cursor::new(data))
+    }
 
     /**
     Writes a table to the given stream
 
     # Example
     ```
-    # use std::fs::File;
     # use criware_utf::{Table, utf_table};
     #[utf_table]
     struct Tab {
@@ -66,16 +402,135 @@ pub trait Table: Sized {
         row_value: i64,
     }
 
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let mut data = Vec::new();
+        let mut table = Tab::new();
+        table.rows.push(TabRow { row_value: 5 });
+        table.write(&mut data)?;
+        assert_eq!(&data[0..4], b"@UTF");
+        Ok(())
+    }
+    ```
+     */
+    fn write(&self, writer: &mut dyn std::io::Write) -> Result<()>;
+
+    /**
+    Writes a table to the given stream with the given options
+
+    This allows a table to be written with a different string encoding than
+    the one it was declared with, like when a table read from a modern game
+    is repacked for an older engine that expects Shift-JIS strings.
+
+    Tables generated by the `utf_table` macro accept any options. By default,
+    the table is written with [`Table::write`] if the options are the same as
+    [`Table::writer_options`], and [`Error::UnsupportedWriterOptions`] is
+    returned otherwise.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Encoding, Table, WriterOptions, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: String,
+    }
+
     fn main() -> Result<(), Box<dyn std::error::Error>> {
         let mut file = File::create("table.bin")?;
         let table = Tab::new();
         // ... do something ...
-        table.write(&mut file)?;
+        table.write_with_options(
+            &mut file,
+            WriterOptions {
+                encoding: Encoding::ShiftJis,
+                ..Tab::writer_options()
+            },
+        )?;
         Ok(())
     }
     ```
      */
-    fn write(&self, writer: &mut dyn std::io::Write) -> Result<()>;
+    fn write_with_options(
+        &self,
+        writer: &mut dyn std::io::Write,
+        options: WriterOptions,
+    ) -> Result<()> {
+        if options == Self::writer_options() {
+            self.write(writer)
+        } else {
+            Err(Error::UnsupportedWriterOptions)
+        }
+    }
+
+    /**
+    Writes a table to the given stream, so that equal tables are always
+    written the same way
+
+    The output only depends on the contents of the table and
+    [`Table::writer_options`]: writing the same table twice, or two tables
+    with the same contents, always produces the same bytes. Unlike
+    [`Table::write`], anything recorded when the table was read (see
+    [`RoundTripContext`](crate::RoundTripContext)) is ignored, apart from
+    which optional columns are included.
+
+    This is useful for build systems that cache packed assets by their hash.
+    See [`check_identical_writes`](crate::check_identical_writes).
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = File::create("table.bin")?;
+        let table = Tab::new();
+        table.write_deterministic(&mut file)?;
+        Ok(())
+    }
+    ```
+     */
+    fn write_deterministic(&self, writer: &mut dyn std::io::Write) -> Result<()> {
+        self.write_with_options(writer, Self::writer_options())
+    }
+
+    /**
+    Writes a table to a new [`Vec`]
+
+    This is the counterpart of [`Table::read_from_slice`], for tables stored
+    inside other containers.
+
+    # Example
+    ```
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let table = Tab::new();
+        let data = table.write_to_vec()?;
+        let table = Tab::read_from_slice(&data)?;
+        assert_eq!(table.rows.len(), 0);
+        Ok(())
+    }
+    ```
+     */
+    fn write_to_vec(&self) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.write(&mut data)?;
+        Ok(data)
+    }
 
     /**
     Reads a UTF table packet from the given stream, verifying that it has
@@ -83,7 +538,6 @@ pub trait Table: Sized {
 
     # Example
     ```
-    # use std::fs::File;
     # use criware_utf::{Packet, Table, utf_table};
     #[utf_table]
     struct Tab {
@@ -93,17 +547,242 @@ pub trait Table: Sized {
     }
 
     fn main() -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = File::open("table.bin")?;
-        let table: Packet<Tab> = Tab::read_packet(&mut file, b"TAB ")?;
-        // ... do something ...
+        # let mut data = Vec::new();
+        # Tab::new().write_packet(&mut data, b"TAB ", true)?;
+        let mut stream = data.as_slice();
+        let table: Packet<Tab> = Tab::read_packet(&mut stream, b"TAB ")?;
+        assert!(table.is_encrypted());
         Ok(())
     }
     ```
      */
-    fn read_packet(
-        reader: &mut dyn std::io::Read,
-        prefix: &'static [u8; 4],
-    ) -> Result<Packet<Self>> {
+    fn read_packet(reader: &mut dyn std::io::Read, prefix: &[u8; 4]) -> Result<Packet<Self>> {
         Packet::<Self>::read_packet(reader, prefix)
     }
+
+    /**
+    Writes this table as a UTF table packet with the given 4-byte prefix,
+    optionally encrypting it
+
+    This is the same as writing a [`Packet`] containing the table, without
+    having to move the table into one.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf::{Table, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() -> Result<(), Box<dyn std::error::Error>> {
+        let table = Tab::new();
+        let mut file = File::create("table.bin")?;
+        table.write_packet(&mut file, b"TAB ", true)?;
+        Ok(())
+    }
+    ```
+     */
+    fn write_packet(
+        &self,
+        writer: &mut dyn std::io::Write,
+        prefix: &[u8; 4],
+        encrypted: bool,
+    ) -> Result<()> {
+        crate::packet::write_packet(
+            self,
+            writer,
+            prefix,
+            0,
+            encrypted,
+            None,
+            crate::PacketPadding::None,
+        )
+    }
+}
+
+/**
+Checks that two tables are written to the exact same bytes by
+[`Table::write_deterministic`]
+
+Each table is written twice, so a table that isn't written the same way
+every time is caught too. On a mismatch, [`Error::WritesDiffer`] is returned
+with the offset of the first byte that differs. Errors from writing either
+table are returned as-is.
+
+# Example
+```
+# use criware_utf::{Table, check_identical_writes, utf_table};
+#[utf_table]
+struct Tab {
+    name: String,
+}
+
+# fn main() -> Result<(), Box<dyn std::error::Error>> {
+let mut first = Tab::new();
+first.rows.push(TabRow { name: "a".to_owned() });
+let data = first.write_to_vec()?;
+let mut second = Tab::read_from_slice(&data)?;
+check_identical_writes(&first, &second)?;
+
+second.rows[0].name = "b".to_owned();
+assert!(check_identical_writes(&first, &second).is_err());
+# Ok(())
+# }
+```
+ */
+pub fn check_identical_writes<T: Table>(first: &T, second: &T) -> Result<()> {
+    let write = |table: &T| -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        table.write_deterministic(&mut data)?;
+        Ok(data)
+    };
+    let expected = write(first)?;
+    for (name, table) in [("first", first), ("second", second), ("second", second)] {
+        let data = write(table)?;
+        if data == expected {
+            continue;
+        }
+        let offset = expected
+            .iter()
+            .zip(&data)
+            .position(|(a, b)| a != b)
+            .unwrap_or(expected.len().min(data.len()));
+        return Err(Error::WritesDiffer(name, offset as u64));
+    }
+    Ok(())
+}
+
+/**
+A table that is always stored in packets with the same 4-byte prefix
+
+With the prefix part of the type, packets of the table can be created and read
+without passing the prefix around (see [`Packet::read`]), so there's no way to
+pass the wrong one. This is implemented by the `utf_table` macro when its
+`prefix` option is given.
+
+`PREFIX` is only used where no other prefix is given. Anything that takes a
+prefix at runtime (like [`Packet::set_prefix`], [`Packet::read_packet`] or
+[`Table::write_packet`]) overrides it, and a packet is always written with its
+own prefix (see [`Packet::prefix`]), wherever that came from.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf::{Packet, PrefixedTable, utf_table};
+#[utf_table(prefix = "TOC ")]
+struct Toc {
+    file_name: String,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(Toc::PREFIX, *b"TOC ");
+    let mut file = File::open("toc.bin")?;
+    let toc = Packet::<Toc>::read(&mut file)?;
+    // ... do something ...
+    toc.write_packet(&mut File::create("toc_new.bin")?)?;
+    Ok(())
+}
+```
+
+Overriding the prefix at runtime:
+```
+# use criware_utf::{Packet, utf_table};
+# #[utf_table(prefix = "TOC ")]
+# struct Toc {
+#     file_name: String,
+# }
+let mut packet = Packet::<Toc>::default();
+assert_eq!(packet.prefix(), *b"TOC ");
+packet.set_prefix(*b"ETOC");
+# let mut data = Vec::new();
+packet.write_packet(&mut data)?;
+assert_eq!(&data[0..4], b"ETOC");
+assert!(Packet::<Toc>::read(&mut data.as_slice()).is_err());
+# Ok::<(), criware_utf::Error>(())
+```
+*/
+pub trait PrefixedTable: Table {
+    /// The prefix of the packets this table is stored in
+    ///
+    const PREFIX: [u8; 4];
+}
+
+/**
+A table that describes the table name and columns it expects to read
+
+This is implemented by the `utf_table` macro. It's separate from [`Table`]
+so tables implemented by hand don't have to describe themselves.
+
+See [`TableSchema`], and [`Schema::matches`](crate::Schema::matches) to check
+a table against it.
+*/
+pub trait DescribedTable: Table {
+    /**
+    Returns the table name and columns this table expects to read
+
+    # Example
+    ```
+    # use criware_utf::{DescribedTable, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    fn main() {
+        let schema = Tab::table_schema();
+        assert_eq!(schema.table_name, "Tab");
+        assert_eq!(schema.columns.len(), 2);
+    }
+    ```
+     */
+    fn table_schema() -> TableSchema;
+}
+
+/// Asynchronous reading for every [`Table`]
+///
+/// Requires the `futures-io` feature.
+///
+#[cfg(feature = "futures-io")]
+pub trait AsyncTable: Table {
+    /**
+    Reads a table from the given asynchronous stream
+
+    If the table is malformed, or if the table's schema does not match this
+    type, then this function will fail.
+
+    # Example
+    ```no_run
+    # use criware_utf::{AsyncTable, utf_table};
+    #[utf_table]
+    struct Tab {
+        #[constant]
+        constant: i32,
+        row_value: i64,
+    }
+
+    async fn example(mut stream: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let table = Tab::read_async(&mut stream).await?;
+        // ... do something ...
+        Ok(())
+    }
+    ```
+     */
+    fn read_async(
+        reader: &mut (impl futures_io::AsyncRead + Unpin),
+    ) -> impl Future<Output = Result<Self>>;
+}
+
+#[cfg(feature = "futures-io")]
+impl<T: Table> AsyncTable for T {
+    async fn read_async(reader: &mut (impl futures_io::AsyncRead + Unpin)) -> Result<Self> {
+        Self::read_from_reader(
+            Reader::new_async_with_options(reader, Self::reader_options()).await?,
+        )
+    }
 }
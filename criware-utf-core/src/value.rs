@@ -1,8 +1,45 @@
 use std::borrow::Cow;
 
+use crate::Error;
+
+mod any_value;
+mod blob_handle;
+mod compressed;
+mod cpk;
+mod date_time;
+mod fixed_point;
+mod fixed_str;
+#[cfg(feature = "bitflags")]
+mod flags;
+mod raw_string;
+mod sub_table;
+mod version;
+mod wide_string;
+
+pub use any_value::AnyValue;
+pub use blob_handle::BlobHandle;
+#[cfg(feature = "lzma-rs")]
+pub use compressed::Lzma;
+pub use compressed::{Codec, CompressedBlob, DEFAULT_MAX_DECOMPRESSED_LEN};
+#[cfg(feature = "flate2")]
+pub use compressed::{Deflate, Gzip, Zlib};
+pub use cpk::{CpkOffset, CpkSize};
+pub use date_time::CriDateTime;
+pub use fixed_point::FixedPoint;
+pub use fixed_str::FixedStr;
+#[cfg(feature = "bitflags")]
+#[doc(hidden)]
+pub use flags::__Flags;
+pub use raw_string::RawString;
+pub use sub_table::LazySubTable;
+pub use version::{PackedUtfVersion, UtfVersion};
+pub use wide_string::WideString;
+
 /// All of the primitives that can be stored in a table
 ///
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[repr(u8)]
 pub enum ValueKind {
     U8 = 0,
@@ -14,32 +51,369 @@ pub enum ValueKind {
     U64 = 6,
     I64 = 7,
     F32 = 8,
+    F64 = 9,
+    #[cfg_attr(feature = "serde", serde(rename = "string"))]
     STR = 0xa,
     BLOB = 0xb,
 }
 
+impl ValueKind {
+    /// Returns the kind with the given type flag (the lower half of a
+    /// column's flag byte)
+    pub(crate) const fn from_flag(flag: u8) -> Option<ValueKind> {
+        Some(match flag {
+            0 => ValueKind::U8,
+            1 => ValueKind::I8,
+            2 => ValueKind::U16,
+            3 => ValueKind::I16,
+            4 => ValueKind::U32,
+            5 => ValueKind::I32,
+            6 => ValueKind::U64,
+            7 => ValueKind::I64,
+            8 => ValueKind::F32,
+            9 => ValueKind::F64,
+            0xa => ValueKind::STR,
+            0xb => ValueKind::BLOB,
+            _ => return None,
+        })
+    }
+    /// Returns the space (in bytes) a value of this kind takes up in column
+    /// or row space
+    pub(crate) const fn size(self) -> u32 {
+        match self {
+            ValueKind::U8 | ValueKind::I8 => 1,
+            ValueKind::U16 | ValueKind::I16 => 2,
+            ValueKind::U32 | ValueKind::I32 | ValueKind::F32 | ValueKind::STR => 4,
+            ValueKind::U64 | ValueKind::I64 | ValueKind::F64 | ValueKind::BLOB => 8,
+        }
+    }
+
+    /// Returns the type flag of the kind (the lower half of a column's flag
+    /// byte)
+    ///
+    pub const fn flag(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the space (in bytes) a value of this kind takes up in a row
+    /// (or in the column data section, if it's constant)
+    ///
+    pub const fn size_in_row(self) -> usize {
+        self.size() as usize
+    }
+
+    /// Returns the name of the kind, as used by [`Schema`](crate::Schema)
+    /// listings (like `"u32"` or `"string"`)
+    ///
+    pub const fn name(self) -> &'static str {
+        match self {
+            ValueKind::U8 => "u8",
+            ValueKind::I8 => "i8",
+            ValueKind::U16 => "u16",
+            ValueKind::I16 => "i16",
+            ValueKind::U32 => "u32",
+            ValueKind::I32 => "i32",
+            ValueKind::U64 => "u64",
+            ValueKind::I64 => "i64",
+            ValueKind::F32 => "f32",
+            ValueKind::F64 => "f64",
+            ValueKind::STR => "string",
+            ValueKind::BLOB => "blob",
+        }
+    }
+}
+
+impl std::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/**
+Returns the kind with the type flag in the lower half of the given flag byte
+(the upper half is ignored)
+
+# Example
+```
+# use criware_utf::ValueKind;
+assert_eq!(ValueKind::try_from(0x5a)?, ValueKind::STR);
+assert!(ValueKind::try_from(0x5f).is_err());
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+impl TryFrom<u8> for ValueKind {
+    type Error = Error;
+
+    fn try_from(flag: u8) -> Result<Self, Error> {
+        ValueKind::from_flag(flag & 0x0f).ok_or(Error::InvalidColumnType(flag & 0x0f))
+    }
+}
+
+/// The byte order of the numbers stored in a table
+///
+/// Official tables are always big-endian. Little-endian tables only show up
+/// in a handful of modified games.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// Most significant byte first (the standard)
+    #[default]
+    Big,
+    /// Least significant byte first
+    Little,
+}
+
+impl Endianness {
+    #[inline]
+    pub(crate) fn u16_from(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Endianness::Big => u16::from_be_bytes(bytes),
+            Endianness::Little => u16::from_le_bytes(bytes),
+        }
+    }
+    #[inline]
+    pub(crate) fn u32_from(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            Endianness::Big => u32::from_be_bytes(bytes),
+            Endianness::Little => u32::from_le_bytes(bytes),
+        }
+    }
+    #[inline]
+    pub(crate) fn u16_to(self, value: u16) -> [u8; 2] {
+        match self {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        }
+    }
+    #[inline]
+    pub(crate) fn u32_to(self, value: u32) -> [u8; 4] {
+        match self {
+            Endianness::Big => value.to_be_bytes(),
+            Endianness::Little => value.to_le_bytes(),
+        }
+    }
+}
+
+/// The character encoding of the strings stored in a table
+///
+/// Modern tables use UTF-8. Tables made before it was adopted use Shift-JIS
+/// (more specifically, Microsoft's CP932 variant), which requires the
+/// `encoding_rs` feature.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8 (the standard)
+    #[default]
+    Utf8,
+    /// Shift-JIS/CP932
+    #[cfg(feature = "encoding_rs")]
+    ShiftJis,
+}
+
+impl Encoding {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            #[cfg(feature = "encoding_rs")]
+            Encoding::ShiftJis => "Shift-JIS",
+        }
+    }
+    pub(crate) fn decode(self, bytes: &[u8]) -> crate::Result<Cow<'_, str>> {
+        match self {
+            Encoding::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(value) => Ok(Cow::Borrowed(value)),
+                Err(error) => Err(Error::StringMalformed(error)),
+            },
+            #[cfg(feature = "encoding_rs")]
+            Encoding::ShiftJis => encoding_rs::SHIFT_JIS
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .ok_or(Error::StringUndecodable(self.name())),
+        }
+    }
+    pub(crate) fn encode(self, value: &str) -> crate::Result<Cow<'_, [u8]>> {
+        match self {
+            Encoding::Utf8 => Ok(Cow::Borrowed(value.as_bytes())),
+            #[cfg(feature = "encoding_rs")]
+            Encoding::ShiftJis => match encoding_rs::SHIFT_JIS.encode(value) {
+                (_, _, true) => Err(Error::StringUnencodable(value.to_owned(), self.name())),
+                (bytes, _, false) => Ok(bytes),
+            },
+        }
+    }
+}
+
 pub(crate) mod sealed {
-    use std::{borrow::Cow, collections::HashMap};
+    use std::borrow::Cow;
+
+    use super::Endianness;
+    use crate::{Reader, Result, Writer};
+
+    /**
+    How a [`Primitive`](crate::Primitive) is stored in a table
+
+    This is only public with the `unstable` feature (as `PrimitiveImpl`),
+    and may change in any release. Implementing it, along with
+    [`Primitive`](crate::Primitive), allows a type to be stored in a way that
+    [`Value`](crate::Value) can't express, with direct access to the
+    [`Reader`] and [`Writer`].
+
+    Other primitives can be used to read and write strings and blobs (like
+    `<str as PrimitiveImpl>::parse`).
+
+    The type flag doesn't have to be one of the
+    [`ValueKind`](crate::ValueKind)s, so vendor types (flags `0xc` to `0xf`)
+    can be stored too, taking up whatever size their buffer is. Using a
+    primitive whose size doesn't match its standard flag (like an 8-byte
+    buffer with the flag of [`ValueKind::U32`](crate::ValueKind::U32)) fails
+    to compile.
+
+    # Example
+    ```
+    # use std::borrow::Cow;
+    # use criware_utf::{Primitive, PrimitiveImpl, Reader, ValueKind, Writer};
+    /// A hash that's always stored little-endian, even in big-endian tables
+    #[derive(Clone)]
+    struct Hash(u32);
+
+    impl PrimitiveImpl for Hash {
+        type Buffer = [u8; 4];
+
+        const TYPE_FLAG: u8 = ValueKind::U32.flag();
+
+        fn parse(data: [u8; 4], _: &mut Reader) -> criware_utf::Result<Hash> {
+            Ok(Hash(u32::from_le_bytes(data)))
+        }
+        fn write<'a>(value: Cow<'a, Hash>, _: &mut Writer<'a>) -> criware_utf::Result<[u8; 4]> {
+            Ok(value.0.to_le_bytes())
+        }
+    }
+
+    impl Primitive for Hash {}
+    ```
+
+    A vendor type, stored with flag `0xc`:
+    ```
+    # use std::borrow::Cow;
+    # use criware_utf::{Primitive, PrimitiveImpl, Reader, Value, Writer};
+    /// A 16-byte identifier some engines store inline
+    #[derive(Clone)]
+    struct Guid([u8; 16]);
+
+    impl PrimitiveImpl for Guid {
+        type Buffer = [u8; 16];
+
+        const TYPE_FLAG: u8 = 0xc;
+
+        fn parse(data: [u8; 16], _: &mut Reader) -> criware_utf::Result<Guid> {
+            Ok(Guid(data))
+        }
+        fn write<'a>(value: Cow<'a, Guid>, _: &mut Writer<'a>) -> criware_utf::Result<[u8; 16]> {
+            Ok(value.0)
+        }
+    }
+
+    impl Primitive for Guid {}
+    # impl Value for Guid {
+    #     type Primitive = Guid;
+    #     fn from_primitive(value: Guid) -> Result<Self, Box<dyn std::error::Error>> {
+    #         Ok(value)
+    #     }
+    #     fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Guid>, Box<dyn std::error::Error>> {
+    #         Ok(Cow::Borrowed(self))
+    #     }
+    # }
+
+    let id = Guid([7; 16]);
+    let mut writer = Writer::new("Ids");
+    writer.push_constant_column("Id", &id)?;
+    let mut data = Vec::new();
+    writer.end(&mut data, 0, 0)?;
+    assert_eq!(data[32], 0x3c);
+
+    let mut reader = Reader::from_bytes(&data)?;
+    let read: Guid = reader.read_constant_column("Id")?;
+    assert_eq!(read.0, id.0);
+    # Ok::<(), criware_utf::Error>(())
+    ```
+
+    A primitive the wrong size for its flag can't be used:
+    ```compile_fail
+    # use std::borrow::Cow;
+    # use criware_utf::{Primitive, PrimitiveImpl, Reader, Value, ValueKind, Writer};
+    #[derive(Clone)]
+    struct Wide(u64);
+
+    impl PrimitiveImpl for Wide {
+        type Buffer = [u8; 8];
 
-    #[doc(hidden)]
+        const TYPE_FLAG: u8 = ValueKind::U32.flag();
+        // ...
+    #   fn parse(data: [u8; 8], _: &mut Reader) -> criware_utf::Result<Wide> {
+    #       Ok(Wide(u64::from_be_bytes(data)))
+    #   }
+    #   fn write<'a>(value: Cow<'a, Wide>, _: &mut Writer<'a>) -> criware_utf::Result<[u8; 8]> {
+    #       Ok(value.0.to_be_bytes())
+    #   }
+    }
+    # impl Primitive for Wide {}
+    # impl Value for Wide {
+    #     type Primitive = Wide;
+    #     fn from_primitive(value: Wide) -> Result<Self, Box<dyn std::error::Error>> {
+    #         Ok(value)
+    #     }
+    #     fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Wide>, Box<dyn std::error::Error>> {
+    #         Ok(Cow::Borrowed(self))
+    #     }
+    # }
+
+    let mut writer = Writer::new("Table");
+    writer.push_rowed_column::<Wide>("Value");
+    ```
+    */
+    #[cfg_attr(not(feature = "unstable"), doc(hidden))]
     pub trait Primitive: ToOwned {
+        /// The bytes the primitive takes up in a row (or the constant data)
         type Buffer: AsRef<[u8]> + AsMut<[u8]> + Default;
 
+        /// The size of [`Primitive::Buffer`]
         const SIZE_IN_UTF: usize = std::mem::size_of::<Self::Buffer>();
-        const TYPE_FLAG: super::ValueKind;
-
-        fn parse<'a>(
-            data: Self::Buffer,
-            strings: &'a HashMap<u32, String>,
-            blobs: &Vec<u8>,
-        ) -> Option<Self::Owned>;
+        /// The type flag of columns storing the primitive (the lower half of
+        /// their flag byte)
+        ///
+        /// If it's the flag of a [`ValueKind`](super::ValueKind), the
+        /// primitive has to be the same size as that kind.
+        const TYPE_FLAG: u8;
+
+        /// Reads the primitive from its bytes in a table
+        fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self::Owned>;
+
+        /// Returns the bytes of the primitive to store in a table (adding any
+        /// strings or blobs it refers to)
+        fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer>;
+
+        /// Checks that the primitive fits in the table once it's encoded for
+        /// the given writer (like a [`FixedStr`](crate::FixedStr), which
+        /// has to fit in `N` bytes)
+        fn check_encoded(&self, _writer: &Writer) -> Result<()> {
+            Ok(())
+        }
+    }
 
-        fn write<'a>(
-            value: Cow<'a, Self>,
-            strings: &mut HashMap<Cow<'a, str>, u32>,
-            string_buffer: &mut Vec<u8>,
-            blobs: &mut Vec<u8>,
-        ) -> Self::Buffer;
+    /// Returns the type flag of columns storing the given primitive
+    ///
+    /// Called in a `const` block, this fails to compile if the flag doesn't
+    /// fit in half a byte, or if the primitive isn't the size of the kind of
+    /// value its flag is for.
+    pub(crate) const fn primitive_type_flag<P: Primitive + ?Sized>() -> u8 {
+        let flag = P::TYPE_FLAG;
+        assert!(flag <= 0x0f, "type flags are 4 bits long");
+        if let Some(kind) = super::ValueKind::from_flag(flag) {
+            assert!(
+                kind.size() as usize == P::SIZE_IN_UTF,
+                "primitive is a different size than its type flag"
+            );
+        }
+        flag
     }
 
     macro_rules! impl_primitive_number {
@@ -48,93 +422,66 @@ pub(crate) mod sealed {
                 impl Primitive for $name {
                     type Buffer = [u8; std::mem::size_of::<$name>()];
 
-                    const TYPE_FLAG: super::ValueKind = super::ValueKind::$flag;
+                    const TYPE_FLAG: u8 = super::ValueKind::$flag.flag();
 
                     #[inline]
-                    fn parse<'a>(
-                        data: Self::Buffer,
-                        _: &HashMap<u32, String>,
-                        _: &Vec<u8>,
-                    ) -> Option<Self> {
-                        Some($name::from_be_bytes(data))
+                    fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self> {
+                        Ok(match reader.endianness() {
+                            Endianness::Big => $name::from_be_bytes(data),
+                            Endianness::Little => $name::from_le_bytes(data),
+                        })
                     }
                     #[inline]
-                    fn write<'a>(
-                        value: Cow<'a, Self>,
-                        _: &mut HashMap<Cow<'a, str>, u32>,
-                        _: &mut Vec<u8>,
-                        _: &mut Vec<u8>,
-                    ) -> Self::Buffer {
-                        value.to_be_bytes()
+                    fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer> {
+                        Ok(match writer.endianness() {
+                            Endianness::Big => value.to_be_bytes(),
+                            Endianness::Little => value.to_le_bytes(),
+                        })
                     }
                 }
             )*
         };
     }
 
-    impl_primitive_number!(u8 U8, i8 I8, u16 U16, i16 I16, u32 U32, i32 I32, u64 U64, i64 I64, f32 F32);
+    impl_primitive_number!(u8 U8, i8 I8, u16 U16, i16 I16, u32 U32, i32 I32, u64 U64, i64 I64, f32 F32, f64 F64);
 
     impl Primitive for str {
         type Buffer = [u8; 4];
 
-        const TYPE_FLAG: super::ValueKind = super::ValueKind::STR;
+        const TYPE_FLAG: u8 = super::ValueKind::STR.flag();
 
-        fn parse<'a>(
-            data: Self::Buffer,
-            strings: &HashMap<u32, String>,
-            _: &Vec<u8>,
-        ) -> Option<Self::Owned> {
-            strings
-                .get(&u32::from_be_bytes(data))
-                .map(|v| v.to_string())
-        }
-        fn write<'a>(
-            value: Cow<'a, Self>,
-            strings: &mut HashMap<Cow<'a, str>, u32>,
-            string_buffer: &mut Vec<u8>,
-            _: &mut Vec<u8>,
-        ) -> Self::Buffer {
-            match strings.get(&value) {
-                Some(idx) => (*idx).to_be_bytes(),
-                None => {
-                    let position = string_buffer.len() as u32;
-                    string_buffer.extend_from_slice(&value.as_bytes());
-                    string_buffer.push(0u8);
-                    strings.insert(value, position);
-                    position.to_be_bytes()
-                }
+        fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self::Owned> {
+            let offset = reader.endianness().u32_from(data);
+            if reader.is_empty_sentinel(offset) {
+                return Ok(String::new());
             }
+            reader.string_at(offset)
+        }
+        fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer> {
+            let position = writer.push_string(value)?;
+            Ok(writer.endianness().u32_to(position))
         }
     }
 
     impl Primitive for [u8] {
         type Buffer = [u8; 8];
 
-        const TYPE_FLAG: super::ValueKind = super::ValueKind::BLOB;
-
-        fn parse<'a>(
-            data: Self::Buffer,
-            _: &HashMap<u32, String>,
-            blobs: &Vec<u8>,
-        ) -> Option<Self::Owned> {
-            let idx = u32::from_be_bytes(data[0..4].try_into().unwrap()) as usize;
-            let len = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
-            let end = idx + len;
-            if end > blobs.len() {
-                None
-            } else {
-                Some(blobs[idx..end].into())
-            }
+        const TYPE_FLAG: u8 = super::ValueKind::BLOB.flag();
+
+        fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self::Owned> {
+            let endianness = reader.endianness();
+            let idx = endianness.u32_from(data[0..4].try_into().unwrap());
+            let len = endianness.u32_from(data[4..8].try_into().unwrap());
+            reader.blob_at(idx, len)
         }
-        fn write<'a>(
-            value: Cow<'a, Self>,
-            _: &mut HashMap<Cow<'a, str>, u32>,
-            _: &mut Vec<u8>,
-            blobs: &mut Vec<u8>,
-        ) -> Self::Buffer {
-            let data = ((blobs.len() << 32) | value.len()) as u64;
-            blobs.extend(value.iter());
-            data.to_be_bytes()
+        fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer> {
+            let endianness = writer.endianness();
+            let mut data = [0u8; 8];
+            data[0..4].copy_from_slice(&endianness.u32_to(writer.push_blob(&value)?));
+            let length =
+                u32::try_from(value.len()).map_err(|_| crate::Error::TableTooLarge("blob"))?;
+            data[4..8].copy_from_slice(&endianness.u32_to(length));
+            Ok(data)
         }
     }
 }
@@ -147,11 +494,16 @@ macro_rules! blanket_impl {
     };
 }
 
-/// A value that can be directly stored in a table (sealed)
+/// A value that can be directly stored in a table
+///
+/// This is sealed, unless the `unstable` feature is enabled (see
+/// `PrimitiveImpl`).
 ///
 pub trait Primitive: sealed::Primitive + ToOwned {}
 
-blanket_impl!(Primitive for u8, u16, u32, u64, i8, i16, i32, i64, f32, str, [u8]);
+blanket_impl!(Primitive for u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, str, [u8], BlobHandle, RawString);
+
+impl<const N: usize> Primitive for FixedStr<N> {}
 
 /**
 A value that can be stored in a table, but must be converted first
@@ -183,7 +535,8 @@ pub trait Value: Sized {
     The primitive to which this value will be converted to/from
 
     This may be [`u8`], [`i8`], [`u16`], [`i16`], [`u32`], [`i32`], [`u64`],
-    [`i64`], [`f32`], [`str`], or `[u8]`
+    [`i64`], [`f32`], [`f64`], [`str`], `[u8]`, [`BlobHandle`],
+    [`RawString`], or [`FixedStr`]
     */
     type Primitive: Primitive + ?Sized;
 
@@ -218,7 +571,54 @@ macro_rules! impl_value_number {
     };
 }
 
-impl_value_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32);
+impl_value_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Value for bool {
+    type Primitive = u8;
+
+    fn from_primitive(value: u8) -> Result<Self, Box<dyn std::error::Error>> {
+        match value {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(crate::Error::InvalidBool(value).into()),
+        }
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(*self as u8))
+    }
+}
+
+/**
+A boolean stored as a [`u8`], where any nonzero value is `true`
+
+Unlike [`bool`], reading a value other than 0 or 1 doesn't fail. `true` is
+always written as 1.
+
+# Example
+```
+# use criware_utf::{LenientBool, utf_table};
+#[utf_table]
+struct Tab {
+    enabled: LenientBool,
+}
+```
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LenientBool(pub bool);
+
+impl Value for LenientBool {
+    type Primitive = u8;
+
+    #[inline]
+    fn from_primitive(value: u8) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(LenientBool(value != 0))
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.0 as u8))
+    }
+}
 
 impl Value for String {
     type Primitive = str;
@@ -233,6 +633,79 @@ impl Value for String {
     }
 }
 
+macro_rules! impl_value_shared_str {
+    ($($type:ty),*) => {
+        $(
+            impl Value for $type {
+                type Primitive = str;
+                #[inline]
+                fn from_primitive(value: String) -> BoxRes<Self> {
+                    Ok(value.into())
+                }
+                #[inline]
+                fn to_primitive<'a>(&'a self) -> BoxRes<Cow<'a, Self::Primitive>> {
+                    Ok(Cow::Borrowed(self))
+                }
+            }
+        )*
+    };
+}
+
+impl_value_shared_str!(
+    Box<str>,
+    std::rc::Rc<str>,
+    std::sync::Arc<str>,
+    Cow<'static, str>
+);
+
+/// Stored as a string
+///
+/// Writing a path that isn't valid UTF-8 fails with
+/// [`Error::NonUtf8Path`](crate::Error::NonUtf8Path), rather than storing a
+/// lossy copy of it.
+///
+/// Requires the `paths` feature.
+///
+#[cfg(feature = "paths")]
+impl Value for std::path::PathBuf {
+    type Primitive = str;
+
+    #[inline]
+    fn from_primitive(value: String) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(value.into())
+    }
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        match self.to_str() {
+            Some(value) => Ok(Cow::Borrowed(value)),
+            None => Err(crate::Error::NonUtf8Path(self.to_string_lossy().into_owned()).into()),
+        }
+    }
+}
+
+/// Stored as a string
+///
+/// Writing a string that isn't valid UTF-8 fails with
+/// [`Error::NonUtf8Path`](crate::Error::NonUtf8Path), rather than storing a
+/// lossy copy of it.
+///
+/// Requires the `paths` feature.
+///
+#[cfg(feature = "paths")]
+impl Value for std::ffi::OsString {
+    type Primitive = str;
+
+    #[inline]
+    fn from_primitive(value: String) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(value.into())
+    }
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        match self.to_str() {
+            Some(value) => Ok(Cow::Borrowed(value)),
+            None => Err(crate::Error::NonUtf8Path(self.to_string_lossy().into_owned()).into()),
+        }
+    }
+}
+
 impl Value for Vec<u8> {
     type Primitive = [u8];
 
@@ -255,7 +728,28 @@ impl Value for Box<[u8]> {
     }
     #[inline]
     fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
-        Ok(Cow::Borrowed(&self))
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+/// Stored as a blob
+///
+/// Reading doesn't copy the blob, since the [`Vec`] it's read into is given to
+/// the [`bytes::Bytes`].
+///
+/// Requires the `bytes` feature.
+///
+#[cfg(feature = "bytes")]
+impl Value for bytes::Bytes {
+    type Primitive = [u8];
+
+    #[inline]
+    fn from_primitive(value: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(value.into())
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        Ok(Cow::Borrowed(self))
     }
 }
 
@@ -274,6 +768,26 @@ impl<const N: usize> Value for [u8; N] {
     }
 }
 
+/// Stored as a 16-byte blob, in the order returned by [`uuid::Uuid::as_bytes`]
+///
+/// Requires the `uuid` feature.
+///
+#[cfg(feature = "uuid")]
+impl Value for uuid::Uuid {
+    type Primitive = [u8];
+
+    fn from_primitive(value: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        match uuid::Uuid::from_slice(&value) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(crate::Error::BlobWrongSize.into()),
+        }
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, Self::Primitive>, Box<dyn std::error::Error>> {
+        Ok(Cow::Borrowed(self.as_bytes()))
+    }
+}
+
 /**
 Returns the space (in bytes) a value would take up in column/row space
 
@@ -284,6 +798,7 @@ data itself and can be determined at compile-time.
 # Example
 ```
 # extern crate criware_utf_core as criware_utf;
+# use std::borrow::Cow;
 # use criware_utf::{Value, utf_size_of};
 #[derive(Default)]
 struct SplitU8(u8, u8);
@@ -311,3 +826,44 @@ fn main() {
 pub const fn utf_size_of<T: Value>() -> usize {
     <T::Primitive as sealed::Primitive>::SIZE_IN_UTF
 }
+
+/**
+Returns the kind of primitive a value is stored as, or [`None`] if it's stored
+with a vendor type flag (see `PrimitiveImpl`)
+
+# Example
+```
+# use criware_utf::{ValueKind, utf_kind_of};
+assert_eq!(utf_kind_of::<u8>(), Some(ValueKind::U8));
+assert_eq!(utf_kind_of::<String>(), Some(ValueKind::STR));
+assert_eq!(utf_kind_of::<Vec<u8>>(), Some(ValueKind::BLOB));
+```
+*/
+pub const fn utf_kind_of<T: Value>() -> Option<ValueKind> {
+    ValueKind::from_flag(const { sealed::primitive_type_flag::<T::Primitive>() })
+}
+
+/**
+Returns the space (in bytes) a value of the given kind takes up in column/row
+space
+
+This is the runtime counterpart of [`utf_size_of`], for when the kind of value
+is only known once a table is read (like from a [`Schema`](crate::Schema)).
+
+# Example
+```
+# use criware_utf::{ColumnStorageFormat, Schema, utf_size_of_kind};
+# fn row_size(schema: &Schema) -> usize {
+let row_size: usize = schema
+    .columns
+    .iter()
+    .filter(|column| column.storage_format == ColumnStorageFormat::Rowed)
+    .map(|column| utf_size_of_kind(column.value_kind))
+    .sum();
+# row_size
+# }
+```
+*/
+pub const fn utf_size_of_kind(kind: ValueKind) -> usize {
+    kind.size_in_row()
+}
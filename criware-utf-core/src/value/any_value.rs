@@ -0,0 +1,121 @@
+use super::ValueKind;
+
+/**
+A value of any kind, for reading and writing tables whose schema is only
+known at runtime
+
+Columns with a type flag this crate doesn't understand can be kept as
+[`AnyValue::Unknown`] (see
+[`ReaderOptions::preserve_unknown_types`](crate::ReaderOptions::preserve_unknown_types)),
+so tables using future or vendor-specific types survive being read and written
+back untouched.
+
+`AnyValue` doesn't implement [`Value`](crate::Value): every [`Primitive`](crate::Primitive)
+has a single type flag and size, fixed at compile time, so no primitive can
+stand in for a value whose kind is only known at runtime. Instead, use
+[`Reader::read_value_any`](crate::Reader::read_value_any) and
+[`Reader::read_any`](crate::Reader::read_any) to read one, and
+[`Writer::write_any`](crate::Writer::write_any) to write one. They read and
+write values the same way the typed methods do.
+
+# Example
+```no_run
+# use criware_utf_core::{AnyValue, Reader, ReaderOptions};
+let data = std::fs::read("vendor-table.bin")?;
+let mut reader = Reader::from_bytes_with_options(
+    &data,
+    ReaderOptions {
+        preserve_unknown_types: true,
+        ..Default::default()
+    },
+)?;
+if let Some(column) = reader.find_column("Extra")? {
+    if let Some(AnyValue::Unknown { flag, bytes }) = reader.read_any(&column, 0)? {
+        println!("type 0x{flag:x}: {bytes:02x?}");
+    }
+}
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Blob(Vec<u8>),
+    /// A value with a type flag this crate doesn't understand
+    Unknown {
+        /// The type flag (the lower half of the column's flag byte)
+        flag: u8,
+        /// The raw bytes stored in the column or row space
+        bytes: Vec<u8>,
+    },
+}
+
+impl AnyValue {
+    /// Returns the default value of the given kind (zero, an empty string, or
+    /// an empty blob)
+    ///
+    pub fn default_of(kind: ValueKind) -> AnyValue {
+        match kind {
+            ValueKind::U8 => AnyValue::U8(0),
+            ValueKind::I8 => AnyValue::I8(0),
+            ValueKind::U16 => AnyValue::U16(0),
+            ValueKind::I16 => AnyValue::I16(0),
+            ValueKind::U32 => AnyValue::U32(0),
+            ValueKind::I32 => AnyValue::I32(0),
+            ValueKind::U64 => AnyValue::U64(0),
+            ValueKind::I64 => AnyValue::I64(0),
+            ValueKind::F32 => AnyValue::F32(0.0),
+            ValueKind::F64 => AnyValue::F64(0.0),
+            ValueKind::STR => AnyValue::String(String::new()),
+            ValueKind::BLOB => AnyValue::Blob(Vec::new()),
+        }
+    }
+
+    /// Returns the kind of the value, or [`None`] if it's unknown
+    ///
+    pub fn kind(&self) -> Option<ValueKind> {
+        Some(match self {
+            AnyValue::U8(_) => ValueKind::U8,
+            AnyValue::I8(_) => ValueKind::I8,
+            AnyValue::U16(_) => ValueKind::U16,
+            AnyValue::I16(_) => ValueKind::I16,
+            AnyValue::U32(_) => ValueKind::U32,
+            AnyValue::I32(_) => ValueKind::I32,
+            AnyValue::U64(_) => ValueKind::U64,
+            AnyValue::I64(_) => ValueKind::I64,
+            AnyValue::F32(_) => ValueKind::F32,
+            AnyValue::F64(_) => ValueKind::F64,
+            AnyValue::String(_) => ValueKind::STR,
+            AnyValue::Blob(_) => ValueKind::BLOB,
+            AnyValue::Unknown { .. } => return None,
+        })
+    }
+
+    /// Returns the type flag the value is stored with
+    ///
+    pub fn type_flag(&self) -> u8 {
+        match self {
+            AnyValue::Unknown { flag, .. } => *flag,
+            value => value.kind().unwrap() as u8,
+        }
+    }
+
+    /// Returns the space (in bytes) the value takes up in column/row space
+    ///
+    pub fn utf_size(&self) -> u32 {
+        match self {
+            AnyValue::Unknown { bytes, .. } => bytes.len() as u32,
+            value => value.kind().unwrap().size(),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Seek, SeekFrom},
+};
+
+use super::{BoxRes, Value, ValueKind, sealed};
+use crate::{Error, IOErrorHelper, Reader, Result, Writer};
+
+/**
+A reference to a data blob stored in a table, without its contents
+
+Reading a blob column as `Vec<u8>` copies every blob out of the table. Reading
+it as a `BlobHandle` instead only records where the blob is, so tables with
+large blobs (e.g. embedded files) can be read without holding two copies of
+every blob. The bytes are copied once the handle is converted, using the
+original table data or the [`Reader`] that produced it.
+
+Handles cannot be written. Load the blob and write it as a `Vec<u8>` instead.
+
+# Example
+```no_run
+# use criware_utf_core::{BlobHandle, Reader};
+let data = std::fs::read("file-table.bin")?;
+let mut reader = Reader::from_bytes(&data)?;
+let handle: BlobHandle = reader.read_value(true)?;
+// no bytes have been copied yet
+let contents: Vec<u8> = handle.to_vec(&data)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlobHandle {
+    offset: u64,
+    length: u32,
+}
+
+impl BlobHandle {
+    pub(crate) fn new(offset: u64, length: u32) -> Self {
+        BlobHandle { offset, length }
+    }
+
+    /// Returns the position of the blob, relative to the start of the table
+    /// (the `@UTF` magic)
+    ///
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the size of the blob (in bytes)
+    ///
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    /// Returns `true` if the blob is empty
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /**
+    Returns the contents of the blob, borrowed from the table it was read from
+
+    `table` must start at the `@UTF` magic of the table.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{BlobHandle, Reader};
+    let data = std::fs::read("file-table.bin")?;
+    let mut reader = Reader::from_bytes(&data)?;
+    let handle: BlobHandle = reader.read_value(true)?;
+    let contents: &[u8] = handle.slice(&data)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn slice<'b>(&self, table: &'b [u8]) -> Result<&'b [u8]> {
+        let start = self.offset as usize;
+        table
+            .get(start..start + self.len())
+            .ok_or(Error::DataNotFound)
+    }
+
+    /// Copies the contents of the blob out of the table it was read from
+    ///
+    /// `table` must start at the `@UTF` magic of the table.
+    ///
+    pub fn to_vec(&self, table: &[u8]) -> Result<Vec<u8>> {
+        self.slice(table).map(<[u8]>::to_vec)
+    }
+
+    /**
+    Reads the contents of the blob from a stream
+
+    `table_start` is the position of the table's `@UTF` magic in the stream.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::{BlobHandle, Reader};
+    let mut file = File::open("file-table.bin")?;
+    let handle: BlobHandle = {
+        let mut reader = Reader::new_seek(&mut file)?;
+        reader.read_value(true)?
+    };
+    let contents = handle.read_from(&mut file, 0)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+    */
+    pub fn read_from(&self, stream: &mut (impl Read + Seek), table_start: u64) -> Result<Vec<u8>> {
+        stream
+            .seek(SeekFrom::Start(table_start + self.offset))
+            .io("UTF blob data")?;
+        let mut blob = vec![0u8; self.len()];
+        stream.read_exact(&mut blob).io("UTF blob data")?;
+        Ok(blob)
+    }
+}
+
+impl sealed::Primitive for BlobHandle {
+    type Buffer = [u8; 8];
+
+    const TYPE_FLAG: u8 = ValueKind::BLOB.flag();
+
+    fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self> {
+        let endianness = reader.endianness();
+        let idx = endianness.u32_from(data[0..4].try_into().unwrap());
+        let len = endianness.u32_from(data[4..8].try_into().unwrap());
+        reader.blob_handle(idx, len)
+    }
+    fn write<'a>(_: Cow<'a, Self>, _: &mut Writer<'a>) -> Result<Self::Buffer> {
+        Err(Error::UnloadedBlob)
+    }
+}
+
+impl Value for BlobHandle {
+    type Primitive = BlobHandle;
+
+    #[inline]
+    fn from_primitive(value: BlobHandle) -> BoxRes<Self> {
+        Ok(value)
+    }
+    fn to_primitive<'a>(&'a self) -> BoxRes<Cow<'a, Self::Primitive>> {
+        Err(Error::UnloadedBlob.into())
+    }
+}
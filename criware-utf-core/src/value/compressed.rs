@@ -0,0 +1,281 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use super::Value;
+
+/// The default limit on the decompressed size of a [`CompressedBlob`]
+/// (256 MiB)
+pub const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 256 * 1024 * 1024;
+
+/// Returns the error for a blob that decompresses to more than `max_len`
+/// bytes
+#[cfg(any(feature = "flate2", feature = "lzma-rs"))]
+fn too_large(len: usize, max_len: usize) -> Box<dyn std::error::Error> {
+    Box::new(crate::Error::LimitExceeded(
+        "decompressed blob",
+        len as u64,
+        max_len as u64,
+    ))
+}
+
+/// A writer that refuses to hold more than a given number of bytes
+#[cfg(feature = "lzma-rs")]
+struct LimitedWriter {
+    data: Vec<u8>,
+    max_len: usize,
+    exceeded: bool,
+}
+
+#[cfg(feature = "lzma-rs")]
+impl std::io::Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.data.len() + buf.len() > self.max_len {
+            self.exceeded = true;
+            return Err(std::io::ErrorKind::OutOfMemory.into());
+        }
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/**
+A compression format that a [`CompressedBlob`] can be stored with
+
+Codecs for deflate, zlib, and gzip are available with the `flate2` feature,
+and one for LZMA is available with the `lzma-rs` feature. Any other format can
+be used by implementing this trait.
+
+Blobs come from tables that may not be trusted, so a codec must stop (and
+return an error) as soon as the decompressed data grows past `max_len`,
+instead of decompressing all of it first.
+
+# Example
+```
+# use criware_utf::{Codec, CompressedBlob};
+/// A game that "compresses" its blobs by inverting every byte
+struct Inverted;
+
+impl Codec for Inverted {
+    fn decompress(
+        data: &[u8],
+        max_len: usize,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        if data.len() > max_len {
+            return Err("blob is too large".into());
+        }
+        Ok(data.iter().map(|byte| !byte).collect())
+    }
+    fn compress(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        Ok(data.iter().map(|byte| !byte).collect())
+    }
+}
+
+let blob = CompressedBlob::<Inverted>::new(vec![0x00, 0x0f]);
+```
+*/
+pub trait Codec {
+    /// Decompresses a blob that was read from a table, failing if it's
+    /// larger than `max_len` bytes once decompressed
+    ///
+    fn decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+
+    /// Compresses a blob before it's written to a table
+    ///
+    fn compress(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>>;
+}
+
+/**
+A blob that's stored compressed, and decompressed when it's read
+
+The blob is decompressed with `C` (see [`Codec`]) when it's read, and
+compressed again when it's written. Since it's compressed again, the blob that
+gets written isn't necessarily identical to the one that was read, even if it
+wasn't modified.
+
+The decompressed bytes can be accessed through [`Deref`] (as a `Vec<u8>`).
+
+A blob that decompresses to more than `MAX_LEN` bytes
+([`DEFAULT_MAX_DECOMPRESSED_LEN`] by default) fails to be read, so a small
+malicious blob can't exhaust memory.
+
+# Example
+```
+# use criware_utf::{CompressedBlob, Deflate, utf_table};
+#[utf_table]
+struct Scripts {
+    name: String,
+    code: CompressedBlob<Deflate>,
+}
+
+let mut code = CompressedBlob::<Deflate>::new(b"print(1)".to_vec());
+code.extend_from_slice(b"\nprint(2)");
+
+// at most 1 KiB once decompressed
+#[utf_table]
+struct SmallScripts {
+    code: CompressedBlob<Deflate, 1024>,
+}
+
+# use criware_utf::{Codec, Value};
+let bomb = Deflate::compress(&[0; 2048]).unwrap();
+assert!(CompressedBlob::<Deflate, 1024>::from_primitive(bomb).is_err());
+```
+*/
+pub struct CompressedBlob<C: Codec, const MAX_LEN: usize = DEFAULT_MAX_DECOMPRESSED_LEN> {
+    data: Vec<u8>,
+    codec: PhantomData<C>,
+}
+
+impl<C: Codec, const MAX_LEN: usize> CompressedBlob<C, MAX_LEN> {
+    /// Creates a blob from its decompressed bytes
+    ///
+    pub fn new(data: Vec<u8>) -> Self {
+        CompressedBlob {
+            data,
+            codec: PhantomData,
+        }
+    }
+
+    /// Returns the decompressed bytes of the blob
+    ///
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> Clone for CompressedBlob<C, MAX_LEN> {
+    fn clone(&self) -> Self {
+        CompressedBlob::new(self.data.clone())
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> std::fmt::Debug for CompressedBlob<C, MAX_LEN> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CompressedBlob").field(&self.data).finish()
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> Default for CompressedBlob<C, MAX_LEN> {
+    fn default() -> Self {
+        CompressedBlob::new(Vec::new())
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> PartialEq for CompressedBlob<C, MAX_LEN> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> Eq for CompressedBlob<C, MAX_LEN> {}
+
+impl<C: Codec, const MAX_LEN: usize> Deref for CompressedBlob<C, MAX_LEN> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> DerefMut for CompressedBlob<C, MAX_LEN> {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+}
+
+impl<C: Codec, const MAX_LEN: usize> Value for CompressedBlob<C, MAX_LEN> {
+    type Primitive = [u8];
+
+    fn from_primitive(value: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(CompressedBlob::new(C::decompress(&value, MAX_LEN)?))
+    }
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, [u8]>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(C::compress(&self.data)?))
+    }
+}
+
+macro_rules! impl_codec_flate2 {
+    ($($(#[$attr:meta])* $name:ident => $encoder:ident, $decoder:ident;)*) => {
+        $(
+            $(#[$attr])*
+            ///
+            /// Requires the `flate2` feature.
+            ///
+            #[cfg(feature = "flate2")]
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+            pub struct $name;
+
+            #[cfg(feature = "flate2")]
+            impl Codec for $name {
+                fn decompress(
+                    data: &[u8],
+                    max_len: usize,
+                ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+                    use std::io::Read;
+                    let mut output = Vec::new();
+                    // one byte past the limit is enough to know it's exceeded
+                    flate2::read::$decoder::new(data)
+                        .take(max_len as u64 + 1)
+                        .read_to_end(&mut output)?;
+                    if output.len() > max_len {
+                        return Err(too_large(output.len(), max_len));
+                    }
+                    Ok(output)
+                }
+                fn compress(data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+                    use std::io::Write;
+                    let mut encoder =
+                        flate2::write::$encoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(data)?;
+                    Ok(encoder.finish()?)
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_flate2! {
+    /// Raw deflate compression (without a header)
+    Deflate => DeflateEncoder, DeflateDecoder;
+    /// Deflate compression with a zlib header
+    Zlib => ZlibEncoder, ZlibDecoder;
+    /// Deflate compression with a gzip header
+    Gzip => GzEncoder, GzDecoder;
+}
+
+/// LZMA compression (the `.lzma` format)
+///
+/// Requires the `lzma-rs` feature.
+///
+#[cfg(feature = "lzma-rs")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lzma;
+
+#[cfg(feature = "lzma-rs")]
+impl Codec for Lzma {
+    fn decompress(mut data: &[u8], max_len: usize) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut output = LimitedWriter {
+            data: Vec::new(),
+            max_len,
+            exceeded: false,
+        };
+        if let Err(error) = lzma_rs::lzma_decompress(&mut data, &mut output) {
+            if output.exceeded {
+                return Err(too_large(output.data.len() + 1, max_len));
+            }
+            return Err(error.into());
+        }
+        Ok(output.data)
+    }
+    fn compress(mut data: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut output = Vec::new();
+        lzma_rs::lzma_compress(&mut data, &mut output)?;
+        Ok(output)
+    }
+}
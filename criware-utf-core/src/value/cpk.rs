@@ -0,0 +1,104 @@
+use std::borrow::Cow;
+
+use super::Value;
+
+/// Rounds a position up to the next multiple of `align` (with 0 and 1
+/// meaning no alignment)
+const fn align_up(value: u64, align: u64) -> u64 {
+    if align <= 1 {
+        value
+    } else {
+        value.next_multiple_of(align)
+    }
+}
+
+/**
+The offset of a file in a CPK, like the `FileOffset` column of a TOC table
+
+CPKs store these offsets relative to another part of the archive (like the TOC
+itself, or the `ContentOffset` in the CPK header), and every file starts at a
+multiple of the archive's `Align`. Use [`CpkOffset::absolute`] to find where a
+file actually is.
+
+# Example
+```
+# use criware_utf::{CpkOffset, CpkSize, utf_table};
+#[utf_table(table_name = "CpkTocInfo")]
+struct Toc {
+    file_offset: CpkOffset,
+    file_size: CpkSize,
+}
+
+let offset = CpkOffset(0x1000);
+assert_eq!(offset.absolute(0x800, 0x800), 0x1800);
+let next = offset.next(CpkSize(0x100), 0x800);
+assert_eq!(next, CpkOffset(0x1800));
+```
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CpkOffset(pub u64);
+
+impl CpkOffset {
+    /// Returns the position of the file in the archive, given the position the
+    /// offset is relative to, and the archive's alignment
+    ///
+    /// The position is rounded up to the alignment.
+    ///
+    pub const fn absolute(self, base: u64, align: u64) -> u64 {
+        align_up(base + self.0, align)
+    }
+
+    /// Returns the offset of the file that comes after this one, given the
+    /// size of this file and the archive's alignment
+    ///
+    /// This is how offsets are laid out in archives that don't store them
+    /// (like ones with only an ITOC table).
+    ///
+    pub const fn next(self, size: CpkSize, align: u64) -> CpkOffset {
+        CpkOffset(align_up(self.0 + size.0 as u64, align))
+    }
+}
+
+impl Value for CpkOffset {
+    type Primitive = u64;
+
+    #[inline]
+    fn from_primitive(value: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(CpkOffset(value))
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, u64>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.0))
+    }
+}
+
+/**
+The size of a file in a CPK, like the `FileSize` or `ExtractSize` column of a
+TOC table
+
+See [`CpkOffset`].
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CpkSize(pub u32);
+
+impl CpkSize {
+    /// Returns the space the file takes up in the archive, including the
+    /// padding after it, given the archive's alignment
+    ///
+    pub const fn aligned(self, align: u64) -> u64 {
+        align_up(self.0 as u64, align)
+    }
+}
+
+impl Value for CpkSize {
+    type Primitive = u32;
+
+    #[inline]
+    fn from_primitive(value: u32) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(CpkSize(value))
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, u32>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.0))
+    }
+}
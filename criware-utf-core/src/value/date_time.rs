@@ -0,0 +1,122 @@
+use std::{borrow::Cow, fmt};
+
+use super::Value;
+
+/**
+A date and time packed into a [`u64`], like the `UpdateDateTime` column of a
+CPK's ETOC table
+
+From the most significant byte down, the value is made up of the year (2
+bytes), month, day, hour, minute, and second, followed by a byte that's
+normally 0. Nothing is validated, so any packed value is read and written back
+as-is.
+
+With the `chrono` feature, it can be converted to and from a
+[`chrono::NaiveDateTime`].
+
+# Example
+```
+# use criware_utf::{CriDateTime, utf_table};
+#[utf_table]
+struct Etoc {
+    update_date_time: CriDateTime,
+}
+
+let time = CriDateTime::from_u64(0x07da_0b0c_0e1a_2200);
+assert_eq!(time.to_string(), "2010-11-12 14:26:34");
+```
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CriDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// The least significant byte, which is normally 0
+    pub reserved: u8,
+}
+
+impl CriDateTime {
+    /// Unpacks a date and time from its packed form
+    ///
+    pub const fn from_u64(value: u64) -> Self {
+        CriDateTime {
+            year: (value >> 48) as u16,
+            month: (value >> 40) as u8,
+            day: (value >> 32) as u8,
+            hour: (value >> 24) as u8,
+            minute: (value >> 16) as u8,
+            second: (value >> 8) as u8,
+            reserved: value as u8,
+        }
+    }
+
+    /// Returns the packed form of the date and time
+    ///
+    pub const fn to_u64(&self) -> u64 {
+        ((self.year as u64) << 48)
+            | ((self.month as u64) << 40)
+            | ((self.day as u64) << 32)
+            | ((self.hour as u64) << 24)
+            | ((self.minute as u64) << 16)
+            | ((self.second as u64) << 8)
+            | (self.reserved as u64)
+    }
+
+    /// Converts a [`chrono::NaiveDateTime`], dropping anything smaller than
+    /// a second
+    ///
+    /// Years that don't fit in a [`u16`] are clamped.
+    ///
+    /// Requires the `chrono` feature.
+    ///
+    #[cfg(feature = "chrono")]
+    pub fn from_naive_date_time(value: chrono::NaiveDateTime) -> Self {
+        use chrono::{Datelike, Timelike};
+        CriDateTime {
+            year: value.year().clamp(0, u16::MAX as i32) as u16,
+            month: value.month() as u8,
+            day: value.day() as u8,
+            hour: value.hour() as u8,
+            minute: value.minute() as u8,
+            second: value.second() as u8,
+            reserved: 0,
+        }
+    }
+
+    /// Converts the date and time to a [`chrono::NaiveDateTime`], or returns
+    /// [`None`] if it isn't a valid date and time
+    ///
+    /// Requires the `chrono` feature.
+    ///
+    #[cfg(feature = "chrono")]
+    pub fn to_naive_date_time(&self) -> Option<chrono::NaiveDateTime> {
+        chrono::NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)?
+            .and_hms_opt(self.hour as u32, self.minute as u32, self.second as u32)
+    }
+}
+
+impl fmt::Display for CriDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl Value for CriDateTime {
+    type Primitive = u64;
+
+    #[inline]
+    fn from_primitive(value: u64) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(CriDateTime::from_u64(value))
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, u64>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.to_u64()))
+    }
+}
@@ -0,0 +1,71 @@
+use std::{borrow::Cow, fmt};
+
+use super::Value;
+
+/**
+A fixed-point number, stored as an integer whose lower `FRAC_BITS` bits are
+the fractional part
+
+`I` may be any of the integer primitives ([`u8`], [`i8`], [`u16`], [`i16`],
+[`u32`], [`i32`], [`u64`], or [`i64`]). The raw integer is read and written
+as-is, so converting it to an [`f32`] is only needed to work with its value.
+
+# Example
+```
+# use criware_utf::{FixedPoint, utf_table};
+#[utf_table]
+struct Cue {
+    volume: FixedPoint<u16, 8>,
+}
+
+let volume = FixedPoint::<u16, 8>::from_f32(0.75);
+assert_eq!(volume.0, 0xc0);
+assert_eq!(volume.to_f32() * 2.0, 1.5);
+```
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedPoint<I, const FRAC_BITS: u32>(pub I);
+
+macro_rules! impl_fixed_point {
+    ($($type:ty),*) => {
+        $(
+            impl<const FRAC_BITS: u32> FixedPoint<$type, FRAC_BITS> {
+                /// Converts a number to fixed-point, rounding it to the nearest
+                /// representable value (and saturating if it's out of range)
+                ///
+                pub fn from_f32(value: f32) -> Self {
+                    FixedPoint((value * 2f32.powi(FRAC_BITS as i32)).round() as $type)
+                }
+
+                /// Returns the value of the number
+                ///
+                pub fn to_f32(self) -> f32 {
+                    self.0 as f32 / 2f32.powi(FRAC_BITS as i32)
+                }
+            }
+
+            impl<const FRAC_BITS: u32> fmt::Display for FixedPoint<$type, FRAC_BITS> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    fmt::Display::fmt(&self.to_f32(), f)
+                }
+            }
+
+            impl<const FRAC_BITS: u32> Value for FixedPoint<$type, FRAC_BITS> {
+                type Primitive = $type;
+
+                #[inline]
+                fn from_primitive(value: $type) -> Result<Self, Box<dyn std::error::Error>> {
+                    Ok(FixedPoint(value))
+                }
+                #[inline]
+                fn to_primitive<'a>(
+                    &'a self,
+                ) -> Result<Cow<'a, $type>, Box<dyn std::error::Error>> {
+                    Ok(Cow::Owned(self.0))
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_point!(u8, i8, u16, i16, u32, i32, u64, i64);
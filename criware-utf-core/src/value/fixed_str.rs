@@ -0,0 +1,153 @@
+use std::{borrow::Cow, fmt, ops::Deref};
+
+use super::{BoxRes, Value, ValueKind, sealed};
+use crate::{Error, Reader, Result, Writer};
+
+/**
+A string that must fit in a buffer of `N` bytes
+
+Some games copy strings from a table into fixed-size buffers, and break if
+they're too long. A `FixedStr` can only be written if it's at most `N` bytes
+long in the table's encoding, not counting its NUL terminator. Writing a
+longer string fails with [`Error::StringTooLong`] (in [`Error::InColumn`],
+which names the column).
+
+Since the encoding isn't known until the string is written, creating a
+`FixedStr` only rejects strings that can't fit in any encoding (ones with more
+than `N` characters), giving their length in UTF-8. Strings longer than `N`
+bytes can still be read.
+
+# Example
+```
+# use criware_utf::{FixedStr, utf_table};
+#[utf_table]
+struct Banks {
+    name: FixedStr<15>,
+}
+
+let name = FixedStr::<15>::new("se_common")?;
+assert_eq!(&*name, "se_common");
+assert!(FixedStr::<15>::new("se_common_streaming").is_err());
+# Ok::<(), criware_utf::Error>(())
+```
+
+Six kanji take up 18 bytes in UTF-8, but only 12 in Shift-JIS:
+```
+# use criware_utf::{Encoding, FixedStr, Table, WriterOptions, utf_table};
+# #[utf_table]
+# struct Banks {
+#     name: FixedStr<15>,
+# }
+let mut table = Banks::new();
+table.rows.push(BanksRow { name: FixedStr::new("効果音共通音")? });
+assert!(table.write_to_vec().is_err());
+let options = WriterOptions {
+    encoding: Encoding::ShiftJis,
+    ..Banks::writer_options()
+};
+table.write_with_options(&mut Vec::new(), options)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedStr<const N: usize>(String);
+
+impl<const N: usize> FixedStr<N> {
+    /// The most bytes the string may take up once it's encoded in the table's
+    /// encoding, not counting its NUL terminator
+    ///
+    pub const MAX_ENCODED_LEN: usize = N;
+
+    /// Creates a string, unless it has more than `N` characters
+    ///
+    /// Every character takes up at least a byte in any encoding, so those
+    /// strings can never be written. Strings that fit in `N` bytes in some
+    /// encodings but not others are only rejected when they're written.
+    ///
+    pub fn new(value: impl Into<String>) -> Result<Self> {
+        let value = value.into();
+        if value.len() > N && value.chars().count() > N {
+            return Err(Error::StringTooLong(value.len(), N));
+        }
+        Ok(FixedStr(value))
+    }
+
+    /// Returns the string
+    ///
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the string
+    ///
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<const N: usize> Deref for FixedStr<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const N: usize> TryFrom<String> for FixedStr<N> {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        FixedStr::new(value)
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for FixedStr<N> {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        FixedStr::new(value)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<const N: usize> sealed::Primitive for FixedStr<N> {
+    type Buffer = [u8; 4];
+
+    const TYPE_FLAG: u8 = ValueKind::STR.flag();
+
+    fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self> {
+        <str as sealed::Primitive>::parse(data, reader).map(FixedStr)
+    }
+    fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer> {
+        let value = match value {
+            Cow::Borrowed(value) => Cow::Borrowed(value.as_str()),
+            Cow::Owned(value) => Cow::Owned(value.0),
+        };
+        <str as sealed::Primitive>::write(value, writer)
+    }
+    fn check_encoded(&self, writer: &Writer) -> Result<()> {
+        let length = writer.encoding().encode(&self.0)?.len();
+        if length > Self::MAX_ENCODED_LEN {
+            return Err(Error::StringTooLong(length, Self::MAX_ENCODED_LEN));
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Value for FixedStr<N> {
+    type Primitive = FixedStr<N>;
+
+    #[inline]
+    fn from_primitive(value: FixedStr<N>) -> BoxRes<Self> {
+        Ok(value)
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> BoxRes<Cow<'a, Self::Primitive>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
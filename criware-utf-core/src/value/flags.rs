@@ -0,0 +1,96 @@
+#[doc(hidden)]
+pub use bitflags::Flags as __Flags;
+
+/**
+Implements [`Value`](crate::Value) for a type defined with
+[`bitflags!`](https://docs.rs/bitflags), storing it as its underlying integer
+
+By default, any bits that don't belong to a flag are kept when reading (and
+written back as-is). Add `strict` to reject them instead, with
+[`Error::UnknownFlagBits`](crate::Error::UnknownFlagBits).
+
+Requires the `bitflags` feature.
+
+# Example
+```
+# use bitflags::bitflags;
+# use criware_utf::{impl_value_bitflags, utf_table};
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct CueFlags: u8 {
+        const LOOP = 1;
+        const STREAMED = 2;
+    }
+}
+impl_value_bitflags!(CueFlags);
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct TrackFlags: u16 {
+        const MUTED = 1;
+    }
+}
+impl_value_bitflags!(TrackFlags, strict);
+
+#[utf_table]
+struct Cue {
+    flags: CueFlags,
+    track_flags: TrackFlags,
+}
+```
+*/
+#[macro_export]
+macro_rules! impl_value_bitflags {
+    ($type:ty) => {
+        impl $crate::Value for $type {
+            type Primitive = <$type as $crate::__Flags>::Bits;
+
+            #[inline]
+            fn from_primitive(
+                value: Self::Primitive,
+            ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error>> {
+                ::std::result::Result::Ok(<$type as $crate::__Flags>::from_bits_retain(value))
+            }
+            #[inline]
+            fn to_primitive<'a>(
+                &'a self,
+            ) -> ::std::result::Result<
+                ::std::borrow::Cow<'a, Self::Primitive>,
+                ::std::boxed::Box<dyn ::std::error::Error>,
+            > {
+                ::std::result::Result::Ok(::std::borrow::Cow::Owned($crate::__Flags::bits(self)))
+            }
+        }
+    };
+    ($type:ty, strict) => {
+        impl $crate::Value for $type {
+            type Primitive = <$type as $crate::__Flags>::Bits;
+
+            fn from_primitive(
+                value: Self::Primitive,
+            ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error>> {
+                match <$type as $crate::__Flags>::from_bits(value) {
+                    ::std::option::Option::Some(flags) => ::std::result::Result::Ok(flags),
+                    ::std::option::Option::None => {
+                        let all = $crate::__Flags::bits(&<$type as $crate::__Flags>::all());
+                        ::std::result::Result::Err(::std::boxed::Box::new(
+                            $crate::Error::UnknownFlagBits(
+                                ::std::stringify!($type),
+                                (value & !all) as u64,
+                            ),
+                        ))
+                    }
+                }
+            }
+            #[inline]
+            fn to_primitive<'a>(
+                &'a self,
+            ) -> ::std::result::Result<
+                ::std::borrow::Cow<'a, Self::Primitive>,
+                ::std::boxed::Box<dyn ::std::error::Error>,
+            > {
+                ::std::result::Result::Ok(::std::borrow::Cow::Owned($crate::__Flags::bits(self)))
+            }
+        }
+    };
+}
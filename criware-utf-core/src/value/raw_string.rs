@@ -0,0 +1,103 @@
+use std::{borrow::Cow, fmt};
+
+use super::{BoxRes, Value, ValueKind, sealed};
+use crate::{Reader, Result, Writer};
+
+/**
+A string stored as its original bytes, whether or not they can be decoded
+
+Some tables contain strings in an encoding other than the table's, which
+can't be read as a [`String`] (reading them fails with
+[`Error::StringMalformed`](crate::Error::StringMalformed) or
+[`Error::StringUndecodable`](crate::Error::StringUndecodable)). Reading those
+columns as `RawString` keeps the bytes of each string instead, and writes them
+back unchanged.
+
+Strings that can be decoded are written like any other string. The rest are
+written as-is, so they may not be valid in the table's encoding.
+
+# Example
+```
+# use criware_utf::{RawString, utf_table};
+#[utf_table]
+struct Sounds {
+    name: RawString,
+}
+
+let name = RawString(b"caf\xe9".to_vec());
+assert_eq!(name.to_str(), None);
+assert_eq!(name.to_string(), "caf\u{fffd}");
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawString(pub Vec<u8>);
+
+impl RawString {
+    /// Returns the bytes of the string
+    ///
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the string, if it's valid UTF-8
+    ///
+    pub fn to_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    /// Returns the string, replacing anything that isn't valid UTF-8 with
+    /// `U+FFFD`
+    ///
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.0)
+    }
+}
+
+impl From<String> for RawString {
+    fn from(value: String) -> Self {
+        RawString(value.into_bytes())
+    }
+}
+
+impl From<Vec<u8>> for RawString {
+    fn from(value: Vec<u8>) -> Self {
+        RawString(value)
+    }
+}
+
+impl fmt::Display for RawString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl sealed::Primitive for RawString {
+    type Buffer = [u8; 4];
+
+    const TYPE_FLAG: u8 = ValueKind::STR.flag();
+
+    fn parse(data: Self::Buffer, reader: &mut Reader) -> Result<Self> {
+        let offset = reader.endianness().u32_from(data);
+        if reader.is_empty_sentinel(offset) {
+            return Ok(RawString(Vec::new()));
+        }
+        reader.raw_string_at(offset).map(RawString)
+    }
+    fn write<'a>(value: Cow<'a, Self>, writer: &mut Writer<'a>) -> Result<Self::Buffer> {
+        let position = writer.push_raw_string(&value.0)?;
+        Ok(writer.endianness().u32_to(position))
+    }
+}
+
+impl Value for RawString {
+    type Primitive = RawString;
+
+    #[inline]
+    fn from_primitive(value: RawString) -> BoxRes<Self> {
+        Ok(value)
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> BoxRes<Cow<'a, Self::Primitive>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
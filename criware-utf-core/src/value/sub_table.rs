@@ -0,0 +1,143 @@
+use std::{borrow::Cow, cell::OnceCell};
+
+use super::Value;
+use crate::{Result, Table};
+
+/**
+A table stored in a blob, which is only parsed once it's needed
+
+Reading a `LazySubTable` only copies the blob. The table is parsed the first
+time it's accessed with [`LazySubTable::parse`] or
+[`LazySubTable::parsed_mut`], and kept afterwards. When written, the original
+bytes are written as-is, unless the table was accessed mutably, in which case
+it's written again.
+
+# Example
+```no_run
+# use criware_utf::{LazySubTable, Table, utf_table};
+#[utf_table]
+struct Waveform {
+    id: u16,
+}
+
+#[utf_table]
+struct Header {
+    #[constant]
+    waveform_table: LazySubTable<Waveform>,
+}
+
+let data = std::fs::read("header.bin")?;
+let mut header = Header::read_from_slice(&data)?;
+// only the waveform table is parsed
+let waveforms = header.constants.waveform_table.parsed_mut()?;
+waveforms.rows.retain(|waveform| waveform.id != 0);
+let data = header.write_to_vec()?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone)]
+pub struct LazySubTable<T: Table> {
+    raw: Vec<u8>,
+    parsed: OnceCell<T>,
+    modified: bool,
+}
+
+impl<T: Table> LazySubTable<T> {
+    /// Creates a sub-table from a table, which is written when the sub-table
+    /// is
+    ///
+    pub fn new(table: T) -> Self {
+        LazySubTable {
+            raw: Vec::new(),
+            parsed: OnceCell::from(table),
+            modified: true,
+        }
+    }
+
+    /// Creates a sub-table from the bytes of a table, which aren't parsed yet
+    ///
+    pub fn from_bytes(raw: Vec<u8>) -> Self {
+        LazySubTable {
+            raw,
+            parsed: OnceCell::new(),
+            modified: false,
+        }
+    }
+
+    /// Returns the bytes the sub-table was read from
+    ///
+    /// These don't reflect changes made with [`LazySubTable::parsed_mut`].
+    ///
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Returns `true` if the table has been parsed (or was given already
+    /// parsed)
+    ///
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    /// Returns `true` if the table will be written again, instead of writing
+    /// the bytes it was read from
+    ///
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Returns the table, parsing it if it hasn't been yet
+    ///
+    pub fn parse(&self) -> Result<&T> {
+        if let Some(table) = self.parsed.get() {
+            return Ok(table);
+        }
+        let table = T::read_from_slice(&self.raw)?;
+        Ok(self.parsed.get_or_init(|| table))
+    }
+
+    /// Returns the table mutably, parsing it if it hasn't been yet
+    ///
+    /// Afterwards, the sub-table is considered modified, so the table is
+    /// written again instead of the original bytes.
+    ///
+    pub fn parsed_mut(&mut self) -> Result<&mut T> {
+        self.parse()?;
+        self.modified = true;
+        Ok(self.parsed.get_mut().unwrap())
+    }
+
+    /// Returns the table, parsing it if it hasn't been yet
+    ///
+    pub fn into_inner(self) -> Result<T> {
+        match self.parsed.into_inner() {
+            Some(table) => Ok(table),
+            None => T::read_from_slice(&self.raw),
+        }
+    }
+}
+
+/// Creates a sub-table from an empty table (see [`Table::new`])
+///
+impl<T: Table> Default for LazySubTable<T> {
+    fn default() -> Self {
+        LazySubTable::new(T::new())
+    }
+}
+
+impl<T: Table> Value for LazySubTable<T> {
+    type Primitive = [u8];
+
+    #[inline]
+    fn from_primitive(value: Vec<u8>) -> std::result::Result<Self, Box<dyn std::error::Error>> {
+        Ok(LazySubTable::from_bytes(value))
+    }
+    fn to_primitive<'a>(
+        &'a self,
+    ) -> std::result::Result<Cow<'a, [u8]>, Box<dyn std::error::Error>> {
+        match self.parsed.get() {
+            Some(table) if self.modified => Ok(Cow::Owned(table.write_to_vec()?)),
+            _ => Ok(Cow::Borrowed(&self.raw)),
+        }
+    }
+}
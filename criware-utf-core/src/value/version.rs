@@ -0,0 +1,209 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use super::Value;
+use crate::Error;
+
+/**
+A version number made up of a major, minor, and patch version
+
+It's stored as a string (like `"2.3.1"`). Use [`PackedUtfVersion`] for a
+version packed into a [`u32`] instead. Versions are compared by their major,
+minor, and patch versions, in that order.
+
+When parsed, the minor and patch versions may be left out (`"2"` and `"2.3"`
+are the same as `"2.0.0"` and `"2.3.0"`), and leading zeros are ignored. The
+way the string was written is remembered though, so a version that's read and
+written back unchanged keeps its original text (apart from any whitespace
+around it). Parts that were left out are only written once they're nonzero.
+Versions made with [`UtfVersion::new`] are written with all three parts.
+
+# Example
+```
+# use criware_utf::{UtfVersion, utf_table};
+#[utf_table]
+struct Tool {
+    #[constant]
+    version: UtfVersion,
+}
+
+let mut version: UtfVersion = "2.03".parse()?;
+assert_eq!(version, UtfVersion::new(2, 3, 0));
+assert!(version > UtfVersion::new(2, 2, 9));
+assert_eq!(version.to_string(), "2.03");
+version.patch = 1;
+assert_eq!(version.to_string(), "2.03.1");
+assert_eq!(UtfVersion::new(2, 3, 0).to_string(), "2.3.0");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct UtfVersion {
+    pub major: u16,
+    pub minor: u8,
+    pub patch: u8,
+    /// How the version was written (see [`VersionText`])
+    text: VersionText,
+}
+
+/// How a [`UtfVersion`] was written, so it can be written back the same way
+#[derive(Debug, Clone, Copy)]
+struct VersionText {
+    /// The number of parts that were written
+    parts: usize,
+    /// The number of digits in each part, including leading zeros
+    widths: [usize; 3],
+}
+
+impl VersionText {
+    /// All three parts, without leading zeros
+    const FULL: VersionText = VersionText {
+        parts: 3,
+        widths: [1; 3],
+    };
+}
+
+impl UtfVersion {
+    /// Creates a new version
+    ///
+    pub const fn new(major: u16, minor: u8, patch: u8) -> Self {
+        UtfVersion {
+            major,
+            minor,
+            patch,
+            text: VersionText::FULL,
+        }
+    }
+
+    /// Unpacks a version from a [`u32`], where the major version is the upper
+    /// 16 bits, followed by the minor and patch versions
+    ///
+    pub const fn from_u32(value: u32) -> Self {
+        UtfVersion::new((value >> 16) as u16, (value >> 8) as u8, value as u8)
+    }
+
+    /// Packs the version into a [`u32`] (see [`UtfVersion::from_u32`])
+    ///
+    pub const fn to_u32(&self) -> u32 {
+        ((self.major as u32) << 16) | ((self.minor as u32) << 8) | (self.patch as u32)
+    }
+}
+
+impl Default for UtfVersion {
+    fn default() -> Self {
+        UtfVersion::new(0, 0, 0)
+    }
+}
+
+impl PartialEq for UtfVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_u32() == other.to_u32()
+    }
+}
+
+impl Eq for UtfVersion {}
+
+impl PartialOrd for UtfVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for UtfVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.to_u32().cmp(&other.to_u32())
+    }
+}
+
+impl std::hash::Hash for UtfVersion {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.to_u32().hash(state);
+    }
+}
+
+impl FromStr for UtfVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidVersion(s.to_owned());
+        let mut values = [0u16; 3];
+        let mut text = VersionText {
+            parts: 0,
+            widths: [1; 3],
+        };
+        for part in s.trim().split('.') {
+            if text.parts == 3 {
+                return Err(invalid());
+            }
+            values[text.parts] = part.parse().map_err(|_| invalid())?;
+            text.widths[text.parts] = part.len();
+            text.parts += 1;
+        }
+        let minor = u8::try_from(values[1]).map_err(|_| invalid())?;
+        let patch = u8::try_from(values[2]).map_err(|_| invalid())?;
+        Ok(UtfVersion {
+            text,
+            ..UtfVersion::new(values[0], minor, patch)
+        })
+    }
+}
+
+impl fmt::Display for UtfVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = [self.major, self.minor as u16, self.patch as u16];
+        let parts = match values {
+            [_, _, 0] if self.minor == 0 => self.text.parts.max(1),
+            [_, _, 0] => self.text.parts.max(2),
+            _ => 3,
+        };
+        for (index, (value, width)) in values.iter().zip(self.text.widths).take(parts).enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{value:0width$}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Value for UtfVersion {
+    type Primitive = str;
+
+    fn from_primitive(value: String) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(value.parse()?)
+    }
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, str>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.to_string()))
+    }
+}
+
+/**
+A [`UtfVersion`] stored as a [`u32`] (see [`UtfVersion::from_u32`])
+
+# Example
+```
+# use criware_utf::{PackedUtfVersion, UtfVersion, utf_table};
+#[utf_table]
+struct Tool {
+    #[constant]
+    version: PackedUtfVersion,
+}
+
+let version = PackedUtfVersion(UtfVersion::new(2, 3, 1));
+assert_eq!(version.0.to_u32(), 0x0002_0301);
+```
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedUtfVersion(pub UtfVersion);
+
+impl Value for PackedUtfVersion {
+    type Primitive = u32;
+
+    #[inline]
+    fn from_primitive(value: u32) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(PackedUtfVersion(UtfVersion::from_u32(value)))
+    }
+    #[inline]
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, u32>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.0.to_u32()))
+    }
+}
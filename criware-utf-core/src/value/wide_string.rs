@@ -0,0 +1,90 @@
+use std::{borrow::Cow, fmt};
+
+use super::Value;
+use crate::Error;
+
+/**
+A string stored in a blob as UTF-16
+
+When read, a byte order mark at the start of the blob selects between little
+and big endian (without one, it's little endian), and is removed from the
+string. It's always written as little endian UTF-16, without a byte order
+mark. Blobs that aren't valid UTF-16 fail to be read with
+[`Error::StringUndecodable`].
+
+# Example
+```
+# use criware_utf::{WideString, utf_table};
+#[utf_table]
+struct Subtitles {
+    id: u32,
+    text: WideString,
+}
+
+let text = WideString::decode(&[0xfe, 0xff, 0x00, 0x48, 0x00, 0x69])?;
+assert_eq!(text.0, "Hi");
+assert_eq!(text.encode(), [0x48, 0x00, 0x69, 0x00]);
+# Ok::<(), criware_utf::Error>(())
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WideString(pub String);
+
+impl WideString {
+    /// Decodes a string from UTF-16 (see [`WideString`])
+    ///
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        let invalid = || Error::StringUndecodable("UTF-16");
+        if !data.len().is_multiple_of(2) {
+            return Err(invalid());
+        }
+        let (data, big_endian) = match data {
+            [0xfe, 0xff, rest @ ..] => (rest, true),
+            [0xff, 0xfe, rest @ ..] => (rest, false),
+            _ => (data, false),
+        };
+        let units = data.chunks_exact(2).map(|pair| match big_endian {
+            true => u16::from_be_bytes([pair[0], pair[1]]),
+            false => u16::from_le_bytes([pair[0], pair[1]]),
+        });
+        char::decode_utf16(units)
+            .collect::<Result<String, _>>()
+            .map(WideString)
+            .map_err(|_| invalid())
+    }
+
+    /// Encodes the string as little endian UTF-16, without a byte order mark
+    ///
+    pub fn encode(&self) -> Vec<u8> {
+        self.0.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+}
+
+impl From<String> for WideString {
+    fn from(value: String) -> Self {
+        WideString(value)
+    }
+}
+
+impl From<&str> for WideString {
+    fn from(value: &str) -> Self {
+        WideString(value.to_owned())
+    }
+}
+
+impl fmt::Display for WideString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Value for WideString {
+    type Primitive = [u8];
+
+    fn from_primitive(value: Vec<u8>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(WideString::decode(&value)?)
+    }
+    fn to_primitive<'a>(&'a self) -> Result<Cow<'a, [u8]>, Box<dyn std::error::Error>> {
+        Ok(Cow::Owned(self.encode()))
+    }
+}
@@ -1,58 +1,470 @@
-use std::{any::type_name, borrow::Cow, collections::HashMap, io::Write};
+use std::{
+    any::type_name,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::Write,
+    sync::Arc,
+};
 
-use crate::{Error, IOErrorHelper, Result, Value, ValueKind, value::sealed::Primitive};
+use crate::{
+    AnyValue, CellHook, Encoding, Endianness, Error, IOErrorHelper, Result, Value, ValueKind,
+    value::sealed::{Primitive, primitive_type_flag},
+};
 
-/**
-Extra contextual info for accurating recreating read tables when writing
+mod builder;
+mod original;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod profile;
+mod promote;
+mod raw;
+mod round_trip;
+mod stream;
 
-This entirely exists to handle the edge case where you read a table, remove
-all of its rows, and then try to write it. If there are optional rowed values,
-there is no clear way to know if a column should be written as zero or rowed.
+pub use builder::WriterBuilder;
+pub use original::OriginalLayout;
+pub use profile::WriterProfile;
+pub use raw::RawWriter;
+pub use round_trip::RoundTripContext;
+use stream::RowStream;
 
-This is used by the `utf_table` macro. When a table is read, a context is
-created with the state of the columns. When a table is created, a context is
-created and configured based on the schema provided to the macro.
+/// The old name of [`RoundTripContext`]
+#[deprecated = "use `RoundTripContext` instead"]
+pub type WriteContext = RoundTripContext;
 
-It is untested whether or not this approach holds, so this type is **subject
-to removal**.
- */
-pub struct WriteContext(HashMap<&'static str, bool>);
+/// The order strings are stored in the string data section
+///
+/// Either way, each string is only stored once, and the same calls always
+/// produce the same table.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringOrder {
+    /// The order they were first used in (the default)
+    #[default]
+    Insertion,
+    /// The NULL sentinel, table name, and column names (in column order),
+    /// followed by every other string in the order it was first used
+    ///
+    /// Unlike [`Insertion`](StringOrder::Insertion), the name of a constant
+    /// column never follows the string value of the column before it. Some
+    /// tables seen in the wild are laid out this way, but this isn't checked
+    /// against any particular packer, so compare the output with a reference
+    /// table before relying on it to match one byte for byte.
+    NamesFirst,
+}
 
-impl WriteContext {
+/// Where empty strings are stored in the string data section
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyStrings {
+    /// Every empty string refers to the same entry, like any other string
+    /// (the default)
+    #[default]
+    Shared,
+    /// Every empty string refers to the NULL sentinel (see
+    /// [`WriterOptions::null_sentinel`]), or is shared if there isn't one
     ///
-    /// Creates a new write context
+    /// Unless the sentinel is empty, these strings are read back as the
+    /// sentinel itself, unless the sentinel is given to
+    /// [`ReaderOptions::empty_sentinel`](crate::ReaderOptions::empty_sentinel).
     ///
-    pub fn new() -> Self {
-        WriteContext(HashMap::new())
-    }
+    /// # Example
+    /// ```
+    /// # use criware_utf::{EmptyStrings, Reader, ReaderOptions, Table, WriterOptions, utf_table};
+    /// #[utf_table]
+    /// struct Strings {
+    ///     name: String,
+    /// }
     ///
-    /// Returns [`true`] if the given column should be included (rowed), or
-    /// [`false`] if it should be excluded (zero)
+    /// let mut table = Strings::new();
+    /// table.rows.push(StringsRow { name: String::new() });
+    /// let mut data = Vec::new();
+    /// let options = WriterOptions {
+    ///     empty_strings: EmptyStrings::Sentinel,
+    ///     ..Strings::writer_options()
+    /// };
+    /// table.write_with_options(&mut data, options)?;
     ///
-    pub fn is_included(&self, column_name: &str) -> bool {
-        match self.0.get(column_name) {
-            Some(v) => *v,
-            None => true,
-        }
-    }
+    /// let options = ReaderOptions {
+    ///     empty_sentinel: Some("<NULL>".to_owned()),
+    ///     ..Strings::reader_options()
+    /// };
+    /// let table = Strings::read_from_reader(Reader::from_bytes_with_options(&data, options)?)?;
+    /// assert_eq!(table.rows[0].name, "");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    Sentinel,
+    /// Every empty string gets its own entry
+    Separate,
+}
+
+/// Where the table name is stored in the string data section
+///
+/// The placements other than [`AfterSentinel`](TableNamePlacement::AfterSentinel)
+/// and [`BeforeSentinel`](TableNamePlacement::BeforeSentinel) are worked out
+/// once every string is known, so they're ignored when the rows are streamed
+/// (like [`WriterOptions::string_order`]). [`Reader::table_name_placement`]
+/// returns the placement of a table that was read.
+///
+/// [`Reader::table_name_placement`]: crate::Reader::table_name_placement
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableNamePlacement {
+    /// Right after the sentinel (the default)
+    ///
+    /// With the default `<NULL>` sentinel, the table name is at offset 7.
+    #[default]
+    AfterSentinel,
+    /// At the start of the section, right before the sentinel
+    BeforeSentinel,
+    /// Right after the last column name
+    ///
+    /// This is mostly seen with [`StringOrder::NamesFirst`], where it puts
+    /// the table name between the column names and the other strings.
+    AfterColumnNames,
+    /// At the given position among the strings in the section (starting
+    /// from 0), or at the end if there aren't enough strings
+    Position(usize),
+}
+
+/// Whether a [`Writer`] changes how columns are stored when the table is
+/// written
+///
+/// Columns stored as zero are never changed, and neither is anything in a
+/// table without rows or without rowed columns.
+///
+/// # Example
+/// ```
+/// # use criware_utf::{ColumnPromotion, Writer, WriterOptions};
+/// let write = |column_promotion| {
+///     let options = WriterOptions {
+///         column_promotion,
+///         ..Default::default()
+///     };
+///     let mut writer = Writer::with_options("Tab", options);
+///     writer.push_constant_column_owned("Version", 2u32)?;
+///     for _ in 0..3 {
+///         writer.begin_row()?;
+///         writer.end_row()?;
+///     }
+///     let mut data = Vec::new();
+///     writer.end(&mut data, 0, 3)?;
+///     Ok::<_, criware_utf::Error>(data)
+/// };
+/// let plain = write(ColumnPromotion::None)?;
+/// assert_eq!(write(ColumnPromotion::RowedToConstant)?, plain);
+/// assert_eq!(write(ColumnPromotion::ConstantToRowed)?, plain);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnPromotion {
+    /// Every column is stored the way it was pushed (the default)
+    #[default]
+    None,
+    /// Rowed columns whose value is the same in every row are stored as
+    /// constant columns instead
+    ///
+    /// Blobs are compared by their contents, and when a BLOB column is made
+    /// constant, the blob data is rebuilt with only the copies that are still
+    /// used. Tables generated by the `utf_table` macro still read these
+    /// columns as rowed, repeating the value in every row (see
+    /// [`Reader::read_rowed_column`]).
+    ///
+    /// [`Reader::read_rowed_column`]: crate::Reader::read_rowed_column
+    ///
+    /// # Example
+    /// ```
+    /// # use criware_utf::{ColumnPromotion, Table, WriterOptions, utf_table};
+    /// #[utf_table]
+    /// struct Tab {
+    ///     version: u32,
+    ///     size: u64,
+    /// }
+    ///
+    /// let mut table = Tab::new();
+    /// for size in [10, 20, 30] {
+    ///     table.rows.push(TabRow { version: 2, size });
+    /// }
+    /// let mut data = Vec::new();
+    /// let options = WriterOptions {
+    ///     column_promotion: ColumnPromotion::RowedToConstant,
+    ///     ..Tab::writer_options()
+    /// };
+    /// table.write_with_options(&mut data, options)?;
     ///
-    /// Sets the inclusion state of a column. [`true`] denotes rowed, [`false`]
-    /// denotes zero
+    /// let table = Tab::read_from_slice(&data)?;
+    /// assert_eq!(table.rows.len(), 3);
+    /// assert!(table.rows.iter().all(|row| row.version == 2));
+    /// assert_eq!(table.rows[2].size, 30);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
     ///
-    pub fn set_inclusion_state(&mut self, column_name: &'static str, included: bool) {
-        self.0.insert(column_name, included);
+    /// Identical blobs are only stored once:
+    /// ```
+    /// # use criware_utf::{ColumnPromotion, Table, WriterOptions, utf_table};
+    /// #[utf_table]
+    /// struct Tab {
+    ///     id: u32,
+    ///     data: Vec<u8>,
+    /// }
+    ///
+    /// let mut table = Tab::new();
+    /// for id in 0..4 {
+    ///     table.rows.push(TabRow { id, data: vec![7; 64] });
+    /// }
+    /// let plain = table.write_to_vec()?;
+    /// let mut data = Vec::new();
+    /// let options = WriterOptions {
+    ///     column_promotion: ColumnPromotion::RowedToConstant,
+    ///     ..Tab::writer_options()
+    /// };
+    /// table.write_with_options(&mut data, options)?;
+    /// // three of the four blobs, and the BLOB cell in every row, are gone
+    /// assert_eq!(plain.len() - data.len(), 3 * 64 + 4 * 8 - 8);
+    ///
+    /// let table = Tab::read_from_slice(&data)?;
+    /// assert!(table.rows.iter().all(|row| row.data == [7; 64]));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    RowedToConstant,
+    /// Constant columns are stored as rowed columns instead, with their value
+    /// repeated in every row
+    ///
+    /// Tables generated by the `utf_table` macro still read these columns as
+    /// constant, as long as their value is the same in every row (see
+    /// [`Reader::read_constant_column`]). Tables without rows are written
+    /// unchanged.
+    ///
+    /// [`Reader::read_constant_column`]: crate::Reader::read_constant_column
+    ///
+    /// # Example
+    /// ```
+    /// # use criware_utf::{ColumnPromotion, Table, WriterOptions, utf_table};
+    /// #[utf_table]
+    /// struct Tab {
+    ///     #[constant]
+    ///     version: u32,
+    ///     size: u64,
+    /// }
+    ///
+    /// let mut table = Tab::new();
+    /// table.constants.version = 2;
+    /// for size in [10, 20, 30] {
+    ///     table.rows.push(TabRow { size });
+    /// }
+    /// let mut data = Vec::new();
+    /// let options = WriterOptions {
+    ///     column_promotion: ColumnPromotion::ConstantToRowed,
+    ///     ..Tab::writer_options()
+    /// };
+    /// table.write_with_options(&mut data, options)?;
+    ///
+    /// let table = Tab::read_from_slice(&data)?;
+    /// assert_eq!(table.constants.version, 2);
+    /// assert_eq!(table.rows.len(), 3);
+    /// assert_eq!(table.rows[2].size, 30);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ConstantToRowed,
+}
+
+/// Options for how a [`Writer`] lays out a table
+///
+/// [`WriterProfile`] has presets for some common combinations of options.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriterOptions {
+    /// The byte order of the numbers stored in the table (big-endian by
+    /// default)
+    pub endianness: Endianness,
+    /// The encoding of the strings stored in the table (UTF-8 by default)
+    pub encoding: Encoding,
+    /// The order of the strings in the string data section
+    pub string_order: StringOrder,
+    /// The string stored at the start of the string data section (`<NULL>`
+    /// by default), or [`None`] for no sentinel at all
+    ///
+    /// Some games use an empty string instead, which empty strings written to
+    /// the table then share.
+    pub null_sentinel: Option<String>,
+    /// Where the table name is stored in the string data section
+    pub table_name_placement: TableNamePlacement,
+    /// Where empty strings are stored
+    pub empty_strings: EmptyStrings,
+    /// The alignment of the blob data section (8 by default), relative to the
+    /// end of the `@UTF` magic and table size
+    ///
+    /// Like official tables, at least one byte of padding always follows the
+    /// string data section. If this is 0, there is no padding at all.
+    pub blob_align: u32,
+    /// The byte that padding is made of (0 by default)
+    pub pad_byte: u8,
+    /// The alignment of the size of the entire table (1 by default, which
+    /// doesn't add anything)
+    ///
+    /// The padding is added to the end of the blob data section, so it's
+    /// included in the table size stored in the header.
+    pub table_align: u32,
+    /// The layout of a table that was read, which is reused as much as
+    /// possible (none by default)
+    ///
+    /// This makes a table that's written back unmodified byte-identical to
+    /// the original. While it's set, `string_order` is ignored, and so are
+    /// `blob_align` and `table_align` unless strings or blobs are added.
+    /// See [`Reader::round_trip_options`](crate::Reader::round_trip_options).
+    pub original_layout: Option<Arc<OriginalLayout>>,
+    /// Whether the storage of columns is changed when the table is written
+    ///
+    /// The row size given to [`Writer::end`] is the size before any columns
+    /// are changed. This is ignored when the rows are streamed.
+    pub column_promotion: ColumnPromotion,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            endianness: Endianness::Big,
+            encoding: Encoding::Utf8,
+            string_order: StringOrder::Insertion,
+            null_sentinel: Some("<NULL>".to_owned()),
+            table_name_placement: TableNamePlacement::AfterSentinel,
+            empty_strings: EmptyStrings::Shared,
+            blob_align: 8,
+            pad_byte: 0,
+            table_align: 1,
+            original_layout: None,
+            column_promotion: ColumnPromotion::None,
+        }
+    }
+}
+
+/**
+Where each section of a written table went, as returned by [`Writer::end`]
+
+Like the offsets stored in the header (and returned by [`Reader`]), the
+offsets are relative to the end of the `@UTF` magic and table size, which
+aren't included in the table size either. The table takes up
+`table_size + 8` bytes in total.
+
+[`Reader`]: crate::Reader
+
+# Example
+```no_run
+# use criware_utf_core::Writer;
+let mut data = Vec::new();
+let writer = Writer::new("ImportantTable");
+// ... table writing code ...
+let layout = writer.end(&mut data, 0, 0)?;
+assert_eq!(data.len(), layout.table_size as usize + 8);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableLayout {
+    /// The size of the table, as stored in its header
+    pub table_size: u32,
+    /// The offset of the row data section
+    pub rows_offset: u32,
+    /// The offset of the string data section
+    pub strings_offset: u32,
+    /// The offset of the blob data section
+    pub data_offset: u32,
+    /// The size (in bytes) of each row
+    pub row_size: u16,
+    /// The number of rows
+    pub row_count: u32,
+}
+
+/// Where each section of a table goes, as worked out by [`Writer::layout`]
+struct Layout {
+    row_offset: u32,
+    string_offset: u32,
+    blob_offset: u32,
+    table_size: u32,
+    /// The offset of the table name in the string data section
+    table_name: u32,
+    blob_padding: u32,
+    table_padding: u32,
+    /// Whether the padding after the string data section is the original
+    /// padding (see [`WriterOptions::original_layout`])
+    original_padding: bool,
+}
+
+impl Layout {
+    /// Returns the parts of the layout that are reported by [`Writer::end`]
+    fn report(&self, row_size: u16, row_count: u32) -> TableLayout {
+        TableLayout {
+            table_size: self.table_size,
+            rows_offset: self.row_offset,
+            strings_offset: self.string_offset,
+            data_offset: self.blob_offset,
+            row_size,
+            row_count,
+        }
     }
 }
 
+/// The sections of a table, as returned by [`Writer::final_sections`]
+struct FinalSections<'b> {
+    column_data: Cow<'b, [u8]>,
+    row_data: Cow<'b, [u8]>,
+    string_data: Cow<'b, [u8]>,
+    blob_data: Cow<'b, [u8]>,
+    row_size: u16,
+    /// The offset of the table name in the string data
+    table_name: u32,
+}
+
 /// Abstraction layer for writing UTF tables
 ///
+/// Writing is deterministic: the same calls, with the same options, always
+/// produce the same bytes.
+///
 pub struct Writer<'a> {
     column_data: Vec<u8>,
     row_data: Vec<u8>,
     strings: HashMap<Cow<'a, str>, u32>,
     string_data: Vec<u8>,
     blobs: Vec<u8>,
-    field_count: u16,
+    field_count: u32,
+    endianness: Endianness,
+    encoding: Encoding,
+    string_order: StringOrder,
+    blob_align: u32,
+    pad_byte: u8,
+    table_align: u32,
+    unencodable_name: Option<String>,
+    stream: Option<RowStream<'a>>,
+    /// The offset of the table name in the string data section
+    table_name: u32,
+    /// The offset of the NULL sentinel in the string data section, if there
+    /// is one
+    null_sentinel: Option<u32>,
+    empty_strings: EmptyStrings,
+    table_name_placement: TableNamePlacement,
+    original_layout: Option<Arc<OriginalLayout>>,
+    /// The first copy of each blob in the original layout, keyed by its hash
+    original_blobs: HashMap<u64, Vec<(u32, u32)>>,
+    /// The number of strings and blobs written so far
+    string_refs: usize,
+    blob_refs: usize,
+    column_promotion: ColumnPromotion,
+    /// The type flag of every rowed column
+    rowed_flags: Vec<u8>,
+    /// The name of every rowed column (for error reporting)
+    rowed_names: Vec<String>,
+    /// The name of the last constant column (for error reporting)
+    last_constant: Option<String>,
+    /// The type flags of the values written to the current row, if one was
+    /// started with [`Writer::begin_row`]
+    current_row: Option<Vec<u8>>,
+    /// The number of rows ended with [`Writer::end_row`]
+    rows_ended: u32,
+    /// The strings written so far, if this writer is writing a chunk of rows
+    /// in parallel (every string is replaced by its index here)
+    #[cfg(feature = "rayon")]
+    deferred_strings: Option<Vec<parallel::DeferredString<'a>>>,
 }
 
 impl<'a> Writer<'a> {
@@ -65,7 +477,92 @@ impl<'a> Writer<'a> {
     let writer = Writer::new("ImportantTable");
     ```
      */
-    pub fn new(table_name: &'a str) -> Writer<'a> {
+    pub fn new(table_name: impl Into<Cow<'a, str>>) -> Writer<'a> {
+        Self::with_endianness(table_name, Endianness::Big)
+    }
+
+    /**
+    Creates a new `Writer` that stores numbers with the given byte order
+
+    Official tables are always big-endian, which is what [`Writer::new`]
+    uses. This only needs to be used for nonstandard tables.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{Endianness, Writer};
+    let writer = Writer::with_endianness("ImportantTable", Endianness::Little);
+    ```
+     */
+    pub fn with_endianness(
+        table_name: impl Into<Cow<'a, str>>,
+        endianness: Endianness,
+    ) -> Writer<'a> {
+        Self::with_encoding(table_name, endianness, Encoding::Utf8)
+    }
+
+    /**
+    Creates a new `Writer` that stores numbers with the given byte order, and
+    strings with the given encoding
+
+    Modern tables always use UTF-8, which is what [`Writer::new`] uses. This
+    only needs to be used to recreate legacy tables.
+
+    If the table name or a column name can't be represented by the encoding,
+    [`Writer::end`] will fail.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{Encoding, Endianness, Writer};
+    let writer = Writer::with_encoding("ImportantTable", Endianness::Big, Encoding::ShiftJis);
+    ```
+     */
+    pub fn with_encoding(
+        table_name: impl Into<Cow<'a, str>>,
+        endianness: Endianness,
+        encoding: Encoding,
+    ) -> Writer<'a> {
+        Self::with_options(
+            table_name,
+            WriterOptions {
+                endianness,
+                encoding,
+                ..Default::default()
+            },
+        )
+    }
+
+    /**
+    Creates a new `Writer` that lays out the table with the given options
+
+    See [`WriterOptions`].
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{StringOrder, Writer, WriterOptions};
+    let writer = Writer::with_options(
+        "ImportantTable",
+        WriterOptions {
+            string_order: StringOrder::NamesFirst,
+            ..Default::default()
+        },
+    );
+    ```
+     */
+    pub fn with_options(table_name: impl Into<Cow<'a, str>>, options: WriterOptions) -> Writer<'a> {
+        let WriterOptions {
+            endianness,
+            encoding,
+            string_order,
+            null_sentinel,
+            table_name_placement,
+            empty_strings,
+            blob_align,
+            pad_byte,
+            table_align,
+            original_layout,
+            column_promotion,
+        } = options;
+        let table_name = table_name.into();
         let mut writer = Writer {
             column_data: Vec::new(),
             row_data: Vec::new(),
@@ -73,19 +570,334 @@ impl<'a> Writer<'a> {
             string_data: Vec::new(),
             blobs: Vec::new(),
             field_count: 0,
+            endianness,
+            encoding,
+            string_order,
+            blob_align,
+            pad_byte,
+            table_align,
+            unencodable_name: None,
+            stream: None,
+            table_name: 0,
+            null_sentinel: None,
+            empty_strings,
+            table_name_placement,
+            original_layout: None,
+            original_blobs: HashMap::new(),
+            string_refs: 0,
+            blob_refs: 0,
+            column_promotion,
+            rowed_flags: Vec::new(),
+            rowed_names: Vec::new(),
+            last_constant: None,
+            current_row: None,
+            rows_ended: 0,
+            #[cfg(feature = "rayon")]
+            deferred_strings: None,
         };
-        writer.strings.insert(Cow::Borrowed("<NULL>"), 0);
-        writer.strings.insert(Cow::Borrowed(table_name), 7);
-        writer.string_data.extend_from_slice(b"<NULL>\0");
-        writer.string_data.extend_from_slice(table_name.as_bytes());
-        writer.string_data.push(0u8);
+        match original_layout {
+            Some(original) => {
+                writer.strings = original
+                    .first_strings()
+                    .into_iter()
+                    .map(|(string, offset)| (Cow::Owned(string), offset))
+                    .collect();
+                writer.string_data = original.string_data.clone();
+                writer.original_blobs = original.first_blobs();
+                writer.blobs = original.blob_data.clone();
+                writer.original_layout = Some(original);
+                writer.table_name = writer.push_name(table_name);
+            }
+            None => {
+                let name_first = table_name_placement == TableNamePlacement::BeforeSentinel;
+                if name_first {
+                    writer.table_name = writer.push_name(table_name.clone());
+                }
+                if let Some(sentinel) = null_sentinel {
+                    match writer.push_string(Cow::Owned(sentinel.clone())) {
+                        Ok(position) => writer.null_sentinel = Some(position),
+                        Err(_) => {
+                            writer.unencodable_name.get_or_insert(sentinel);
+                        }
+                    }
+                }
+                if !name_first {
+                    writer.table_name = writer.push_name(table_name);
+                }
+            }
+        }
+        writer
+    }
+
+    /**
+    Creates a new `Writer` with room for the given amount of data
+
+    This avoids growing the buffers over and over when writing large tables.
+    See [`Writer::reserve`].
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # let row_count = 100000;
+    // 16 bytes per row, with roughly 8 bytes of new strings per row
+    let writer = Writer::with_capacity("HugeTable", row_count, 16, row_count * 8, 0);
+    ```
+     */
+    pub fn with_capacity(
+        table_name: impl Into<Cow<'a, str>>,
+        rows: usize,
+        avg_row_size: usize,
+        est_string_bytes: usize,
+        est_blob_bytes: usize,
+    ) -> Writer<'a> {
+        let mut writer = Self::new(table_name);
+        writer.reserve(rows, avg_row_size, est_string_bytes, est_blob_bytes);
         writer
     }
 
+    /**
+    Reserves room for at least the given amount of additional data
+
+    `rows` rows of `row_size` bytes are reserved in the row buffer, and the
+    string and blob estimates (in bytes) are reserved in their sections.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # let names: Vec<String> = Vec::new();
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_rowed_column::<String>("Name");
+    writer.reserve(names.len(), 4, 0, 0);
+    ```
+     */
+    pub fn reserve(
+        &mut self,
+        rows: usize,
+        row_size: usize,
+        string_bytes: usize,
+        blob_bytes: usize,
+    ) {
+        if self.stream.is_none() {
+            self.row_data.reserve(rows.saturating_mul(row_size));
+        }
+        self.string_data.reserve(string_bytes);
+        self.blobs.reserve(blob_bytes);
+    }
+
+    /// Returns the byte order of the table being written
+    pub(crate) fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the encoding of the strings in the table being written
+    pub(crate) fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Adds a string to the string data section (if it isn't there already),
+    /// and returns its offset
+    pub(crate) fn push_string(&mut self, value: Cow<'a, str>) -> Result<u32> {
+        if value.contains('\0') {
+            return Err(Error::StringContainsNul(value.into_owned()));
+        }
+        #[cfg(feature = "rayon")]
+        if let Some(deferred) = &mut self.deferred_strings {
+            deferred.push(parallel::DeferredString::Text(value));
+            return Ok((deferred.len() - 1) as u32);
+        }
+        self.string_refs += 1;
+        if let Some(original) = &self.original_layout
+            && let Some(position) = original.string_ref(self.string_refs - 1, &value)
+        {
+            return Ok(position);
+        }
+        let separate = match (self.empty_strings, self.null_sentinel) {
+            _ if !value.is_empty() => false,
+            (EmptyStrings::Sentinel, Some(position)) => return Ok(position),
+            (EmptyStrings::Separate, _) => true,
+            _ => false,
+        };
+        if !separate && let Some(position) = self.strings.get(&value) {
+            return Ok(*position);
+        }
+        let position = u32::try_from(self.string_data.len())
+            .map_err(|_| Error::TableTooLarge("string data"))?;
+        self.string_data
+            .extend_from_slice(&self.encoding.encode(&value)?);
+        self.string_data.push(0u8);
+        self.strings.insert(value, position);
+        Ok(position)
+    }
+
+    /// Adds a string that may not be valid in the table's encoding to the
+    /// string data section, and returns its offset
+    ///
+    /// Strings that can be decoded are added like any other string (see
+    /// [`Writer::push_string`]). The rest are added as-is, without being
+    /// shared with other strings.
+    pub(crate) fn push_raw_string(&mut self, value: &[u8]) -> Result<u32> {
+        if let Ok(string) = self.encoding.decode(value)
+            && self
+                .encoding
+                .encode(&string)
+                .is_ok_and(|bytes| bytes == value)
+        {
+            return self.push_string(Cow::Owned(string.into_owned()));
+        }
+        if value.contains(&0) {
+            return Err(Error::StringContainsNul(
+                String::from_utf8_lossy(value).into_owned(),
+            ));
+        }
+        #[cfg(feature = "rayon")]
+        if let Some(deferred) = &mut self.deferred_strings {
+            deferred.push(parallel::DeferredString::Raw(value.to_vec()));
+            return Ok((deferred.len() - 1) as u32);
+        }
+        self.string_refs += 1;
+        if let Some(original) = &self.original_layout
+            && let Some(position) = original.raw_string_ref(self.string_refs - 1, value)
+        {
+            return Ok(position);
+        }
+        let position = u32::try_from(self.string_data.len())
+            .map_err(|_| Error::TableTooLarge("string data"))?;
+        self.string_data.extend_from_slice(value);
+        self.string_data.push(0u8);
+        Ok(position)
+    }
+
+    /// Adds a table or column name to the string data section, and returns
+    /// its offset
+    ///
+    /// If the name can't be encoded, the error is reported by [`Writer::end`].
+    fn push_name(&mut self, name: Cow<'a, str>) -> u32 {
+        match self.push_string(name.clone()) {
+            Ok(position) => position,
+            Err(_) => {
+                self.unencodable_name
+                    .get_or_insert_with(|| name.into_owned());
+                0
+            }
+        }
+    }
+
+    /// Returns the error for the first table or column name (or null
+    /// sentinel) that couldn't be added to the string data section
+    fn check_names(&self) -> Result<()> {
+        match &self.unencodable_name {
+            Some(name) if name.contains('\0') => Err(Error::StringContainsNul(name.clone())),
+            Some(name) => Err(Error::StringUnencodable(name.clone(), self.encoding.name())),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the storage/type flag and name of a column to the column buffer
+    fn push_column_header(&mut self, flag: u8, name: Cow<'a, str>) {
+        match flag & 0xf0 {
+            0x30 => self.last_constant = Some(name.to_string()),
+            0x50 => {
+                self.rowed_flags.push(flag & 0x0f);
+                self.rowed_names.push(name.to_string());
+            }
+            _ => {}
+        }
+        let name = self.push_name(name);
+        self.column_data.push(flag);
+        self.column_data
+            .extend_from_slice(&self.endianness.u32_to(name));
+        self.field_count += 1;
+    }
+
+    /// Returns the name of the column that the next value written to the
+    /// column or row buffer belongs to, along with its row (if it's rowed)
+    fn next_column(&self, rowed: bool) -> Option<(String, Option<u32>)> {
+        if !rowed {
+            return Some((self.last_constant.clone()?, None));
+        }
+        if let Some(row) = &self.current_row {
+            return Some((
+                self.rowed_names.get(row.len())?.clone(),
+                Some(self.rows_ended),
+            ));
+        }
+        let sizes = self
+            .rowed_flags
+            .iter()
+            .map(|flag| ValueKind::from_flag(*flag).map(|kind| kind.size() as u64))
+            .collect::<Option<Vec<u64>>>()?;
+        let row_size: u64 = sizes.iter().sum();
+        let written = match &self.stream {
+            Some(stream) => stream.rows_len,
+            None => self.row_data.len() as u64,
+        };
+        let position = written.checked_rem(row_size)?;
+        let mut offset = 0;
+        for (name, size) in self.rowed_names.iter().zip(sizes) {
+            if offset == position {
+                return Some((name.clone(), Some((written / row_size) as u32)));
+            }
+            offset += size;
+        }
+        None
+    }
+
+    /// Wraps an error from converting a value to its primitive, saying which
+    /// column it was written to (see [`Error::InColumn`])
+    fn conversion_error<T: Value>(&self, rowed: bool, error: Box<dyn std::error::Error>) -> Error {
+        let error = Error::ValueConversion(type_name::<T>(), type_name::<T::Primitive>(), error);
+        match self.next_column(rowed) {
+            Some((name, row)) => Error::InColumn(name, row, Box::new(error)),
+            None => error,
+        }
+    }
+
+    /// Adds a blob to the blob data section, and returns its offset
+    pub(crate) fn push_blob(&mut self, value: &[u8]) -> Result<u32> {
+        self.blob_refs += 1;
+        if let Some(original) = &self.original_layout
+            && let Some(position) = original
+                .blob_ref(self.blob_refs - 1, value)
+                .or_else(|| original.find_blob(&self.original_blobs, value))
+        {
+            return Ok(position);
+        }
+        if let Some(stream) = &mut self.stream
+            && let Some(spool) = &mut stream.blob_spool
+        {
+            let position =
+                u32::try_from(stream.blobs_len).map_err(|_| Error::TableTooLarge("blob data"))?;
+            spool.write_all(value).io("UTF blobs")?;
+            stream.blobs_len += value.len() as u64;
+            return Ok(position);
+        }
+        let position =
+            u32::try_from(self.blobs.len()).map_err(|_| Error::TableTooLarge("blob data"))?;
+        self.blobs.extend_from_slice(value);
+        Ok(position)
+    }
+
+    /// Adds the bytes of a value to the column or row buffer (or the stream
+    /// the rows are written to)
+    fn push_value_bytes(&mut self, rowed: bool, bytes: &[u8]) -> Result<()> {
+        if !rowed {
+            self.column_data.extend_from_slice(bytes);
+        } else if let Some(stream) = &mut self.stream {
+            stream.destination.write_all(bytes).io("UTF row data")?;
+            stream.rows_len += bytes.len() as u64;
+        } else {
+            self.row_data.extend_from_slice(bytes);
+        }
+        Ok(())
+    }
+
     /**
     Verifies the amount of data written to the row buffer, and writes the final
     UTF table to the given stream.
 
+    Returns where each section of the table went (see [`TableLayout`]), which
+    is useful for laying out the container the table is stored in.
+
     # Example
     ```no_run
     # use std::fs::File;
@@ -93,68 +905,342 @@ impl<'a> Writer<'a> {
     let mut file = File::create("important-table.bin")?;
     let writer = Writer::new("ImportantTable");
     // ... table writing code ...
-    writer.end(file, 12, 1000)?:
+    writer.end(&mut file, 12, 1000)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn end(&self, writer: &mut dyn Write, row_size: u16, row_count: u32) -> Result<()> {
-        if self.row_data.len() != (row_size as usize) * (row_count as usize) {
+    pub fn end(
+        &self,
+        writer: &mut dyn Write,
+        row_size: u16,
+        row_count: u32,
+    ) -> Result<TableLayout> {
+        let sections = self.final_sections(row_size, row_count)?;
+        let layout = self.final_layout(&sections)?;
+        self.write_header(writer, &layout, sections.row_size, row_count)?;
+        self.write_sections(writer, &layout, &sections)?;
+        Ok(layout.report(sections.row_size, row_count))
+    }
+
+    /// Verifies the amount of data written to the row buffer, and returns the
+    /// column data, row data, string data, row size, and table name offset of
+    /// the final table
+    ///
+    /// Columns are promoted and strings are reordered here, according to the
+    /// options.
+    fn final_sections(&self, row_size: u16, row_count: u32) -> Result<FinalSections<'_>> {
+        self.check_row_ended()?;
+        if self.stream.is_some()
+            || self.row_data.len() != (row_size as usize) * (row_count as usize)
+        {
             return Err(Error::MalformedHeader);
         }
-        let zeroes = [0u8; 8];
-        let row_offset = self.column_data.len() as u32 + 24;
-        let string_offset = row_offset + self.row_data.len() as u32;
-        let mut blob_offset = string_offset + self.string_data.len() as u32;
-        let blob_offset_remainder = 8 - (blob_offset & 7);
-        blob_offset += blob_offset_remainder;
-        let table_name: u32 = 7;
-        let table_size = blob_offset + self.blobs.len() as u32;
+        self.check_names()?;
+        let (column_data, row_data, row_size, blob_data) =
+            match self.promote_columns(row_size, row_count)? {
+                Some((column_data, row_data, row_size, blob_data)) => (
+                    Cow::Owned(column_data),
+                    Cow::Owned(row_data),
+                    row_size,
+                    blob_data.map_or(Cow::Borrowed(self.blobs.as_slice()), Cow::Owned),
+                ),
+                None => (
+                    Cow::Borrowed(self.column_data.as_slice()),
+                    Cow::Borrowed(self.row_data.as_slice()),
+                    row_size,
+                    Cow::Borrowed(self.blobs.as_slice()),
+                ),
+            };
+        let reordered = match (self.string_order, self.table_name_placement) {
+            _ if self.original_layout.is_some() => None,
+            (
+                StringOrder::Insertion,
+                TableNamePlacement::AfterSentinel | TableNamePlacement::BeforeSentinel,
+            ) => None,
+            _ => self.reorder_strings(&column_data, &row_data, row_size),
+        };
+        Ok(match reordered {
+            Some(sections) => FinalSections {
+                blob_data,
+                ..sections
+            },
+            None => FinalSections {
+                column_data,
+                row_data,
+                string_data: Cow::Borrowed(self.string_data.as_slice()),
+                blob_data,
+                row_size,
+                table_name: self.table_name,
+            },
+        })
+    }
+
+    /// Works out where each of the final sections goes (see [`Writer::layout`])
+    fn final_layout(&self, sections: &FinalSections) -> Result<Layout> {
+        let mut layout = self.layout(
+            sections.column_data.len() as u64,
+            sections.row_data.len() as u64,
+            sections.string_data.len() as u64,
+            sections.blob_data.len() as u64,
+        )?;
+        layout.table_name = sections.table_name;
+        Ok(layout)
+    }
+
+    /// Writes every section of the table after the header
+    fn write_sections(
+        &self,
+        writer: &mut dyn Write,
+        layout: &Layout,
+        sections: &FinalSections,
+    ) -> Result<()> {
+        writer
+            .write_all(&sections.column_data)
+            .io("UTF column data")?;
+        writer.write_all(&sections.row_data).io("UTF row data")?;
+        writer
+            .write_all(&sections.string_data)
+            .io("UTF string data")?;
+        writer
+            .write_all(&self.blob_padding(layout))
+            .io("UTF string data")?;
+        writer.write_all(&sections.blob_data).io("UTF blobs")?;
+        writer
+            .write_all(&vec![self.pad_byte; layout.table_padding as usize])
+            .io("UTF blobs")?;
+        Ok(())
+    }
+
+    /// Works out where each section of the table goes
+    ///
+    /// Every offset is worked out in 64 bits, and then checked to fit in the
+    /// header.
+    fn layout(
+        &self,
+        columns_len: u64,
+        rows_len: u64,
+        strings_len: u64,
+        blobs_len: u64,
+    ) -> Result<Layout> {
+        if self.field_count > u16::MAX as u32 {
+            return Err(Error::TableTooLarge("field count"));
+        }
+        let row_offset = columns_len + 24;
+        let string_offset = row_offset + rows_len;
+        let strings_end = string_offset + strings_len;
+        // the original padding is part of the original data, so it's only
+        // added again if the data has grown
+        let original = self.original_layout.as_deref();
+        let original_padding = original.filter(|o| o.string_data.len() as u64 == strings_len);
+        let blob_padding = match self.blob_align as u64 {
+            _ if let Some(original) = original_padding => original.string_padding.len() as u64,
+            0 => 0,
+            align => align - (strings_end % align),
+        };
+        let blob_offset = strings_end + blob_padding;
+        let blobs_end = blob_offset + blobs_len;
+        let table_padding = match self.table_align as u64 {
+            _ if original.is_some_and(|o| o.blob_data.len() as u64 == blobs_len) => 0,
+            0 | 1 => 0,
+            align => (align - ((blobs_end + 8) % align)) % align,
+        };
+        let table_size = blobs_end + table_padding;
+        if table_size > u32::MAX as u64 {
+            return Err(Error::TableTooLarge("table"));
+        }
+        // nothing can be larger than the table, so the rest fit too
+        Ok(Layout {
+            row_offset: row_offset as u32,
+            string_offset: string_offset as u32,
+            blob_offset: blob_offset as u32,
+            table_size: table_size as u32,
+            table_name: self.table_name,
+            blob_padding: blob_padding as u32,
+            table_padding: table_padding as u32,
+            original_padding: original_padding.is_some(),
+        })
+    }
+
+    /// Returns the padding between the string and blob data sections
+    fn blob_padding(&self, layout: &Layout) -> Vec<u8> {
+        match &self.original_layout {
+            Some(original) if layout.original_padding => original.string_padding.clone(),
+            _ => vec![self.pad_byte; layout.blob_padding as usize],
+        }
+    }
+
+    /// Writes the 8-byte prefix and 24-byte header of the table
+    fn write_header(
+        &self,
+        writer: &mut dyn Write,
+        layout: &Layout,
+        row_size: u16,
+        row_count: u32,
+    ) -> Result<()> {
         writer.write_all(b"@UTF").io("@UTF header")?;
         writer
-            .write_all(&table_size.to_be_bytes())
+            .write_all(&self.endianness.u32_to(layout.table_size))
             .io("@UTF header")?;
         writer
-            .write_all(&row_offset.to_be_bytes())
+            .write_all(&self.endianness.u32_to(layout.row_offset))
             .io("@UTF header")?;
         writer
-            .write_all(&string_offset.to_be_bytes())
+            .write_all(&self.endianness.u32_to(layout.string_offset))
             .io("@UTF header")?;
         writer
-            .write_all(&blob_offset.to_be_bytes())
+            .write_all(&self.endianness.u32_to(layout.blob_offset))
             .io("@UTF header")?;
         writer
-            .write_all(&table_name.to_be_bytes())
+            .write_all(&self.endianness.u32_to(layout.table_name))
             .io("@UTF header")?;
         writer
-            .write_all(&self.field_count.to_be_bytes())
+            .write_all(&self.endianness.u16_to(self.field_count as u16))
             .io("@UTF header")?;
         writer
-            .write_all(&row_size.to_be_bytes())
+            .write_all(&self.endianness.u16_to(row_size))
             .io("@UTF header")?;
         writer
-            .write_all(&row_count.to_be_bytes())
+            .write_all(&self.endianness.u32_to(row_count))
             .io("@UTF header")?;
-        writer.write_all(&self.column_data).io("UTF column data")?;
-        writer.write_all(&self.row_data).io("UTF row data")?;
-        writer.write_all(&self.string_data).io("UTF string data")?;
-        writer
-            .write_all(&zeroes[0..(blob_offset_remainder as usize)])
-            .io("UTF string data")?;
-        writer.write_all(&self.blobs).io("UTF blobs")?;
         Ok(())
     }
 
+    /// Lays out the string data section again, according to the string
+    /// order and table name placement, and returns the updated sections
+    ///
+    /// With [`StringOrder::NamesFirst`], the table and column names are moved
+    /// to the start of the section. The table name is then moved if it's
+    /// placed after the column names, or at a given position.
+    ///
+    /// If there's a column whose size isn't known (from [`Writer::write_any`])
+    /// or the row size doesn't match the columns, nothing is moved.
+    fn reorder_strings(
+        &self,
+        column_data: &[u8],
+        row_data: &[u8],
+        row_size: u16,
+    ) -> Option<FinalSections<'static>> {
+        let endianness = self.endianness;
+        let read_u32 = |data: &[u8], position: usize| {
+            endianness.u32_from(data[position..position + 4].try_into().unwrap())
+        };
+        // find every string reference in the column and row data
+        let mut names = Vec::new();
+        let mut constant_strings = Vec::new();
+        let mut row_strings = Vec::new();
+        let mut position = 0;
+        let mut row_position = 0;
+        for _ in 0..self.field_count {
+            let flag = column_data[position];
+            names.push(position + 1);
+            position += 5;
+            let kind = ValueKind::from_flag(flag & 0x0f)?;
+            match flag & 0xf0 {
+                0x30 => {
+                    if kind == ValueKind::STR {
+                        constant_strings.push(position);
+                    }
+                    position += kind.size() as usize;
+                }
+                0x50 => {
+                    if kind == ValueKind::STR {
+                        row_strings.push(row_position);
+                    }
+                    row_position += kind.size() as usize;
+                }
+                _ => {}
+            }
+        }
+        if row_position != row_size as usize {
+            return None;
+        }
+        // lay out the strings again
+        let mut old_strings = HashMap::new();
+        let mut start = 0;
+        while start < self.string_data.len() {
+            let end = start + self.string_data[start..].iter().position(|b| *b == 0)?;
+            old_strings.insert(start as u32, &self.string_data[start..=end]);
+            start = end + 1;
+        }
+        let name_offsets: Vec<u32> = names
+            .iter()
+            .map(|position| read_u32(column_data, *position))
+            .collect();
+        let mut order: Vec<u32> = Vec::with_capacity(old_strings.len() + names.len() + 2);
+        if self.string_order == StringOrder::NamesFirst {
+            order.extend(self.null_sentinel);
+            order.push(self.table_name);
+            order.sort_unstable();
+            order.extend(&name_offsets);
+        }
+        order.extend(
+            (0..self.string_data.len() as u32).filter(|offset| old_strings.contains_key(offset)),
+        );
+        let mut seen = HashSet::with_capacity(order.len());
+        order.retain(|offset| old_strings.contains_key(offset) && seen.insert(*offset));
+        let moved = match self.table_name_placement {
+            TableNamePlacement::AfterColumnNames | TableNamePlacement::Position(_) => {
+                order.iter().position(|offset| *offset == self.table_name)
+            }
+            _ => None,
+        };
+        if let Some(current) = moved {
+            order.remove(current);
+            let index = match self.table_name_placement {
+                TableNamePlacement::Position(index) => index,
+                _ => order
+                    .iter()
+                    .rposition(|offset| name_offsets.contains(offset))
+                    .map_or(current, |last| last + 1),
+            };
+            order.insert(index.min(order.len()), self.table_name);
+        }
+        let mut new_offsets = HashMap::new();
+        let mut string_data = Vec::with_capacity(self.string_data.len());
+        for offset in order {
+            new_offsets.insert(offset, string_data.len() as u32);
+            string_data.extend_from_slice(old_strings[&offset]);
+        }
+        // point every reference at its string's new position
+        let patch = |data: &mut [u8], position: usize| {
+            if let Some(offset) = new_offsets.get(&read_u32(data, position)) {
+                data[position..position + 4].copy_from_slice(&endianness.u32_to(*offset));
+            }
+        };
+        let mut column_data = column_data.to_vec();
+        for position in names.into_iter().chain(constant_strings) {
+            patch(&mut column_data, position);
+        }
+        let mut row_data = row_data.to_vec();
+        if row_size > 0 {
+            for row in row_data.chunks_exact_mut(row_size as usize) {
+                for position in &row_strings {
+                    patch(row, *position);
+                }
+            }
+        }
+        Some(FinalSections {
+            column_data: Cow::Owned(column_data),
+            row_data: Cow::Owned(row_data),
+            string_data: Cow::Owned(string_data),
+            // filled in by `Writer::final_sections`
+            blob_data: Cow::Borrowed(&[]),
+            row_size,
+            table_name: new_offsets[&self.table_name],
+        })
+    }
+
     fn push_constant_column_private<T: Value>(
         &mut self,
-        name: &'a str,
+        name: Cow<'a, str>,
         value: Option<&'a T>,
+        hook: Option<&CellHook>,
     ) -> Result<()> {
         let flag = if value.is_some() { 0x30 } else { 0x10 };
-        self.write_primitive::<u8>(false, Cow::Owned(flag | (T::Primitive::TYPE_FLAG as u8)));
-        self.write_primitive(false, Cow::Borrowed(name));
+        self.push_column_header(flag | const { primitive_type_flag::<T::Primitive>() }, name);
         if let Some(value) = value {
-            self.write_value(false, value)?;
+            self.write_value_private(false, value, hook)?;
         }
-        self.field_count += 1;
         Ok(())
     }
 
@@ -167,14 +1253,77 @@ impl<'a> Writer<'a> {
     let file_count = 5000u64;
     let comment = "This is my comment".to_owned();
     {
-        let writer = Writer::new("ImportantTable");
-        writer.push_constant_column("FileCount", file_count)?;
+        let mut writer = Writer::new("ImportantTable");
+        writer.push_constant_column("FileCount", &file_count)?;
         writer.push_constant_column::<String>("Comment", &comment)?;
     }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
-    pub fn push_constant_column<T: Value>(&mut self, name: &'a str, value: &'a T) -> Result<()> {
-        self.push_constant_column_private(name, Some(value))
+    pub fn push_constant_column<T: Value>(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: &'a T,
+    ) -> Result<()> {
+        self.push_constant_column_private(name.into(), Some(value), None)
+    }
+
+    /**
+    Adds a new constant column with the given value, which is moved into the
+    writer
+
+    Unlike [`Writer::push_constant_column`], the value doesn't need to outlive
+    the writer, so it can be a temporary (like a formatted string).
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # let version = 3;
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_constant_column_owned("Comment", format!("Version {version}"))?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn push_constant_column_owned<T: Value>(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: T,
+    ) -> Result<()>
+    where
+        T::Primitive: 'a,
+    {
+        self.push_column_header(
+            0x30 | const { primitive_type_flag::<T::Primitive>() },
+            name.into(),
+        );
+        self.write_value_owned(false, value)
+    }
+
+    /**
+    Adds a new constant column with the given value, passing the encoded bytes
+    of the value through `hook` before they are stored
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    let file_count = 0u32;
+    let mut writer = Writer::new("ImportantTable");
+    // this game expects 0xFFFFFFFF where 0 is meant
+    writer.push_constant_column_with_hook("FileCount", &file_count, &|bytes| {
+        if bytes == [0; 4] {
+            bytes.fill(0xff);
+        }
+    })?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn push_constant_column_with_hook<T: Value>(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: &'a T,
+        hook: &CellHook,
+    ) -> Result<()> {
+        self.push_constant_column_private(name.into(), Some(value), Some(hook))
     }
 
     /**
@@ -184,23 +1333,39 @@ impl<'a> Writer<'a> {
     ```no_run
     # use criware_utf_core::Writer;
     let crc32: Option<u32> = Some(0);
-    let writer = Writer::new("ImportantTable");
+    let mut writer = Writer::new("ImportantTable");
     writer.push_constant_column_opt("Crc", &crc32)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn push_constant_column_opt<T: Value>(
         &mut self,
-        name: &'a str,
+        name: impl Into<Cow<'a, str>>,
+        value: &'a Option<T>,
+    ) -> Result<()> {
+        self.push_constant_column_private::<T>(name.into(), value.into(), None)
+    }
+
+    /**
+    Adds a new optional constant column with the given value, passing the
+    encoded bytes of the value (if there is one) through `hook` before they
+    are stored
+
+    See [`Writer::push_constant_column_with_hook`] and
+    [`Writer::push_constant_column_opt`].
+     */
+    pub fn push_constant_column_opt_with_hook<T: Value>(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
         value: &'a Option<T>,
+        hook: &CellHook,
     ) -> Result<()> {
-        self.push_constant_column_private::<T>(name, value.into())
+        self.push_constant_column_private::<T>(name.into(), value.into(), Some(hook))
     }
 
-    fn push_rowed_column_private(&mut self, name: &'a str, included: bool, kind: ValueKind) {
+    fn push_rowed_column_private(&mut self, name: Cow<'a, str>, included: bool, type_flag: u8) {
         let storage_flag = if included { 0x50 } else { 0x10 };
-        self.write_primitive::<u8>(false, Cow::Owned(storage_flag | (kind as u8)));
-        self.write_primitive::<str>(false, Cow::Borrowed(name));
-        self.field_count += 1;
+        self.push_column_header(storage_flag | type_flag, name);
     }
 
     /**
@@ -209,12 +1374,16 @@ impl<'a> Writer<'a> {
     # Example
     ```no_run
     # use criware_utf_core::Writer;
-    let writer = Writer::new("ImportantTable");
+    let mut writer = Writer::new("ImportantTable");
     writer.push_rowed_column::<u64>("ID");
     ```
      */
-    pub fn push_rowed_column<T: Value>(&mut self, name: &'a str) {
-        self.push_rowed_column_private(name, true, T::Primitive::TYPE_FLAG)
+    pub fn push_rowed_column<T: Value>(&mut self, name: impl Into<Cow<'a, str>>) {
+        self.push_rowed_column_private(
+            name.into(),
+            true,
+            const { primitive_type_flag::<T::Primitive>() },
+        )
     }
 
     /**
@@ -222,61 +1391,389 @@ impl<'a> Writer<'a> {
 
     # Example
     ```no_run
-    # use criware_utf_core::{Writer, WriteContext};
-    let context = WriteContext::new();
+    # use criware_utf_core::{RoundTripContext, Writer};
+    let context = RoundTripContext::new();
     // ...
-    let writer = Writer::new("ImportantTable");
+    let mut writer = Writer::new("ImportantTable");
     writer.push_rowed_column_opt::<u64>("ID", context.is_included("ID"));
     ```
      */
-    pub fn push_rowed_column_opt<T: Value>(&mut self, name: &'a str, included: bool) {
-        self.push_rowed_column_private(name, included, T::Primitive::TYPE_FLAG)
+    pub fn push_rowed_column_opt<T: Value>(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        included: bool,
+    ) {
+        self.push_rowed_column_private(
+            name.into(),
+            included,
+            const { primitive_type_flag::<T::Primitive>() },
+        )
     }
 
-    fn write_primitive<T: Primitive + ?Sized>(&mut self, rowed: bool, value: Cow<'a, T>) {
-        let destination = if rowed {
-            &mut self.row_data
-        } else {
-            &mut self.column_data
-        };
-        destination.extend_from_slice(
-            T::write(
-                value,
-                &mut self.strings,
-                &mut self.string_data,
-                &mut self.blobs,
-            )
-            .as_ref(),
+    /**
+    Adds a new column that doesn't store any data
+
+    Zero columns have a type, but no value in the column or row data. This is
+    how optional columns without a value are stored, and some games expect
+    placeholder columns like this to exist regardless.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_rowed_column::<u64>("ID");
+    writer.push_zero_column::<u32>("Reserved");
+    ```
+     */
+    pub fn push_zero_column<T: Value>(&mut self, name: impl Into<Cow<'a, str>>) {
+        self.push_column_header(
+            0x10 | const { primitive_type_flag::<T::Primitive>() },
+            name.into(),
         );
     }
 
+    /**
+    Adds a new column that doesn't store any data, with the given type flag
+
+    See [`Writer::push_zero_column`] and [`Writer::push_rowed_column_any`].
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{ValueKind, Writer};
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_zero_column_any("Reserved", ValueKind::U32 as u8);
+    ```
+     */
+    pub fn push_zero_column_any(&mut self, name: impl Into<Cow<'a, str>>, type_flag: u8) {
+        self.push_column_header(0x10 | (type_flag & 0x0f), name.into());
+    }
+
+    /**
+    Adds a new constant column with the given value, whatever kind it is
+
+    Values with unknown type flags are stored as-is, so tables read with
+    [`ReaderOptions::preserve_unknown_types`](crate::ReaderOptions::preserve_unknown_types)
+    can be written back untouched.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{AnyValue, Writer};
+    let value = AnyValue::Unknown { flag: 0xc, bytes: vec![0; 16] };
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_constant_column_any("Extra", &value)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn push_constant_column_any(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: &'a AnyValue,
+    ) -> Result<()> {
+        self.push_column_header(0x30 | value.type_flag(), name.into());
+        self.write_any(false, value)
+    }
+
+    /**
+    Adds a new rowed column with the given type flag
+
+    The flag doesn't need to be one this crate understands (see
+    [`Writer::write_any`]).
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{ValueKind, Writer};
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_rowed_column_any("ID", ValueKind::U64 as u8);
+    writer.push_rowed_column_any("Extra", 0xc);
+    ```
+     */
+    pub fn push_rowed_column_any(&mut self, name: impl Into<Cow<'a, str>>, type_flag: u8) {
+        self.push_column_header(0x50 | (type_flag & 0x0f), name.into());
+    }
+
+    /**
+    Writes a value directly into the column or row buffer, whatever kind it
+    is
+
+    The bytes of an [`AnyValue::Unknown`] are stored as-is.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::{AnyValue, Writer};
+    # let rows: Vec<Vec<AnyValue>> = Vec::new();
+    # let mut writer = Writer::new("ImportantTable");
+    for row in &rows {
+        for value in row {
+            writer.write_any(true, value)?;
+        }
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_any(&mut self, rowed: bool, value: &'a AnyValue) -> Result<()> {
+        match value {
+            AnyValue::U8(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::I8(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::U16(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::I16(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::U32(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::I32(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::U64(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::I64(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::F32(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::F64(value) => self.write_primitive(rowed, Cow::Borrowed(value), None),
+            AnyValue::String(value) => {
+                self.write_primitive::<str>(rowed, Cow::Borrowed(value), None)
+            }
+            AnyValue::Blob(value) => {
+                self.write_primitive::<[u8]>(rowed, Cow::Borrowed(value), None)
+            }
+            AnyValue::Unknown { flag, bytes } => {
+                if rowed {
+                    self.check_cell(*flag)?;
+                }
+                self.push_value_bytes(rowed, bytes)?;
+                self.record_cell(rowed, *flag);
+                Ok(())
+            }
+        }
+    }
+
+    fn write_primitive<T: Primitive + ?Sized>(
+        &mut self,
+        rowed: bool,
+        value: Cow<'a, T>,
+        hook: Option<&CellHook>,
+    ) -> Result<()> {
+        if rowed {
+            self.check_cell(const { primitive_type_flag::<T>() })?;
+        }
+        let mut buffer = T::write(value, self)?;
+        if let Some(hook) = hook {
+            hook(buffer.as_mut());
+        }
+        self.push_value_bytes(rowed, buffer.as_ref())?;
+        self.record_cell(rowed, const { primitive_type_flag::<T>() });
+        Ok(())
+    }
+
     /**
     Writes a value directly into the column or row buffer
 
     # Example
     ```no_run
-    # use criware_utf_core::{Writer, WriteContext};
-    # let rows = Vec::new();
-    # let writer = Writer::new("ImportantTable");
-    for row in rows {
+    # use criware_utf_core::Writer;
+    # struct Row { id: u64, name: String }
+    # let rows: Vec<Row> = Vec::new();
+    # let mut writer = Writer::new("ImportantTable");
+    for row in &rows {
         writer.write_value::<u64>(true, &row.id)?;
         writer.write_value(true, &row.name)?;
     }
+    # Ok::<(), Box<dyn std::error::Error>>(())
     ```
      */
     pub fn write_value<T: Value>(&mut self, rowed: bool, value: &'a T) -> Result<()> {
-        match T::to_primitive(value) {
+        self.write_value_private(rowed, value, None)
+    }
+
+    /**
+    Writes a value directly into the column or row buffer, passing its encoded
+    bytes through `hook` before they are stored
+
+    This allows game-specific quirks (sentinel values, garbage padding) to be
+    recreated without writing a custom [`Value`] type.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # struct Row { id: u16 }
+    # let rows: Vec<Row> = Vec::new();
+    # let mut writer = Writer::new("ImportantTable");
+    for row in &rows {
+        writer.write_value_with_hook(true, &row.id, &|bytes| bytes.reverse())?;
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_value_with_hook<T: Value>(
+        &mut self,
+        rowed: bool,
+        value: &'a T,
+        hook: &CellHook,
+    ) -> Result<()> {
+        self.write_value_private(rowed, value, Some(hook))
+    }
+
+    /**
+    Writes a value directly into the column or row buffer, moving it into the
+    writer
+
+    Unlike [`Writer::write_value`], the value doesn't need to outlive the
+    writer, so rows can be built from temporaries (like computed blobs).
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # let ids: Vec<u32> = Vec::new();
+    let mut writer = Writer::new("ImportantTable");
+    writer.push_rowed_column::<String>("Name");
+    for id in &ids {
+        writer.write_value_owned(true, format!("File{id:04}"))?;
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_value_owned<T: Value>(&mut self, rowed: bool, value: T) -> Result<()>
+    where
+        T::Primitive: 'a,
+    {
+        match T::to_primitive(&value) {
             Ok(prim) => {
-                self.write_primitive(rowed, prim);
-                Ok(())
+                self.check_encoded::<T>(rowed, &prim)?;
+                let prim: Cow<'a, T::Primitive> = Cow::Owned(prim.into_owned());
+                self.write_primitive(rowed, prim, None)
             }
-            Err(error) => {
-                return Err(Error::ValueConversion(
-                    type_name::<T>(),
-                    type_name::<T::Primitive>(),
-                    error,
-                ));
+            Err(error) => Err(self.conversion_error::<T>(rowed, error)),
+        }
+    }
+
+    /**
+    Starts a new row, whose values are checked against the rowed columns
+
+    Until [`Writer::end_row`] is called, every value written to the row
+    buffer must have the same type as the next rowed column, or an error
+    naming the row is returned. This catches mistakes right away, instead of
+    ending up with misaligned rows that [`Writer::end`] can only reject as a
+    whole.
+
+    Rows written without this aren't checked.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # struct Row { id: u64, name: String }
+    # let rows: Vec<Row> = Vec::new();
+    # let mut writer = Writer::new("ImportantTable");
+    writer.push_rowed_column::<u64>("ID");
+    writer.push_rowed_column::<String>("Name");
+    for row in &rows {
+        writer.begin_row()?;
+        writer.write_value(true, &row.id)?;
+        writer.write_value(true, &row.name)?;
+        writer.end_row()?;
+    }
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    A value that fails to write isn't counted as part of the row, so another
+    one can be written in its place:
+    ```
+    # use criware_utf_core::Writer;
+    let mut writer = Writer::new("Files");
+    writer.push_rowed_column::<u32>("ID");
+    writer.push_rowed_column::<String>("Name");
+    writer.begin_row()?;
+    writer.write_value_owned(true, 1u32)?;
+    assert!(writer.write_value_owned(true, "bad\0name".to_owned()).is_err());
+    writer.write_value_owned(true, "file.bin".to_owned())?;
+    writer.end_row()?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn begin_row(&mut self) -> Result<()> {
+        self.check_row_ended()?;
+        self.current_row = Some(Vec::with_capacity(self.rowed_flags.len()));
+        Ok(())
+    }
+
+    /// Ends the row started with [`Writer::begin_row`], checking that a value
+    /// was written for every rowed column
+    ///
+    pub fn end_row(&mut self) -> Result<()> {
+        let Some(row) = self.current_row.take() else {
+            return Err(Error::RowMismatch(
+                self.rows_ended,
+                "row was never started".to_owned(),
+            ));
+        };
+        if row.len() != self.rowed_flags.len() {
+            return Err(Error::RowMismatch(
+                self.rows_ended,
+                format!(
+                    "{} values written, but there are {} rowed columns",
+                    row.len(),
+                    self.rowed_flags.len()
+                ),
+            ));
+        }
+        self.rows_ended += 1;
+        Ok(())
+    }
+
+    /// Checks that there isn't a row that was started but not ended
+    fn check_row_ended(&self) -> Result<()> {
+        match self.current_row {
+            Some(_) => Err(Error::RowMismatch(
+                self.rows_ended,
+                "row was never ended".to_owned(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks a value being written to the current row (if there is one)
+    /// against the next rowed column
+    ///
+    /// The value is only counted as part of the row once it's been written, so
+    /// a value that fails to write doesn't move the row on.
+    fn check_cell(&self, type_flag: u8) -> Result<()> {
+        let Some(row) = &self.current_row else {
+            return Ok(());
+        };
+        let index = row.len();
+        match self.rowed_flags.get(index) {
+            Some(expected) if *expected == type_flag => Ok(()),
+            Some(expected) => Err(Error::RowMismatch(
+                self.rows_ended,
+                format!(
+                    "value {index} has type flag 0x{type_flag:02x} (expected 0x{expected:02x})"
+                ),
+            )),
+            None => Err(Error::RowMismatch(
+                self.rows_ended,
+                format!("more than {index} values written"),
+            )),
+        }
+    }
+
+    /// Counts a value that was written to the current row (if there is one),
+    /// once it's been checked with [`Writer::check_cell`]
+    fn record_cell(&mut self, rowed: bool, type_flag: u8) {
+        if rowed && let Some(row) = &mut self.current_row {
+            row.push(type_flag);
+        }
+    }
+
+    fn write_value_private<T: Value>(
+        &mut self,
+        rowed: bool,
+        value: &'a T,
+        hook: Option<&CellHook>,
+    ) -> Result<()> {
+        match T::to_primitive(value) {
+            Ok(prim) => {
+                self.check_encoded::<T>(rowed, &prim)?;
+                self.write_primitive(rowed, prim, hook)
             }
+            Err(error) => Err(self.conversion_error::<T>(rowed, error)),
         }
     }
+
+    /// Checks that a value fits in the table once it's encoded (see
+    /// [`FixedStr`](crate::FixedStr))
+    fn check_encoded<T: Value>(&self, rowed: bool, prim: &T::Primitive) -> Result<()> {
+        prim.check_encoded(self)
+            .map_err(|error| self.conversion_error::<T>(rowed, Box::new(error)))
+    }
 }
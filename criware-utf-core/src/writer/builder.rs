@@ -0,0 +1,189 @@
+use std::{borrow::Cow, collections::HashMap, io::Write};
+
+use super::{TableLayout, Writer, WriterOptions};
+use crate::{
+    Encoding, Endianness, Error, Result, Value, utf_size_of, value::sealed::primitive_type_flag,
+};
+
+type WriteFn<'a> = Box<dyn Fn(&mut Writer<'a>) -> Result<()> + 'a>;
+
+enum BuilderColumn<'a> {
+    Constant(WriteFn<'a>),
+    Zero(&'a str, u8),
+    /// The name, type flag, and size of a rowed column
+    Rowed(&'a str, u8, u32),
+}
+
+/**
+A [`Writer`] that accepts columns and values in any order
+
+[`Writer`] requires every column to be pushed before any rows, and every
+value of a row to be written in column order. A `WriterBuilder` instead
+records everything it's given, and lays out the table when
+[`WriterBuilder::end`] is called. The row size and row count are worked out
+automatically.
+
+Columns are stored in the order they're added. Values of rowed columns can be
+set before or after their column is added, as long as it's added before the
+table is written.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::WriterBuilder;
+let version = 3u32;
+let names = ["a.bin".to_owned(), "b.bin".to_owned()];
+let sizes = [1024u64, 2048u64];
+let mut builder = WriterBuilder::new("Files");
+for row in 0..2 {
+    builder.value(row, "FileSize", &sizes[row as usize]);
+    builder.value(row, "FileName", &names[row as usize]);
+}
+builder
+    .constant_column("Version", &version)
+    .rowed_column::<String>("FileName")
+    .rowed_column::<u64>("FileSize");
+builder.end(&mut File::create("files.bin")?)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub struct WriterBuilder<'a> {
+    table_name: &'a str,
+    options: WriterOptions,
+    columns: Vec<BuilderColumn<'a>>,
+    values: HashMap<(&'a str, u32), (u8, WriteFn<'a>)>,
+    row_count: u32,
+}
+
+impl<'a> WriterBuilder<'a> {
+    /// Creates a new `WriterBuilder`
+    ///
+    pub fn new(table_name: &'a str) -> Self {
+        Self::with_encoding(table_name, Endianness::Big, Encoding::Utf8)
+    }
+
+    /// Creates a new `WriterBuilder` that stores numbers with the given byte
+    /// order, and strings with the given encoding
+    ///
+    /// See [`Writer::with_encoding`].
+    ///
+    pub fn with_encoding(table_name: &'a str, endianness: Endianness, encoding: Encoding) -> Self {
+        Self::with_options(
+            table_name,
+            WriterOptions {
+                endianness,
+                encoding,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Creates a new `WriterBuilder` that lays out the table with the given
+    /// options
+    ///
+    /// See [`Writer::with_options`].
+    ///
+    pub fn with_options(table_name: &'a str, options: WriterOptions) -> Self {
+        WriterBuilder {
+            table_name,
+            options,
+            columns: Vec::new(),
+            values: HashMap::new(),
+            row_count: 0,
+        }
+    }
+
+    /// Adds a new constant column with the given value
+    ///
+    pub fn constant_column<T: Value>(&mut self, name: &'a str, value: &'a T) -> &mut Self {
+        self.columns
+            .push(BuilderColumn::Constant(Box::new(move |writer| {
+                writer.push_constant_column(name, value)
+            })));
+        self
+    }
+
+    /// Adds a new column that doesn't store any data (like an optional column
+    /// without a value)
+    ///
+    pub fn zero_column<T: Value>(&mut self, name: &'a str) -> &mut Self {
+        self.columns.push(BuilderColumn::Zero(
+            name,
+            const { primitive_type_flag::<T::Primitive>() },
+        ));
+        self
+    }
+
+    /// Adds a new rowed column
+    ///
+    pub fn rowed_column<T: Value>(&mut self, name: &'a str) -> &mut Self {
+        self.columns.push(BuilderColumn::Rowed(
+            name,
+            const { primitive_type_flag::<T::Primitive>() },
+            utf_size_of::<T>() as u32,
+        ));
+        self
+    }
+
+    /// Sets the value of a rowed column in the given row (starting from 0)
+    ///
+    /// Setting a value that was already set replaces it. Every row up to the
+    /// highest one given a value is written, so every rowed column needs a
+    /// value in each of them.
+    ///
+    pub fn value<T: Value>(&mut self, row: u32, column: &'a str, value: &'a T) -> &mut Self {
+        self.values.insert(
+            (column, row),
+            (
+                const { primitive_type_flag::<T::Primitive>() },
+                Box::new(move |writer| writer.write_value(true, value)),
+            ),
+        );
+        self.row_count = self.row_count.max(row + 1);
+        self
+    }
+
+    /**
+    Lays out the table, and writes it to the given stream
+
+    This fails if a value was set for a column that wasn't added (or isn't
+    rowed), a value is missing, or a value is of a different kind than its
+    column. Otherwise, where each section went is returned (see
+    [`TableLayout`]).
+     */
+    pub fn end(self, writer: &mut dyn Write) -> Result<TableLayout> {
+        let mut table_writer = Writer::with_options(self.table_name, self.options);
+        let mut rowed = Vec::new();
+        for column in &self.columns {
+            match column {
+                BuilderColumn::Constant(write) => write(&mut table_writer)?,
+                BuilderColumn::Zero(name, flag) => {
+                    table_writer.push_rowed_column_private(Cow::Borrowed(name), false, *flag)
+                }
+                BuilderColumn::Rowed(name, flag, size) => {
+                    table_writer.push_rowed_column_private(Cow::Borrowed(name), true, *flag);
+                    rowed.push((*name, *flag, *size));
+                }
+            }
+        }
+        for (name, _) in self.values.keys() {
+            if !rowed.iter().any(|(column, _, _)| column == name) {
+                return Err(Error::ColumnNotFound(name.to_string()));
+            }
+        }
+        for row in 0..self.row_count {
+            for (name, flag, _) in &rowed {
+                match self.values.get(&(*name, row)) {
+                    Some((value_flag, _)) if value_flag != flag => {
+                        return Err(Error::WrongColumnType(*value_flag, *flag));
+                    }
+                    Some((_, write)) => write(&mut table_writer)?,
+                    None => return Err(Error::MissingValue(name.to_string(), row)),
+                }
+            }
+        }
+        let row_size = rowed.iter().map(|(_, _, size)| size).sum::<u32>();
+        let row_size = u16::try_from(row_size).map_err(|_| Error::TableTooLarge("row size"))?;
+        table_writer.end(writer, row_size, self.row_count)
+    }
+}
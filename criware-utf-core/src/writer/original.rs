@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+/**
+The layout of a table that was read, used to write it back exactly as it was
+
+This is created by [`Reader::round_trip_options`](crate::Reader::round_trip_options),
+and used through [`WriterOptions::original_layout`](crate::WriterOptions::original_layout).
+
+It holds the original string and blob data sections (including any padding),
+and every string and blob reference in the order they're stored. When the
+table is written, the `n`-th string or blob written reuses the offset of the
+`n`-th original reference if their contents match, or the offset of any
+original copy of it otherwise. Only strings and blobs that weren't in the
+original table are added, after the original data.
+
+As long as the same values are written in the same order, the table is
+written byte-for-byte as it was read.
+ */
+#[derive(Debug, PartialEq, Eq)]
+pub struct OriginalLayout {
+    /// The string data section, up to the end of the last string
+    pub(crate) string_data: Vec<u8>,
+    /// The rest of the string data section (like padding)
+    pub(crate) string_padding: Vec<u8>,
+    /// Every string in the section, keyed by its offset
+    pub(crate) strings: HashMap<u32, String>,
+    /// The offset of every string reference (starting with the table name)
+    pub(crate) string_refs: Vec<u32>,
+    /// The entire blob data section
+    pub(crate) blob_data: Vec<u8>,
+    /// The offset and length of every blob reference
+    pub(crate) blob_refs: Vec<(u32, u32)>,
+}
+
+/// Hashes the contents of a blob, so it can be found without storing it twice
+fn blob_hash(blob: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    blob.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl OriginalLayout {
+    /// Returns the offset of the first copy of each string
+    pub(super) fn first_strings(&self) -> HashMap<String, u32> {
+        let mut offsets: Vec<&u32> = self.strings.keys().collect();
+        offsets.sort_unstable();
+        let mut first = HashMap::with_capacity(offsets.len());
+        for offset in offsets {
+            first.entry(self.strings[offset].clone()).or_insert(*offset);
+        }
+        first
+    }
+
+    /// Returns the offset of the first copy of each blob, keyed by its hash
+    pub(super) fn first_blobs(&self) -> HashMap<u64, Vec<(u32, u32)>> {
+        let mut first: HashMap<u64, Vec<(u32, u32)>> = HashMap::new();
+        for (offset, length) in &self.blob_refs {
+            if let Some(blob) = self.blob(*offset, *length) {
+                let copies = first.entry(blob_hash(blob)).or_default();
+                if !copies
+                    .iter()
+                    .any(|copy| self.blob(copy.0, copy.1) == Some(blob))
+                {
+                    copies.push((*offset, *length));
+                }
+            }
+        }
+        first
+    }
+
+    /// Returns the offset of the `index`-th string reference, if it refers to
+    /// the given string
+    pub(super) fn string_ref(&self, index: usize, value: &str) -> Option<u32> {
+        let offset = *self.string_refs.get(index)?;
+        (self.strings.get(&offset)? == value).then_some(offset)
+    }
+
+    /// Returns the offset of the `index`-th string reference, if it refers to
+    /// the given bytes (for strings that can't be decoded)
+    pub(super) fn raw_string_ref(&self, index: usize, value: &[u8]) -> Option<u32> {
+        let offset = *self.string_refs.get(index)?;
+        let start = offset as usize;
+        let string = self.string_data.get(start..=start + value.len())?;
+        (string[..value.len()] == *value && string[value.len()] == 0).then_some(offset)
+    }
+
+    /// Returns the offset of the `index`-th blob reference, if it refers to
+    /// the given blob
+    pub(super) fn blob_ref(&self, index: usize, value: &[u8]) -> Option<u32> {
+        let (offset, length) = *self.blob_refs.get(index)?;
+        (self.blob(offset, length)? == value).then_some(offset)
+    }
+
+    /// Returns the offset of any copy of the given blob
+    pub(super) fn find_blob(
+        &self,
+        first_blobs: &HashMap<u64, Vec<(u32, u32)>>,
+        value: &[u8],
+    ) -> Option<u32> {
+        first_blobs
+            .get(&blob_hash(value))?
+            .iter()
+            .find(|(offset, length)| self.blob(*offset, *length) == Some(value))
+            .map(|(offset, _)| *offset)
+    }
+
+    fn blob(&self, offset: u32, length: u32) -> Option<&[u8]> {
+        self.blob_data
+            .get((offset as usize)..(offset as usize + length as usize))
+    }
+}
@@ -0,0 +1,323 @@
+use std::borrow::Cow;
+
+use rayon::prelude::*;
+
+use super::{Writer, WriterOptions};
+use crate::{Encoding, Endianness, Error, Result, ValueKind};
+
+/// A string written by a chunk writer
+pub(super) enum DeferredString<'a> {
+    Text(Cow<'a, str>),
+    /// A string that may not be valid in the table's encoding
+    Raw(Vec<u8>),
+}
+
+/// The rows written by a chunk writer, before their strings and blobs are
+/// added to the table
+struct Chunk<'a> {
+    row_data: Vec<u8>,
+    /// Every string written, in order (the rows refer to their indices)
+    strings: Vec<DeferredString<'a>>,
+    blobs: Vec<u8>,
+}
+
+/// An error returned while writing a chunk, in a form that can be sent back
+/// to the calling thread
+///
+/// [`Error`] isn't `Send`, since [`Error::ValueConversion`] holds whatever
+/// error a value's conversion returned. Only that error's message is sent;
+/// everything else is rebuilt exactly as it was.
+struct ChunkError(Box<dyn FnOnce() -> Error + Send>);
+
+impl ChunkError {
+    fn into_error(self) -> Error {
+        (self.0)()
+    }
+}
+
+impl From<Error> for ChunkError {
+    fn from(error: Error) -> Self {
+        ChunkError(match error {
+            Error::BlobWrongSize => Box::new(|| Error::BlobWrongSize),
+            Error::ColumnNotFound(name) => Box::new(move || Error::ColumnNotFound(name)),
+            Error::DataNotFound => Box::new(|| Error::DataNotFound),
+            Error::DecryptionError => Box::new(|| Error::DecryptionError),
+            Error::EOF(message) => Box::new(move || Error::EOF(message)),
+            Error::InColumn(name, row, error) => {
+                let error = ChunkError::from(*error);
+                Box::new(move || Error::InColumn(name, row, Box::new(error.into_error())))
+            }
+            Error::InvalidBool(value) => Box::new(move || Error::InvalidBool(value)),
+            Error::InvalidColumnStorage(flag) => {
+                Box::new(move || Error::InvalidColumnStorage(flag))
+            }
+            Error::InvalidColumnType(flag) => Box::new(move || Error::InvalidColumnType(flag)),
+            Error::InvalidVersion(text) => Box::new(move || Error::InvalidVersion(text)),
+            Error::IOError(error) => Box::new(move || Error::IOError(error)),
+            Error::LimitExceeded(what, size, limit) => {
+                Box::new(move || Error::LimitExceeded(what, size, limit))
+            }
+            Error::MalformedHeader => Box::new(|| Error::MalformedHeader),
+            Error::MissingValue(name, row) => Box::new(move || Error::MissingValue(name, row)),
+            Error::NonUtf8Path(path) => Box::new(move || Error::NonUtf8Path(path)),
+            Error::PacketTruncated(size, left) => {
+                Box::new(move || Error::PacketTruncated(size, left))
+            }
+            Error::PacketVerificationFailed(message) => {
+                Box::new(move || Error::PacketVerificationFailed(message))
+            }
+            Error::RowMismatch(row, message) => Box::new(move || Error::RowMismatch(row, message)),
+            Error::RowOutOfRange(row, count) => Box::new(move || Error::RowOutOfRange(row, count)),
+            Error::StringContainsNul(value) => Box::new(move || Error::StringContainsNul(value)),
+            Error::StringMalformed(error) => Box::new(move || Error::StringMalformed(error)),
+            Error::StringTooLong(len, max) => Box::new(move || Error::StringTooLong(len, max)),
+            Error::StringUndecodable(encoding) => {
+                Box::new(move || Error::StringUndecodable(encoding))
+            }
+            Error::StringUnencodable(value, encoding) => {
+                Box::new(move || Error::StringUnencodable(value, encoding))
+            }
+            Error::OptionalColumnConflict(name) => {
+                Box::new(move || Error::OptionalColumnConflict(name))
+            }
+            Error::TableTooLarge(what) => Box::new(move || Error::TableTooLarge(what)),
+            Error::UnterminatedString(offset) => {
+                Box::new(move || Error::UnterminatedString(offset))
+            }
+            Error::UnknownFlagBits(name, bits) => {
+                Box::new(move || Error::UnknownFlagBits(name, bits))
+            }
+            Error::UnknownEnumValue(name, value) => {
+                Box::new(move || Error::UnknownEnumValue(name, value))
+            }
+            Error::UnloadedBlob => Box::new(|| Error::UnloadedBlob),
+            Error::UnsupportedWriterOptions => Box::new(|| Error::UnsupportedWriterOptions),
+            Error::ValueConversion(from, to, error) => {
+                let message = error.to_string();
+                Box::new(move || Error::ValueConversion(from, to, message.into()))
+            }
+            Error::WritesDiffer(table, offset) => {
+                Box::new(move || Error::WritesDiffer(table, offset))
+            }
+            Error::WrongColumnName(name, expected) => {
+                Box::new(move || Error::WrongColumnName(name, expected))
+            }
+            Error::WrongColumnType(flag, expected) => {
+                Box::new(move || Error::WrongColumnType(flag, expected))
+            }
+            Error::WrongColumnStorage(flag, expected) => {
+                Box::new(move || Error::WrongColumnStorage(flag, expected))
+            }
+            Error::WrongTableSchema => Box::new(|| Error::WrongTableSchema),
+        })
+    }
+}
+
+/// What every chunk writer needs to know about the table
+struct ChunkSettings {
+    endianness: Endianness,
+    encoding: Encoding,
+    rowed_flags: Vec<u8>,
+    rowed_names: Vec<String>,
+}
+
+impl ChunkSettings {
+    /// Writes a chunk of rows into a new writer, starting at the given row
+    fn write_chunk<'a, R, F>(
+        &self,
+        first_row: u32,
+        rows: &'a [R],
+        write_row: &F,
+    ) -> Result<Chunk<'a>>
+    where
+        F: Fn(&mut Writer<'a>, &'a R) -> Result<()>,
+    {
+        let mut writer = Writer::with_options(
+            "",
+            WriterOptions {
+                endianness: self.endianness,
+                encoding: self.encoding,
+                null_sentinel: None,
+                ..Default::default()
+            },
+        );
+        writer.deferred_strings = Some(Vec::new());
+        writer.rowed_flags = self.rowed_flags.clone();
+        writer.rowed_names = self.rowed_names.clone();
+        writer.rows_ended = first_row;
+        for row in rows {
+            writer.begin_row()?;
+            write_row(&mut writer, row)?;
+            writer.end_row()?;
+            if !writer.column_data.is_empty() {
+                return Err(Error::RowMismatch(
+                    writer.rows_ended - 1,
+                    "value written to the column buffer".to_owned(),
+                ));
+            }
+        }
+        Ok(Chunk {
+            row_data: writer.row_data,
+            strings: writer.deferred_strings.unwrap_or_default(),
+            blobs: writer.blobs,
+        })
+    }
+}
+
+impl<'a> Writer<'a> {
+    /**
+    Writes every row on multiple threads, by calling `write_row` for each one
+
+    The rows are split into chunks, and each chunk is written by its own
+    writer (the one passed to `write_row`) in parallel. Afterwards, the chunks
+    are added to this writer in order, along with their strings and blobs, so
+    the table comes out exactly as if the rows were written one by one.
+
+    Every row is checked like it is with [`Writer::begin_row`], and only
+    rowed values can be written. Hooks must not change string or blob values.
+    If there are no rowed columns, or a rowed column has a type flag this
+    crate doesn't understand, the rows are written one by one instead.
+
+    If any row fails, the error from the first chunk that failed is returned,
+    and only the chunks before it are added to this writer. `write_row` is
+    never called twice for the same row. Errors have to be sent between
+    threads, so the error inside an [`Error::ValueConversion`] only keeps its
+    message.
+
+    Requires the `rayon` feature.
+
+    # Example
+    ```no_run
+    # use criware_utf_core::Writer;
+    # struct Row { id: u64, name: String }
+    # let rows: Vec<Row> = Vec::new();
+    let mut writer = Writer::new("HugeTable");
+    writer.push_rowed_column::<u64>("ID");
+    writer.push_rowed_column::<String>("Name");
+    writer.write_rows_par(&rows, |writer, row| {
+        writer.write_value(true, &row.id)?;
+        writer.write_value(true, &row.name)
+    })?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+
+    When a row fails, the rows after it may still have been written, but
+    nothing is written twice:
+    ```
+    # use std::sync::atomic::{AtomicUsize, Ordering};
+    # use criware_utf_core::{Error, Writer};
+    let ids: Vec<u32> = (0..1000).collect();
+    let calls = AtomicUsize::new(0);
+    let mut writer = Writer::new("Files");
+    writer.push_rowed_column::<u32>("ID");
+    let result = writer.write_rows_par(&ids, |writer, id| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        if *id == 600 {
+            return Err(Error::ColumnNotFound("Size".to_owned()));
+        }
+        writer.write_value(true, id)
+    });
+    assert!(matches!(result, Err(Error::ColumnNotFound(_))));
+    assert!(calls.load(Ordering::Relaxed) <= ids.len());
+    ```
+
+    Tables without rowed columns can have rows too:
+    ```
+    # use criware_utf_core::Writer;
+    let mut writer = Writer::new("Settings");
+    writer.push_constant_column_owned("Version", 2u32)?;
+    writer.write_rows_par(&[(); 3], |_, _| Ok(()))?;
+    let mut data = Vec::new();
+    writer.end(&mut data, 0, 3)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn write_rows_par<R, F>(&mut self, rows: &'a [R], write_row: F) -> Result<()>
+    where
+        R: Sync,
+        F: Fn(&mut Writer<'a>, &'a R) -> Result<()> + Sync,
+    {
+        self.check_row_ended()?;
+        let kinds = self
+            .rowed_flags
+            .iter()
+            .map(|flag| ValueKind::from_flag(*flag))
+            .collect::<Option<Vec<_>>>()
+            .filter(|kinds| !kinds.is_empty());
+        let Some(kinds) = kinds else {
+            for row in rows {
+                self.begin_row()?;
+                write_row(self, row)?;
+                self.end_row()?;
+            }
+            return Ok(());
+        };
+        let row_size: usize = kinds.iter().map(|kind| kind.size() as usize).sum();
+        let settings = ChunkSettings {
+            endianness: self.endianness,
+            encoding: self.encoding,
+            rowed_flags: self.rowed_flags.clone(),
+            rowed_names: self.rowed_names.clone(),
+        };
+        let chunk_size = rows
+            .len()
+            .div_ceil(rayon::current_num_threads() * 4)
+            .max(256);
+        let chunks: Vec<std::result::Result<Chunk<'a>, ChunkError>> = rows
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let first_row = self.rows_ended + (index * chunk_size) as u32;
+                Ok(settings.write_chunk(first_row, chunk, &write_row)?)
+            })
+            .collect();
+        for chunk in chunks {
+            let mut chunk = chunk.map_err(ChunkError::into_error)?;
+            for row in chunk.row_data.chunks_exact_mut(row_size) {
+                self.merge_row(row, &kinds, &mut chunk.strings, &chunk.blobs)?;
+                self.push_value_bytes(true, row)?;
+                self.rows_ended += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the strings and blobs of a row written by a chunk writer to this
+    /// writer, and points the row at them
+    fn merge_row(
+        &mut self,
+        row: &mut [u8],
+        kinds: &[ValueKind],
+        strings: &mut [DeferredString<'a>],
+        blobs: &[u8],
+    ) -> Result<()> {
+        let endianness = self.endianness;
+        let row_index = self.rows_ended;
+        let changed = move || Error::RowMismatch(row_index, "value changed by a hook".into());
+        let mut offset = 0;
+        for kind in kinds {
+            let cell = &mut row[offset..offset + kind.size() as usize];
+            offset += kind.size() as usize;
+            match kind {
+                ValueKind::STR => {
+                    let index = endianness.u32_from(cell[0..4].try_into().unwrap()) as usize;
+                    let value = strings.get_mut(index).ok_or_else(changed)?;
+                    let position = match std::mem::replace(value, DeferredString::Raw(Vec::new())) {
+                        DeferredString::Text(value) => self.push_string(value)?,
+                        DeferredString::Raw(value) => self.push_raw_string(&value)?,
+                    };
+                    cell.copy_from_slice(&endianness.u32_to(position));
+                }
+                ValueKind::BLOB => {
+                    let start = endianness.u32_from(cell[0..4].try_into().unwrap()) as usize;
+                    let length = endianness.u32_from(cell[4..8].try_into().unwrap()) as usize;
+                    let value = blobs.get(start..start + length).ok_or_else(changed)?;
+                    let position = self.push_blob(value)?;
+                    cell[0..4].copy_from_slice(&endianness.u32_to(position));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
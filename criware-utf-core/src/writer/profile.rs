@@ -0,0 +1,109 @@
+use super::{EmptyStrings, StringOrder, TableNamePlacement, WriterOptions};
+use crate::ReaderOptions;
+
+/**
+Presets for common combinations of [`WriterOptions`]
+
+Each preset only sets how the table is laid out (string order, sentinel, and
+so on). Everything else is left as the default, and can be changed on the
+returned options.
+
+The presets aren't known to match any specific tool or game. Before relying
+on one for files a game has to accept, compare what it writes against a table
+from that game.
+
+# Example
+```no_run
+# use criware_utf_core::{Endianness, Writer, WriterOptions, WriterProfile};
+let writer = Writer::with_options(
+    "ImportantTable",
+    WriterOptions {
+        endianness: Endianness::Little,
+        blob_align: 32,
+        ..WriterProfile::NamesFirst.options()
+    },
+);
+```
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriterProfile {
+    /// The defaults of [`WriterOptions`]
+    #[default]
+    Default,
+    /// The table and column names come first in the string data section (see
+    /// [`StringOrder::NamesFirst`])
+    NamesFirst,
+    /// Like [`NamesFirst`](WriterProfile::NamesFirst), but empty strings refer
+    /// to the `<NULL>` sentinel (see [`EmptyStrings::Sentinel`])
+    ///
+    /// These tables have to be read with [`WriterProfile::reader_options`] to
+    /// get the empty strings back.
+    ///
+    /// # Example
+    /// ```
+    /// # use criware_utf::{Reader, Table, WriterProfile, utf_table};
+    /// #[utf_table]
+    /// struct Strings {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut table = Strings::new();
+    /// table.rows.push(StringsRow { name: String::new() });
+    /// let profile = WriterProfile::SentinelEmptyStrings;
+    /// let mut data = Vec::new();
+    /// table.write_with_options(&mut data, profile.options())?;
+    ///
+    /// let options = profile.reader_options();
+    /// let table = Strings::read_from_reader(Reader::from_bytes_with_options(&data, options)?)?;
+    /// assert_eq!(table.rows[0].name, "");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    SentinelEmptyStrings,
+    /// Strings are in the order they were first used, the table name comes
+    /// before the `<NULL>` sentinel, and every empty string gets its own
+    /// entry (see [`TableNamePlacement::BeforeSentinel`] and
+    /// [`EmptyStrings::Separate`])
+    NameBeforeSentinel,
+}
+
+impl WriterProfile {
+    /// Returns the options for this preset
+    ///
+    pub fn options(self) -> WriterOptions {
+        let defaults = WriterOptions::default();
+        match self {
+            WriterProfile::Default => defaults,
+            WriterProfile::NamesFirst => WriterOptions {
+                string_order: StringOrder::NamesFirst,
+                ..defaults
+            },
+            WriterProfile::SentinelEmptyStrings => WriterOptions {
+                empty_strings: EmptyStrings::Sentinel,
+                ..WriterProfile::NamesFirst.options()
+            },
+            WriterProfile::NameBeforeSentinel => WriterOptions {
+                table_name_placement: TableNamePlacement::BeforeSentinel,
+                empty_strings: EmptyStrings::Separate,
+                ..defaults
+            },
+        }
+    }
+
+    /// Returns the options to read tables written with this preset
+    ///
+    /// Most presets are read with the default options. Tables written with
+    /// [`SentinelEmptyStrings`](WriterProfile::SentinelEmptyStrings) have
+    /// their empty strings read back as empty strings instead of the sentinel
+    /// (see [`ReaderOptions::empty_sentinel`]).
+    ///
+    pub fn reader_options(self) -> ReaderOptions {
+        let defaults = ReaderOptions::default();
+        match self {
+            WriterProfile::SentinelEmptyStrings => ReaderOptions {
+                empty_sentinel: Some("<NULL>".to_owned()),
+                ..defaults
+            },
+            _ => defaults,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use super::{ColumnPromotion, Writer};
+use crate::{Error, Result, ValueKind};
+
+/// A column found in the column data, as seen by [`Writer::promote_columns`]
+struct Column {
+    /// The position of the column's flag in the column data
+    position: usize,
+    kind: ValueKind,
+    storage: u8,
+    /// The position of the column's value within each row, if it's rowed
+    row_offset: usize,
+}
+
+/// The column data, row data, row size, and blob data (if it was rebuilt) of
+/// a table
+type PromotedColumns = (Vec<u8>, Vec<u8>, u16, Option<Vec<u8>>);
+
+/// Returns a column's value in a row
+fn cell<'a>(row: &'a [u8], column: &Column) -> &'a [u8] {
+    &row[column.row_offset..column.row_offset + column.kind.size() as usize]
+}
+
+impl Writer<'_> {
+    /// Changes the storage of columns according to the promotion mode, and
+    /// returns the updated column data, row data, and row size
+    ///
+    /// If a BLOB column is made constant, the blob data is rebuilt too, since
+    /// the copies of its blob in every row but the first are left unused.
+    ///
+    /// If nothing changes (including when there are no rows, or no rowed
+    /// columns to compare), or the columns can't be worked out (see
+    /// [`Writer::reorder_strings`]), [`None`] is returned.
+    pub(super) fn promote_columns(
+        &self,
+        row_size: u16,
+        row_count: u32,
+    ) -> Result<Option<PromotedColumns>> {
+        if self.column_promotion == ColumnPromotion::None || row_count == 0 || row_size == 0 {
+            return Ok(None);
+        }
+        let Some(columns) = self.find_columns(row_size) else {
+            return Ok(None);
+        };
+        let rows: Vec<&[u8]> = self.row_data.chunks_exact(row_size as usize).collect();
+        // work out the new storage of each column
+        let mut promoted = false;
+        let mut storage = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let new_storage = match (self.column_promotion, column.storage) {
+                (ColumnPromotion::RowedToConstant, 0x50) => {
+                    let first = self.cell_contents(cell(rows[0], column), column.kind);
+                    let same = rows[1..]
+                        .iter()
+                        .all(|row| self.cell_contents(cell(row, column), column.kind) == first);
+                    if same { 0x30 } else { 0x50 }
+                }
+                (ColumnPromotion::ConstantToRowed, 0x30) => 0x50,
+                (_, storage) => storage,
+            };
+            promoted |= new_storage != column.storage;
+            storage.push(new_storage);
+        }
+        if !promoted {
+            return Ok(None);
+        }
+        let rebuild_blobs = columns.iter().zip(&storage).any(|(column, &new_storage)| {
+            column.kind == ValueKind::BLOB && column.storage == 0x50 && new_storage == 0x30
+        });
+        // lay out the columns and rows again
+        let mut column_data = Vec::with_capacity(self.column_data.len());
+        let mut row_values = Vec::new();
+        let mut constant_blobs = Vec::new();
+        let mut rowed_blobs = Vec::new();
+        let mut row_offset = 0;
+        for (column, new_storage) in columns.iter().zip(storage) {
+            let size = column.kind.size() as usize;
+            let name = &self.column_data[(column.position + 1)..(column.position + 5)];
+            column_data.push(new_storage | column.kind as u8);
+            column_data.extend_from_slice(name);
+            let constant = (column.storage == 0x30)
+                .then(|| &self.column_data[(column.position + 5)..(column.position + 5 + size)]);
+            let blob = column.kind == ValueKind::BLOB;
+            match (new_storage, constant) {
+                (0x30, value) => {
+                    if blob {
+                        constant_blobs.push(column_data.len());
+                    }
+                    column_data.extend_from_slice(value.unwrap_or(cell(rows[0], column)));
+                }
+                (0x50, constant) => {
+                    if blob {
+                        rowed_blobs.push(row_offset);
+                    }
+                    row_offset += size;
+                    row_values.push((column, constant));
+                }
+                _ => {}
+            }
+        }
+        let new_row_size =
+            u16::try_from(row_offset).map_err(|_| Error::TableTooLarge("row size"))?;
+        let mut row_data = Vec::with_capacity(new_row_size as usize * rows.len());
+        for row in &rows {
+            for (column, constant) in &row_values {
+                match constant {
+                    Some(value) => row_data.extend_from_slice(value),
+                    None => row_data.extend_from_slice(cell(row, column)),
+                }
+            }
+        }
+        let blob_data = if rebuild_blobs {
+            let mut blob_data = Vec::new();
+            let mut copies = HashMap::new();
+            for position in constant_blobs {
+                let cell = &mut column_data[position..position + 8];
+                self.relocate_blob(cell, &mut blob_data, &mut copies)?;
+            }
+            if new_row_size > 0 {
+                for row in row_data.chunks_exact_mut(new_row_size as usize) {
+                    for &position in &rowed_blobs {
+                        let cell = &mut row[position..position + 8];
+                        self.relocate_blob(cell, &mut blob_data, &mut copies)?;
+                    }
+                }
+            }
+            Some(blob_data)
+        } else {
+            None
+        };
+        Ok(Some((column_data, row_data, new_row_size, blob_data)))
+    }
+
+    /// Copies the blob a BLOB cell refers to into new blob data (unless it
+    /// was already copied), and points the cell at the copy
+    ///
+    /// Cells that referred to the same blob share one copy, and blobs that no
+    /// cell refers to are left out of the new blob data.
+    fn relocate_blob(
+        &self,
+        cell: &mut [u8],
+        blob_data: &mut Vec<u8>,
+        copies: &mut HashMap<(usize, usize), u32>,
+    ) -> Result<()> {
+        let offset = self.endianness.u32_from(cell[0..4].try_into().unwrap()) as usize;
+        let length = self.endianness.u32_from(cell[4..8].try_into().unwrap()) as usize;
+        let new_offset = match copies.get(&(offset, length)) {
+            Some(&new_offset) => new_offset,
+            None => {
+                let blob = self
+                    .blobs
+                    .get(offset..offset + length)
+                    .ok_or(Error::MalformedHeader)?;
+                let new_offset = u32::try_from(blob_data.len())
+                    .map_err(|_| Error::TableTooLarge("blob data"))?;
+                blob_data.extend_from_slice(blob);
+                copies.insert((offset, length), new_offset);
+                new_offset
+            }
+        };
+        cell[0..4].copy_from_slice(&self.endianness.u32_to(new_offset));
+        Ok(())
+    }
+
+    /// Finds every column in the column data
+    fn find_columns(&self, row_size: u16) -> Option<Vec<Column>> {
+        let mut columns = Vec::with_capacity(self.field_count as usize);
+        let mut position = 0;
+        let mut row_offset = 0;
+        for _ in 0..self.field_count {
+            let flag = self.column_data[position];
+            let kind = ValueKind::from_flag(flag & 0x0f)?;
+            columns.push(Column {
+                position,
+                kind,
+                storage: flag & 0xf0,
+                row_offset,
+            });
+            position += 5;
+            match flag & 0xf0 {
+                0x30 => position += kind.size() as usize,
+                0x50 => row_offset += kind.size() as usize,
+                _ => {}
+            }
+        }
+        (row_offset == row_size as usize).then_some(columns)
+    }
+
+    /// Returns what a cell refers to, so cells can be compared
+    ///
+    /// Blobs are compared by their contents, since every blob is stored
+    /// separately. Everything else is compared by its bytes, since strings
+    /// are only stored once.
+    fn cell_contents<'b>(&'b self, cell: &'b [u8], kind: ValueKind) -> &'b [u8] {
+        if kind != ValueKind::BLOB {
+            return cell;
+        }
+        let offset = self.endianness.u32_from(cell[0..4].try_into().unwrap()) as usize;
+        let length = self.endianness.u32_from(cell[4..8].try_into().unwrap()) as usize;
+        self.blobs.get(offset..offset + length).unwrap_or(cell)
+    }
+}
@@ -0,0 +1,241 @@
+use std::io::Write;
+
+use crate::{Endianness, IOErrorHelper, Result};
+
+/**
+A writer that emits exactly what it's given, without any checks
+
+Unlike [`Writer`](crate::Writer), nothing is validated or worked out
+automatically beyond what's asked for: columns are given as raw flags and
+string offsets, values as raw bytes, and every field of the header can be
+overridden. This is meant for generating intentionally malformed tables, like
+when fuzzing or testing how other tools handle bad input.
+
+By default, the header is filled in from the sizes of the sections, the number
+of columns pushed, and no rows. Nothing is padded.
+
+# Example
+```no_run
+# use std::fs::File;
+# use criware_utf_core::RawWriter;
+let mut raw = RawWriter::new();
+let table_name = raw.push_string("BrokenTable");
+let column_name = raw.push_string("ID");
+// an invalid type flag
+raw.push_column(0x5f, column_name);
+raw.push_row_bytes(&[0xff; 4]);
+// a string offset that points outside of the string data section
+raw.push_column(0x3a, column_name);
+raw.push_column_bytes(&0xdeadbeefu32.to_be_bytes());
+raw.table_name(table_name).row_size(4).row_count(1000);
+raw.end(&mut File::create("broken-table.bin")?)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+*/
+pub struct RawWriter {
+    endianness: Endianness,
+    magic: [u8; 4],
+    column_data: Vec<u8>,
+    row_data: Vec<u8>,
+    string_data: Vec<u8>,
+    blob_data: Vec<u8>,
+    field_count: u16,
+    table_size: Option<u32>,
+    row_offset: Option<u32>,
+    string_offset: Option<u32>,
+    blob_offset: Option<u32>,
+    table_name: u32,
+    field_count_override: Option<u16>,
+    row_size: u16,
+    row_count: u32,
+}
+
+impl Default for RawWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RawWriter {
+    /// Creates a new, empty `RawWriter` for a big-endian table
+    ///
+    pub fn new() -> Self {
+        Self::with_endianness(Endianness::Big)
+    }
+
+    /// Creates a new, empty `RawWriter` that stores the header with the given
+    /// byte order
+    ///
+    pub fn with_endianness(endianness: Endianness) -> Self {
+        RawWriter {
+            endianness,
+            magic: *b"@UTF",
+            column_data: Vec::new(),
+            row_data: Vec::new(),
+            string_data: Vec::new(),
+            blob_data: Vec::new(),
+            field_count: 0,
+            table_size: None,
+            row_offset: None,
+            string_offset: None,
+            blob_offset: None,
+            table_name: 0,
+            field_count_override: None,
+            row_size: 0,
+            row_count: 0,
+        }
+    }
+
+    /// Adds a column with the given flag (storage and type) and name offset
+    ///
+    pub fn push_column(&mut self, flag: u8, name: u32) -> &mut Self {
+        self.column_data.push(flag);
+        self.column_data
+            .extend_from_slice(&self.endianness.u32_to(name));
+        self.field_count = self.field_count.wrapping_add(1);
+        self
+    }
+
+    /// Adds bytes to the column data section (like the value of a constant
+    /// column)
+    ///
+    pub fn push_column_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.column_data.extend_from_slice(bytes);
+        self
+    }
+
+    /// Adds bytes to the row data section
+    ///
+    pub fn push_row_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.row_data.extend_from_slice(bytes);
+        self
+    }
+
+    /// Adds bytes to the string data section as-is, and returns their offset
+    ///
+    /// Nothing is added after them, so this can be used to leave a string
+    /// unterminated.
+    ///
+    pub fn push_string_bytes(&mut self, bytes: &[u8]) -> u32 {
+        let position = self.string_data.len() as u32;
+        self.string_data.extend_from_slice(bytes);
+        position
+    }
+
+    /// Adds a NUL-terminated UTF-8 string to the string data section, and
+    /// returns its offset
+    ///
+    /// Strings are never deduplicated.
+    ///
+    pub fn push_string(&mut self, value: &str) -> u32 {
+        let position = self.push_string_bytes(value.as_bytes());
+        self.string_data.push(0);
+        position
+    }
+
+    /// Adds bytes to the blob data section, and returns their offset
+    ///
+    pub fn push_blob(&mut self, bytes: &[u8]) -> u32 {
+        let position = self.blob_data.len() as u32;
+        self.blob_data.extend_from_slice(bytes);
+        position
+    }
+
+    /// Sets the 4-byte magic at the start of the table (`@UTF` by default)
+    ///
+    pub fn magic(&mut self, magic: [u8; 4]) -> &mut Self {
+        self.magic = magic;
+        self
+    }
+
+    /// Sets the table size stored in the header, instead of the actual size
+    ///
+    pub fn table_size(&mut self, table_size: u32) -> &mut Self {
+        self.table_size = Some(table_size);
+        self
+    }
+
+    /// Sets the offset of the row data section stored in the header, instead
+    /// of the actual offset
+    ///
+    pub fn row_offset(&mut self, row_offset: u32) -> &mut Self {
+        self.row_offset = Some(row_offset);
+        self
+    }
+
+    /// Sets the offset of the string data section stored in the header,
+    /// instead of the actual offset
+    ///
+    pub fn string_offset(&mut self, string_offset: u32) -> &mut Self {
+        self.string_offset = Some(string_offset);
+        self
+    }
+
+    /// Sets the offset of the blob data section stored in the header, instead
+    /// of the actual offset
+    ///
+    pub fn blob_offset(&mut self, blob_offset: u32) -> &mut Self {
+        self.blob_offset = Some(blob_offset);
+        self
+    }
+
+    /// Sets the offset of the table name (0 by default)
+    ///
+    pub fn table_name(&mut self, table_name: u32) -> &mut Self {
+        self.table_name = table_name;
+        self
+    }
+
+    /// Sets the field count stored in the header, instead of the number of
+    /// columns pushed
+    ///
+    pub fn field_count(&mut self, field_count: u16) -> &mut Self {
+        self.field_count_override = Some(field_count);
+        self
+    }
+
+    /// Sets the row size stored in the header (0 by default)
+    ///
+    pub fn row_size(&mut self, row_size: u16) -> &mut Self {
+        self.row_size = row_size;
+        self
+    }
+
+    /// Sets the row count stored in the header (0 by default)
+    ///
+    pub fn row_count(&mut self, row_count: u32) -> &mut Self {
+        self.row_count = row_count;
+        self
+    }
+
+    /// Writes the table to the given stream
+    ///
+    /// The only error that can happen is an I/O error.
+    ///
+    pub fn end(&self, writer: &mut dyn Write) -> Result<()> {
+        // sizes wrap around instead of failing, since nothing is checked
+        let row_offset = (self.column_data.len() as u32).wrapping_add(24);
+        let string_offset = row_offset.wrapping_add(self.row_data.len() as u32);
+        let blob_offset = string_offset.wrapping_add(self.string_data.len() as u32);
+        let table_size = blob_offset.wrapping_add(self.blob_data.len() as u32);
+        let endianness = self.endianness;
+        let mut header = Vec::with_capacity(32);
+        header.extend_from_slice(&self.magic);
+        header.extend_from_slice(&endianness.u32_to(self.table_size.unwrap_or(table_size)));
+        header.extend_from_slice(&endianness.u32_to(self.row_offset.unwrap_or(row_offset)));
+        header.extend_from_slice(&endianness.u32_to(self.string_offset.unwrap_or(string_offset)));
+        header.extend_from_slice(&endianness.u32_to(self.blob_offset.unwrap_or(blob_offset)));
+        header.extend_from_slice(&endianness.u32_to(self.table_name));
+        header.extend_from_slice(
+            &endianness.u16_to(self.field_count_override.unwrap_or(self.field_count)),
+        );
+        header.extend_from_slice(&endianness.u16_to(self.row_size));
+        header.extend_from_slice(&endianness.u32_to(self.row_count));
+        writer.write_all(&header).io("@UTF header")?;
+        writer.write_all(&self.column_data).io("UTF column data")?;
+        writer.write_all(&self.row_data).io("UTF row data")?;
+        writer.write_all(&self.string_data).io("UTF string data")?;
+        writer.write_all(&self.blob_data).io("UTF blobs")?;
+        Ok(())
+    }
+}
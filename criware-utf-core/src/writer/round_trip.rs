@@ -0,0 +1,157 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{OriginalLayout, TableNamePlacement, WriterOptions};
+use crate::{ColumnStorageFormat, Reader, Result};
+
+/**
+Extra contextual info for accurately recreating read tables when writing
+
+This is used by the `utf_table` macro, for tables with optional rowed columns
+or the `round_trip = true` option. When a table is read, a context is created
+with the state of its columns. When a table is created, a context is
+created and configured based on the schema provided to the macro.
+
+It covers two things:
+
+- The storage of each column. If a table with optional rowed values has all of
+  its rows removed, there's no other way to know whether a column should be
+  written as zero or rowed.
+- The layout of the table (if
+  [`ReaderOptions::record_layout`](crate::ReaderOptions::record_layout) is
+  set), which is passed to the writer through
+  [`WriterOptions::original_layout`]. This makes a table that's written back
+  unmodified byte-identical to the original. The placement of the table name
+  is recorded too, so it stays in place even if the layout is cleared.
+
+# Example
+```no_run
+# use criware_utf::{Reader, ReaderOptions, Table, utf_table};
+# #[utf_table(round_trip = true)]
+# struct Tab { value: u32 }
+let data = std::fs::read("random-table.bin")?;
+let options = ReaderOptions {
+    record_layout: true,
+    ..Tab::reader_options()
+};
+let mut table = Tab::read_from_reader(Reader::from_bytes_with_options(&data, options)?)?;
+table.rows[0].value = 100;
+// only the value that changed is different
+let mut patched = Vec::new();
+table.write(&mut patched)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+ */
+#[derive(Debug, Clone, Default)]
+pub struct RoundTripContext {
+    storage: HashMap<String, ColumnStorageFormat>,
+    layout: Option<Arc<OriginalLayout>>,
+    table_name_placement: Option<TableNamePlacement>,
+}
+
+impl RoundTripContext {
+    ///
+    /// Creates a new, empty context
+    ///
+    pub fn new() -> Self {
+        RoundTripContext {
+            storage: HashMap::new(),
+            layout: None,
+            table_name_placement: None,
+        }
+    }
+    ///
+    /// Creates a context for a table being read
+    ///
+    /// If [`ReaderOptions::record_layout`](crate::ReaderOptions::record_layout)
+    /// is set, the storage of every column and the layout of the table are
+    /// recorded. Otherwise, the context is empty. Either way, this doesn't
+    /// affect the position of the column or row buffers.
+    ///
+    pub fn read(reader: &mut Reader<'_>) -> Result<Self> {
+        let mut context = Self::new();
+        if reader.options().record_layout {
+            for (name, location) in reader.columns()? {
+                context
+                    .storage
+                    .insert(name.clone(), location.storage_format);
+            }
+            let options = reader.round_trip_options()?;
+            context.layout = options.original_layout;
+            context.table_name_placement = Some(options.table_name_placement);
+        }
+        Ok(context)
+    }
+    ///
+    /// Returns the original storage of the given column, if it's known
+    ///
+    pub fn storage(&self, column_name: &str) -> Option<ColumnStorageFormat> {
+        self.storage.get(column_name).copied()
+    }
+    ///
+    /// Sets the original storage of the given column
+    ///
+    pub fn set_storage(&mut self, column_name: &str, storage: ColumnStorageFormat) {
+        self.storage.insert(column_name.to_owned(), storage);
+    }
+    ///
+    /// Returns [`true`] if the given column should be included (rowed), or
+    /// [`false`] if it should be excluded (zero)
+    ///
+    pub fn is_included(&self, column_name: &str) -> bool {
+        self.storage(column_name) != Some(ColumnStorageFormat::Zero)
+    }
+    ///
+    /// Sets the inclusion state of a column. [`true`] denotes rowed, [`false`]
+    /// denotes zero
+    ///
+    pub fn set_inclusion_state(&mut self, column_name: &str, included: bool) {
+        let storage = match included {
+            true => ColumnStorageFormat::Rowed,
+            false => ColumnStorageFormat::Zero,
+        };
+        self.set_storage(column_name, storage);
+    }
+    ///
+    /// Returns the recorded layout of the table, if there is one
+    ///
+    pub fn layout(&self) -> Option<&Arc<OriginalLayout>> {
+        self.layout.as_ref()
+    }
+    ///
+    /// Sets (or clears) the recorded layout of the table
+    ///
+    /// Clearing it makes the table be laid out from scratch when it's written,
+    /// which gets rid of strings and blobs that are no longer used.
+    ///
+    pub fn set_layout(&mut self, layout: Option<Arc<OriginalLayout>>) {
+        self.layout = layout;
+    }
+    ///
+    /// Returns the recorded placement of the table name, if there is one
+    ///
+    pub fn table_name_placement(&self) -> Option<TableNamePlacement> {
+        self.table_name_placement
+    }
+    ///
+    /// Sets (or clears) the recorded placement of the table name
+    ///
+    pub fn set_table_name_placement(&mut self, placement: Option<TableNamePlacement>) {
+        self.table_name_placement = placement;
+    }
+    ///
+    /// Returns the given options with the recorded layout and table name
+    /// placement, unless they already have their own (a placement other than
+    /// the default)
+    ///
+    pub fn apply(&self, mut options: WriterOptions) -> WriterOptions {
+        if options.original_layout.is_none() {
+            options.original_layout = self.layout.clone();
+        }
+        if options.table_name_placement == TableNamePlacement::default()
+            && let Some(placement) = self.table_name_placement
+        {
+            options.table_name_placement = placement;
+        }
+        options
+    }
+}
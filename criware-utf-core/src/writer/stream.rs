@@ -0,0 +1,170 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::{TableLayout, Writer};
+use crate::{Error, IOErrorHelper, Result};
+
+pub(super) trait WriteSeek: Write + Seek {}
+impl<T: Write + Seek> WriteSeek for T {}
+
+pub(super) trait ReadWriteSeek: Read + Write + Seek {}
+impl<T: Read + Write + Seek> ReadWriteSeek for T {}
+
+/// The destination of a [`Writer`] that streams its rows
+pub(super) struct RowStream<'a> {
+    pub(super) destination: Box<dyn WriteSeek + 'a>,
+    /// The position of the table in the destination
+    start: u64,
+    /// The length of the column data when streaming started
+    column_data_len: usize,
+    pub(super) rows_len: u64,
+    pub(super) blob_spool: Option<Box<dyn ReadWriteSeek + 'a>>,
+    /// The number of bytes written to the blob spool
+    pub(super) blobs_len: u64,
+}
+
+impl<'a> Writer<'a> {
+    /**
+    Starts writing rows directly to the given stream, instead of buffering
+    them
+
+    Every column must be pushed before this is called. The header and column
+    data are written right away (the header is filled in by
+    [`Writer::end_stream`]), and each value written to the row buffer
+    afterwards goes straight to the stream. Strings and blobs are still
+    buffered until the end, since they're stored after the rows. To keep the
+    blobs out of memory as well, use [`Writer::stream_rows_with_blob_spool`].
+
+    [`WriterOptions::string_order`](crate::WriterOptions::string_order) is
+    ignored, since the rows can't be changed once they're written.
+
+    # Example
+    ```no_run
+    # use std::fs::File;
+    # use criware_utf_core::Writer;
+    # let ids: Vec<u64> = Vec::new();
+    let mut file = File::create("huge-table.bin")?;
+    let mut writer = Writer::new("HugeTable");
+    writer.push_rowed_column::<u64>("ID");
+    writer.stream_rows(&mut file)?;
+    for id in &ids {
+        writer.write_value(true, id)?;
+    }
+    writer.end_stream(8, ids.len() as u32)?;
+    # Ok::<(), Box<dyn std::error::Error>>(())
+    ```
+     */
+    pub fn stream_rows(&mut self, destination: impl Write + Seek + 'a) -> Result<()> {
+        self.start_stream(Box::new(destination), None)
+    }
+
+    /**
+    Starts writing rows directly to the given stream, and blobs to the given
+    spool (like a temporary file)
+
+    Once the rows and strings are written, the blobs are copied from the spool
+    to the end of the table. The spool should be empty.
+
+    See [`Writer::stream_rows`].
+     */
+    pub fn stream_rows_with_blob_spool(
+        &mut self,
+        destination: impl Write + Seek + 'a,
+        spool: impl Read + Write + Seek + 'a,
+    ) -> Result<()> {
+        self.start_stream(Box::new(destination), Some(Box::new(spool)))
+    }
+
+    fn start_stream(
+        &mut self,
+        mut destination: Box<dyn WriteSeek + 'a>,
+        mut blob_spool: Option<Box<dyn ReadWriteSeek + 'a>>,
+    ) -> Result<()> {
+        if self.stream.is_some() || !self.row_data.is_empty() {
+            return Err(Error::MalformedHeader);
+        }
+        let start = destination.stream_position().io("@UTF header")?;
+        destination.write_all(&[0u8; 32]).io("@UTF header")?;
+        destination
+            .write_all(&self.column_data)
+            .io("UTF column data")?;
+        // blobs written to a constant column so far are moved to the spool
+        let mut blobs_len = 0;
+        if let Some(spool) = &mut blob_spool {
+            spool.write_all(&self.blobs).io("UTF blobs")?;
+            blobs_len = self.blobs.len() as u64;
+            self.blobs = Vec::new();
+        }
+        self.stream = Some(RowStream {
+            destination,
+            start,
+            column_data_len: self.column_data.len(),
+            rows_len: 0,
+            blob_spool,
+            blobs_len,
+        });
+        Ok(())
+    }
+
+    /**
+    Finishes a table whose rows were streamed, by writing the strings and
+    blobs, and then filling in the header
+
+    Afterwards, the stream is positioned at the end of the table, and where
+    each section went is returned (see [`TableLayout`]). This fails
+    if [`Writer::stream_rows`] wasn't called, or if a column was pushed after
+    it was.
+
+    See [`Writer::end`].
+     */
+    pub fn end_stream(mut self, row_size: u16, row_count: u32) -> Result<TableLayout> {
+        self.check_row_ended()?;
+        let Some(mut stream) = self.stream.take() else {
+            return Err(Error::MalformedHeader);
+        };
+        if stream.column_data_len != self.column_data.len()
+            || stream.rows_len != row_size as u64 * row_count as u64
+        {
+            return Err(Error::MalformedHeader);
+        }
+        self.check_names()?;
+        let blobs_len = match &stream.blob_spool {
+            Some(_) => stream.blobs_len,
+            None => self.blobs.len() as u64,
+        };
+        let layout = self.layout(
+            self.column_data.len() as u64,
+            stream.rows_len,
+            self.string_data.len() as u64,
+            blobs_len,
+        )?;
+        let destination = &mut stream.destination;
+        destination
+            .write_all(&self.string_data)
+            .io("UTF string data")?;
+        destination
+            .write_all(&self.blob_padding(&layout))
+            .io("UTF string data")?;
+        match &mut stream.blob_spool {
+            Some(spool) => {
+                spool.seek(SeekFrom::Start(0)).io("UTF blobs")?;
+                let copied = std::io::copy(&mut spool.take(stream.blobs_len), destination)
+                    .io("UTF blobs")?;
+                if copied != stream.blobs_len {
+                    return Err(Error::EOF("UTF blobs".to_owned()));
+                }
+            }
+            None => destination.write_all(&self.blobs).io("UTF blobs")?,
+        }
+        destination
+            .write_all(&vec![self.pad_byte; layout.table_padding as usize])
+            .io("UTF blobs")?;
+        let end = destination.stream_position().io("UTF blobs")?;
+        destination
+            .seek(SeekFrom::Start(stream.start))
+            .io("@UTF header")?;
+        self.write_header(destination, &layout, row_size, row_count)?;
+        destination.seek(SeekFrom::Start(end)).io("UTF blobs")?;
+        destination.flush().io("UTF blobs")?;
+        Ok(layout.report(row_size, row_count))
+    }
+}
@@ -14,6 +14,8 @@ macro_rules! syn_error {
     };
 }
 
+mod utf_enum;
+mod utf_newtype;
 mod utf_table;
 mod utils;
 
@@ -23,7 +25,8 @@ Macro for arranging a table and implementing useful `Table` behavior
 # General Output Structure
 
 This attribute macro accepts a struct definition, and outputs one or more new
-struct definitions, along with an implementation of the `Table` trait.
+struct definitions, along with implementations of the `Table` and
+`DescribedTable` traits.
 
 For example, if the struct is named `ImportantTable`. The final struct
 definition may look something like this:
@@ -34,7 +37,7 @@ definition may look something like this:
 struct ImportantTable {
     constants: ImportantTableConstants,
     rows: Vec<ImportantTableRow>,
-    write_context: criware_utf::WriteContext
+    write_context: criware_utf::RoundTripContext
 }
 ```
 
@@ -43,16 +46,18 @@ as part of its output, containing the constant or row fields respectively. They
 are only defined when they are needed.
 
 - If there are no constant columns, `ImportantTableConstants` and the `constants`
-field are not included
+  field are not included
 - If there are no rowed columns, `ImportantTableRow` and the `rows` field are
-not included
-- If there are no optional rowed columns, the `write_context` field is not
-included
+  not included
+- The `write_context` field holds what's needed to write a table back the way
+  it was read (see `RoundTripContext`, which replaced `WriteContext`). It's
+  only included if there are optional rowed columns, or if the `round_trip`
+  option is set
 
 ## Input/Output Examples
 
-```no_run
-# use criware_utf_derive::utf_table;
+```ignore
+# use criware_utf::utf_table;
 // Input
 #[utf_table]
 struct NuTable {
@@ -77,8 +82,8 @@ struct NuTable {
 impl Table for NuTable {/** ... */}
 ```
 
-```no_run
-# use criware_utf_derive::utf_table;
+```ignore
+# use criware_utf::utf_table;
 // Input
 #[utf_table(constants = FileTableInfo, row = File)]
 struct FileTable {
@@ -107,7 +112,7 @@ struct File {
 struct FileTable {
     constants: FileTableInfo,
     rows: Vec<File>,
-    write_context: WriteContext
+    write_context: RoundTripContext
 }
 impl Table for FileTable {/** ... */}
 ```
@@ -121,7 +126,7 @@ These options can be chained together by separating them with a comma. Specifyin
 any of these options multiple times will cause a compile error.
 
 ```no_run
-# use criware_utf_derive::utf_table;
+# use criware_utf::utf_table;
 #[utf_table(table_name = "TABLE", constants = TableStuff)]
 # struct Table {}
 ```
@@ -133,7 +138,7 @@ This can be overwritten by specifying a string literal. The generated read proce
 will check that the name of the table matches, so specifying this is important.
 
 ```no_run
-# use criware_utf_derive::utf_table;
+# use criware_utf::utf_table;
 #[utf_table(table_name = "ActualTableName")]
 # struct Table {}
 ```
@@ -145,7 +150,7 @@ input struct* + "Constants". This name can be overwritten by specifying a type n
 If there is no constant struct generated, this option does nothing.
 
 ```no_run
-# use criware_utf_derive::utf_table;
+# use criware_utf::utf_table;
 #[utf_table(constants = TConsts)]
 # struct Table {}
 ```
@@ -157,11 +162,78 @@ input struct* + "Row". This name can be overwritten by specifying a type name.
 If there is no row struct generated, this option does nothing.
 
 ```no_run
-# use criware_utf_derive::utf_table;
+# use criware_utf::utf_table;
 #[utf_table(row = TRow)]
 # struct Table {}
 ```
 
+## `endian`
+
+Official tables are always big-endian, and that is what the generated read
+and write procedures expect by default. Some modified games ship tables with
+little-endian numbers instead, which can be handled by specifying `"little"`
+(or `"big"`, which does nothing).
+
+```no_run
+# use criware_utf::utf_table;
+#[utf_table(endian = "little")]
+# struct Table {}
+```
+
+## `encoding`
+
+Modern tables store strings as UTF-8, which is what the generated read and
+write procedures expect by default. Legacy tables store Shift-JIS strings
+instead, which can be handled by specifying `"shift_jis"` (or `"utf8"`, which
+does nothing). This requires the `encoding_rs` feature.
+
+```no_run
+# use criware_utf::utf_table;
+#[utf_table(encoding = "shift_jis")]
+# struct Table {}
+```
+
+## `prefix`
+
+Tables that are always stored in packets with the same 4-byte prefix can
+specify it, which implements `PrefixedTable` for the table. Packets of the
+table can then be read and written without passing the prefix around. A
+prefix given at runtime (like with `Packet::set_prefix`) still overrides it.
+
+```no_run
+# use criware_utf::utf_table;
+#[utf_table(prefix = "TOC ")]
+# struct Table {}
+```
+
+## `round_trip`
+
+By default, the table struct only has a `write_context` field if it has
+optional rowed columns, so tables can be created with struct literals. Setting
+this to `true` always includes it, so a table read with
+`ReaderOptions::record_layout` is written back byte-identical to the original.
+
+```
+# use criware_utf::utf_table;
+#[utf_table]
+struct Plain {
+    value: u32,
+}
+
+#[utf_table(round_trip = true)]
+struct Recorded {
+    value: u32,
+}
+
+let plain = Plain {
+    rows: vec![PlainRow { value: 1 }],
+};
+let recorded = Recorded {
+    rows: vec![RecordedRow { value: 1 }],
+    write_context: criware_utf::RoundTripContext::new(),
+};
+```
+
 # Field Options
 
 This section outlines the optional configuration options for each field within
@@ -171,7 +243,7 @@ Multiple may be used on the same field, but duplicate or conflicting options
 will result in a compile error.
 
 ```no_run
-# #[criware_utf_derive::utf_table]
+# #[criware_utf::utf_table]
 # struct Table {
 #[optional]
 #[constant]
@@ -186,7 +258,7 @@ camel case** conversion of the field name (e.g. "some_column" => "SomeColumn").
 This can be overwritten by passing a string literal.
 
 ```no_run
-# #[criware_utf_derive::utf_table]
+# #[criware_utf::utf_table]
 # struct Table {
 #[column_name = "TheActualColumnName"]
 some_value: i64
@@ -200,7 +272,7 @@ By default, columns are rowed. This marks the column as constant instead.
 If this is used in the presence of `#[rowed]`, there will be a compile error.
 
 ```no_run
-# #[criware_utf_derive::utf_table]
+# #[criware_utf::utf_table]
 # struct Table {
 #[constant]
 some_value: i64
@@ -216,7 +288,7 @@ column clear.
 If this is used in the presence of `#[constant]`, there will be a compile error.
 
 ```no_run
-# #[criware_utf_derive::utf_table]
+# #[criware_utf::utf_table]
 # struct Table {
 #[rowed]
 some_value: i64
@@ -256,6 +328,51 @@ This will make the column constant/rowed instead of zero
 
 This does nothing, but can make the write procedure's behavior more clear to
 readers.
+
+## `#[on_read = "{function}"]`
+
+Names a function with the signature `fn(&mut [u8])`, which is called with the
+raw bytes of each of the column's values right before they are decoded.
+
+This can be used to normalize game-specific quirks (sentinel values, garbage
+padding) that would otherwise be lost or fail to decode. It is never called
+for a column stored as zero.
+
+```no_run
+fn fix_sentinel(bytes: &mut [u8]) {
+    if bytes == [0xff; 4] {
+        bytes.fill(0);
+    }
+}
+
+# #[criware_utf::utf_table]
+# struct Table {
+#[on_read = "fix_sentinel"]
+some_value: u32
+# }
+```
+
+## `#[on_write = "{function}"]`
+
+Names a function with the signature `fn(&mut [u8])`, which is called with the
+encoded bytes of each of the column's values right before they are stored.
+
+Paired with `#[on_read]`, this allows game-specific quirks to survive a read
+and write round trip.
+
+```no_run
+fn restore_sentinel(bytes: &mut [u8]) {
+    if bytes == [0; 4] {
+        bytes.fill(0xff);
+    }
+}
+
+# #[criware_utf::utf_table]
+# struct Table {
+#[on_write = "restore_sentinel"]
+some_value: u32
+# }
+```
 */
 #[proc_macro_attribute]
 pub fn utf_table(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -264,3 +381,119 @@ pub fn utf_table(attr: TokenStream, item: TokenStream) -> TokenStream {
         Err(error) => error.to_compile_error().into(),
     }
 }
+
+/**
+Derive macro for implementing `Value` for an enum, so it can be used as the
+type of a column
+
+The enum must have an integer representation (`#[repr(u8)]`, `#[repr(u16)]`,
+`#[repr(u32)]`, etc.), which is the type the column is stored as. Each
+variant is read from and written as its discriminant.
+
+```
+# use criware_utf::{UtfEnum, utf_table};
+#[derive(UtfEnum)]
+#[repr(u8)]
+enum FileKind {
+    Audio = 1,
+    Video,
+    Image = 10,
+}
+
+#[utf_table]
+struct Files {
+    name: String,
+    kind: FileKind,
+}
+```
+
+By default, reading a value that isn't the discriminant of any variant fails
+with `Error::UnknownEnumValue`.
+
+The generated code refers to everything by its full path, so it still works
+where names like `Ok` are shadowed:
+
+```
+# use criware_utf::{UtfEnum, UtfNewtype};
+mod outcome {
+    pub enum Outcome {
+        Ok,
+        Err,
+    }
+    pub use Outcome::{Err, Ok};
+}
+use outcome::{Err, Ok};
+
+#[derive(UtfEnum)]
+#[repr(u8)]
+enum FileKind {
+    Audio,
+    Video,
+}
+
+#[derive(UtfNewtype)]
+struct FileId(u32);
+```
+
+# Variant Options
+
+## `#[utf(other)]`
+
+Marks the variant that unknown values are read as. If the variant has a single
+field (of the enum's representation type), the unknown value is kept in it, and
+written back as-is. If the variant is fieldless, the unknown value is lost, and
+the variant is written as its own discriminant.
+
+Only one variant can be marked.
+
+```
+# use criware_utf::UtfEnum;
+#[derive(UtfEnum)]
+#[repr(u16)]
+enum Codec {
+    Adx = 0,
+    Hca = 2,
+    #[utf(other)]
+    Unknown(u16) = 0xffff,
+}
+```
+*/
+#[proc_macro_derive(UtfEnum, attributes(utf))]
+pub fn derive_utf_enum(item: TokenStream) -> TokenStream {
+    match utf_enum::derive(item.into()) {
+        Ok(value) => value.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/**
+Derive macro for implementing `Value` for a struct with a single field, by
+delegating to the field's implementation
+
+This allows a wrapper type to be used as the type of a column, without writing
+the conversions by hand. The struct is stored exactly like its field.
+
+```
+# use criware_utf::{UtfNewtype, utf_table};
+#[derive(UtfNewtype)]
+struct FileId(u32);
+
+#[derive(UtfNewtype)]
+struct FileName {
+    name: String,
+}
+
+#[utf_table]
+struct Files {
+    id: FileId,
+    name: FileName,
+}
+```
+*/
+#[proc_macro_derive(UtfNewtype)]
+pub fn derive_utf_newtype(item: TokenStream) -> TokenStream {
+    match utf_newtype::derive(item.into()) {
+        Ok(value) => value.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,171 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Fields, Ident, Meta, Variant, spanned::Spanned};
+
+use crate::Result;
+
+/// The integer types an enum may be represented as
+const REPRS: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+enum OtherVariant {
+    /// A unit variant that unknown values are read as (and that's written as
+    /// its own discriminant)
+    Unit(Ident),
+    /// A variant with a single field that holds unknown values
+    Holding(Ident),
+}
+
+fn find_repr(input: &DeriveInput) -> Result<Ident> {
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        let mut repr = None;
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident()
+                && REPRS.contains(&ident.to_string().as_str())
+            {
+                repr = Some(ident.clone());
+            }
+            Ok(())
+        })?;
+        if let Some(repr) = repr {
+            return Ok(repr);
+        }
+    }
+    syn_error!(
+        input.ident.span(),
+        "UtfEnum requires an integer representation, like #[repr(u8)]"
+    )
+}
+
+fn is_other(variant: &Variant) -> Result<bool> {
+    let mut other = false;
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("utf") {
+            continue;
+        }
+        let Meta::List(list) = &attr.meta else {
+            syn_error!(attr.span(), "Incorrect usage of \"utf\"\n#[utf(other)]")
+        };
+        let ident = list.parse_args::<Ident>()?;
+        if ident != "other" {
+            syn_error!(ident.span(), "Unknown option. Expected \"other\"")
+        }
+        if other {
+            syn_error!(attr.span(), "Duplicate option")
+        }
+        other = true;
+    }
+    Ok(other)
+}
+
+pub fn derive(input: TokenStream) -> Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let syn::Data::Enum(data) = &input.data else {
+        syn_error!(input.span(), "UtfEnum can only be derived for enums")
+    };
+    let repr = find_repr(&input)?;
+    let mut other = None;
+    let mut consts = Vec::new();
+    let mut idents = Vec::new();
+    let mut previous: Option<Ident> = None;
+    for (index, variant) in data.variants.iter().enumerate() {
+        let is_other = is_other(variant)?;
+        if is_other && other.is_some() {
+            syn_error!(variant.span(), "Only one variant can be #[utf(other)]")
+        }
+        let discriminant_const = format_ident!("__DISCRIMINANT_{}", index);
+        let discriminant = match (&variant.discriminant, &previous) {
+            (Some((_, expr)), _) => quote! { #expr },
+            (None, Some(previous)) => quote! { #previous + 1 },
+            (None, None) => quote! { 0 },
+        };
+        match &variant.fields {
+            Fields::Unit => {
+                consts.push(quote! { const #discriminant_const: #repr = #discriminant; });
+                previous = Some(discriminant_const.clone());
+                if is_other {
+                    other = Some(OtherVariant::Unit(variant.ident.clone()));
+                } else {
+                    idents.push((variant.ident.clone(), discriminant_const));
+                }
+            }
+            Fields::Unnamed(fields) if is_other && fields.unnamed.len() == 1 => {
+                consts.push(quote! { const #discriminant_const: #repr = #discriminant; });
+                previous = Some(discriminant_const);
+                other = Some(OtherVariant::Holding(variant.ident.clone()));
+            }
+            _ => syn_error!(
+                variant.span(),
+                "UtfEnum variants must be fieldless (except for a #[utf(other)] variant, which may hold the value)"
+            ),
+        }
+    }
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let read_idents = idents.iter().map(|(ident, _)| ident);
+    let read_consts = idents.iter().map(|(_, value)| value);
+    let write_idents = read_idents.clone();
+    let write_consts = read_consts.clone();
+    let (read_other, write_other) = match &other {
+        Some(OtherVariant::Unit(other)) => {
+            let value = format_ident!(
+                "__DISCRIMINANT_{}",
+                data.variants
+                    .iter()
+                    .position(|variant| &variant.ident == other)
+                    .unwrap()
+            );
+            (
+                quote! { _ => Self::#other, },
+                quote! { Self::#other => #value, },
+            )
+        }
+        Some(OtherVariant::Holding(other)) => (
+            quote! { value => Self::#other(value), },
+            quote! { Self::#other(value) => *value, },
+        ),
+        None => (
+            quote! {
+                value => {
+                    return ::std::result::Result::Err(::std::boxed::Box::new(
+                        ::criware_utf::Error::UnknownEnumValue(#name, value as i128),
+                    ))
+                }
+            },
+            quote! {},
+        ),
+    };
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::criware_utf::Value for #ident #type_generics #where_clause {
+            type Primitive = #repr;
+
+            #[allow(dead_code, non_upper_case_globals, clippy::identity_op)]
+            fn from_primitive(
+                value: #repr,
+            ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error>> {
+                #(#consts)*
+                ::std::result::Result::Ok(match value {
+                    #(#read_consts => Self::#read_idents,)*
+                    #read_other
+                })
+            }
+            #[allow(dead_code, non_upper_case_globals, clippy::identity_op)]
+            fn to_primitive<'a>(
+                &'a self,
+            ) -> ::std::result::Result<
+                ::std::borrow::Cow<'a, #repr>,
+                ::std::boxed::Box<dyn ::std::error::Error>,
+            > {
+                #(#consts)*
+                ::std::result::Result::Ok(::std::borrow::Cow::Owned(match self {
+                    #(Self::#write_idents => #write_consts,)*
+                    #write_other
+                }))
+            }
+        }
+    })
+}
@@ -0,0 +1,56 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Fields, Member, parse_quote, spanned::Spanned};
+
+use crate::Result;
+
+pub fn derive(input: TokenStream) -> Result<TokenStream> {
+    let input = syn::parse2::<DeriveInput>(input)?;
+    let syn::Data::Struct(data) = &input.data else {
+        syn_error!(input.span(), "UtfNewtype can only be derived for structs")
+    };
+    let field = match &data.fields {
+        Fields::Named(fields) if fields.named.len() == 1 => &fields.named[0],
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => syn_error!(
+            data.fields.span(),
+            "UtfNewtype can only be derived for structs with a single field"
+        ),
+    };
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(0.into()),
+    };
+    let inner = &field.ty;
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { #inner: ::criware_utf::Value });
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::criware_utf::Value for #ident #type_generics #where_clause {
+            type Primitive = <#inner as ::criware_utf::Value>::Primitive;
+
+            #[inline]
+            fn from_primitive(
+                value: <Self::Primitive as ::std::borrow::ToOwned>::Owned,
+            ) -> ::std::result::Result<Self, ::std::boxed::Box<dyn ::std::error::Error>> {
+                ::std::result::Result::Ok(Self {
+                    #member: <#inner as ::criware_utf::Value>::from_primitive(value)?,
+                })
+            }
+            #[inline]
+            fn to_primitive<'a>(
+                &'a self,
+            ) -> ::std::result::Result<
+                ::std::borrow::Cow<'a, Self::Primitive>,
+                ::std::boxed::Box<dyn ::std::error::Error>,
+            > {
+                <#inner as ::criware_utf::Value>::to_primitive(&self.#member)
+            }
+        }
+    })
+}
@@ -15,8 +15,10 @@ mod main_attr;
 mod structs;
 
 pub fn parse(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
-    let struct_info = parse_struct_info(attr, item)?;
+    let mut struct_info = parse_struct_info(attr, item)?;
     let columns = parse_columns(&struct_info.data)?;
+    // Optional rowed columns need the context to remember their storage
+    struct_info.write_context |= columns.has_optional_row;
     let structs = generate_structs(&struct_info, &columns);
     let table_impl = impl_table(&struct_info, &columns);
     Ok(quote! {
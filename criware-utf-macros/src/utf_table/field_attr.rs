@@ -1,5 +1,5 @@
 use quote::format_ident;
-use syn::{DataStruct, Field, Ident, Type, Visibility, spanned::Spanned};
+use syn::{DataStruct, Field, Ident, Path, Type, Visibility, spanned::Spanned};
 
 use crate::{Result, utils::*};
 
@@ -14,6 +14,8 @@ pub struct Column {
     pub column_name: String,
     pub storage_type: ColumnStorageType,
     pub optional: Option<bool>,
+    pub on_read: Option<Path>,
+    pub on_write: Option<Path>,
     pub ty: Type,
     pub vis: Visibility,
     pub variable_ident: Ident,
@@ -24,8 +26,10 @@ fn parse_column(field: &Field, idx: usize) -> Result<Column> {
     let mut storage_type = None;
     let mut optional = None;
     let mut column_name = None;
+    let mut on_read = None;
+    let mut on_write = None;
     for attr in &field.attrs {
-        let name = get_attribute_name(&attr)?;
+        let name = get_attribute_name(attr)?;
         macro_rules! set_storage_type {
             ($name:expr => $var:ident) => {{
                 use_meta_path(&attr, $name)?;
@@ -71,6 +75,28 @@ fn parse_column(field: &Field, idx: usize) -> Result<Column> {
                     )?)?);
                 }
             }
+            "on_read" => {
+                if on_read.is_some() {
+                    syn_error!(attr.span(), "Duplicate attribute");
+                } else {
+                    on_read = Some(path_from_expr(get_name_value(
+                        attr,
+                        "on_read",
+                        "\"{function}\"",
+                    )?)?);
+                }
+            }
+            "on_write" => {
+                if on_write.is_some() {
+                    syn_error!(attr.span(), "Duplicate attribute");
+                } else {
+                    on_write = Some(path_from_expr(get_name_value(
+                        attr,
+                        "on_write",
+                        "\"{function}\"",
+                    )?)?);
+                }
+            }
             _ => syn_error!(attr.span(), "Unknown attribute"),
         }
     }
@@ -81,6 +107,8 @@ fn parse_column(field: &Field, idx: usize) -> Result<Column> {
             .unwrap_or(snake_case_to_upper_camel(field_name.to_string().as_str())),
         storage_type: storage_type.unwrap_or(ColumnStorageType::Rowed),
         optional,
+        on_read,
+        on_write,
         ty: field.ty.clone(),
         vis: field.vis.clone(),
         variable_ident: format_ident!("__v{idx}"),
@@ -100,9 +128,8 @@ pub fn parse_columns(struct_input: &DataStruct) -> Result<Columns> {
     let mut has_row = false;
     let mut has_optional_row = false;
     let mut columns = Vec::new();
-    let mut idx = 0;
-    for field in &struct_input.fields {
-        let column = parse_column(&field, idx)?;
+    for (idx, field) in struct_input.fields.iter().enumerate() {
+        let column = parse_column(field, idx)?;
         match column.storage_type {
             ColumnStorageType::Constant => {
                 has_constant = true;
@@ -115,7 +142,6 @@ pub fn parse_columns(struct_input: &DataStruct) -> Result<Columns> {
             }
         }
         columns.push(column);
-        idx += 1;
     }
     Ok(Columns {
         has_constant,
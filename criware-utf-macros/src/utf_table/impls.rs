@@ -12,19 +12,28 @@ mod read {
         main_attr::StructInfo,
     };
 
+    /// Returns the suffix for the reader method to call, and the extra hook
+    /// argument to pass to it (if the column has an `on_read` hook)
+    fn hook(column: &Column) -> (&'static str, TokenStream) {
+        match &column.on_read {
+            Some(path) => ("_with_hook", quote! { , &#path }),
+            None => ("", TokenStream::new()),
+        }
+    }
+
     fn read_column(column: &Column) -> TokenStream {
         match column.storage_type {
             ColumnStorageType::Constant => {
                 let column_name = &column.column_name;
                 let var_ident = &column.variable_ident;
-                if column.optional.is_some() {
-                    quote! {
-                        let #var_ident = reader.read_constant_column_opt(#column_name)?;
-                    }
+                let (suffix, hook_arg) = hook(column);
+                let fn_ident = if column.optional.is_some() {
+                    format_ident!("read_constant_column_opt{suffix}")
                 } else {
-                    quote! {
-                        let #var_ident = reader.read_constant_column(#column_name)?;
-                    }
+                    format_ident!("read_constant_column{suffix}")
+                };
+                quote! {
+                    let #var_ident = reader.#fn_ident(#column_name #hook_arg)?;
                 }
             }
             ColumnStorageType::Rowed => {
@@ -74,78 +83,116 @@ mod read {
 
     fn read_row_value(column: &Column) -> TokenStream {
         let var_ident = &column.variable_ident;
+        let (suffix, hook_arg) = hook(column);
+        let fn_ident = format_ident!("read_value{suffix}");
         if column.optional.is_some() {
             let cond_ident = &column.condition_ident;
             quote! {
                 let #var_ident = if #cond_ident {
-                    Some(reader.read_value(true)?)
+                    ::std::option::Option::Some(reader.#fn_ident(true #hook_arg)?)
                 } else {
-                    None
+                    ::std::option::Option::None
                 };
             }
         } else {
             quote! {
-                let #var_ident = reader.read_value(true)?;
+                let #var_ident = reader.#fn_ident(true #hook_arg)?;
+            }
+        }
+    }
+
+    /// Returns a loop that reads every remaining row, and pushes it to `rows`
+    fn row_loop(struct_info: &StructInfo, columns: &Columns, rows: TokenStream) -> TokenStream {
+        let row_ident = &struct_info.row_ident;
+        let field_idents = columns
+            .columns
+            .iter()
+            .filter(|c| c.storage_type == ColumnStorageType::Rowed)
+            .map(field_init);
+        let decls = columns
+            .columns
+            .iter()
+            .filter(|c| c.storage_type == ColumnStorageType::Rowed)
+            .map(read_row_value);
+        quote! {
+            while reader.more_row_data() {
+                #(#decls)*
+                #rows.push(#row_ident { #(#field_idents),* });
             }
         }
     }
 
     fn read_rows(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
         if columns.has_row {
-            let row_ident = &struct_info.row_ident;
-            let field_idents = columns
-                .columns
-                .iter()
-                .filter(|c| c.storage_type == ColumnStorageType::Rowed)
-                .map(field_init);
-            let decls = columns
-                .columns
-                .iter()
-                .filter(|c| c.storage_type == ColumnStorageType::Rowed)
-                .map(read_row_value);
+            let row_loop = row_loop(struct_info, columns, quote! { rows });
             quote! {
                 let mut rows = ::std::vec::Vec::new();
-                while reader.more_row_data() {
-                    #(#decls)*
-                    rows.push(#row_ident { #(#field_idents),* });
-                }
+                #row_loop
             }
         } else {
             TokenStream::new()
         }
     }
 
-    fn context(columns: &Columns) -> TokenStream {
-        if columns.has_optional_row {
-            let context_additions = columns
-                .columns
-                .iter()
-                .filter(|column| {
-                    column.optional.is_some() && column.storage_type == ColumnStorageType::Rowed
-                })
-                .map(|column| {
-                    let column_name = &column.column_name;
-                    let cond_ident = &column.condition_ident;
-                    quote! {
-                        write_context.set_inclusion_state(#column_name, #cond_ident);
-                    }
-                });
-            quote! {
-                let mut write_context = ::criware_utf::WriteContext::new();
-                #(#context_additions)*
-            }
+    /// Returns the code for reading into an existing table (`table`), so
+    /// everything that was read before an error is kept
+    fn salvage_body(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
+        let column_code = read_columns(struct_info, columns);
+        let context_code = context(struct_info, columns);
+        let mut assignments = Vec::new();
+        if columns.has_constant {
+            assignments.push(quote! { table.constants = constants; });
+        }
+        if struct_info.write_context {
+            assignments.push(quote! { table.write_context = write_context; });
+        }
+        let row_code = if columns.has_row {
+            row_loop(struct_info, columns, quote! { table.rows })
         } else {
             TokenStream::new()
+        };
+        quote! {
+            #column_code
+            #context_code
+            #(#assignments)*
+            #row_code
+        }
+    }
+
+    fn context(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
+        if !struct_info.write_context {
+            return TokenStream::new();
+        }
+        let context_additions = columns
+            .columns
+            .iter()
+            .filter(|column| {
+                column.optional.is_some() && column.storage_type == ColumnStorageType::Rowed
+            })
+            .map(|column| {
+                let column_name = &column.column_name;
+                let cond_ident = &column.condition_ident;
+                quote! {
+                    write_context.set_inclusion_state(#column_name, #cond_ident);
+                }
+            });
+        quote! {
+            #[allow(unused_mut)]
+            let mut write_context = ::criware_utf::RoundTripContext::read(&mut reader)?;
+            #(#context_additions)*
         }
     }
 
     pub fn fn_read(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
         let table_ident = &struct_info.table_ident;
         let table_name = &struct_info.table_name;
+        let endianness = &struct_info.endianness;
+        let encoding = &struct_info.encoding;
         let field_count = columns.columns.len() as u16;
         let column_code = read_columns(struct_info, columns);
         let row_code = read_rows(struct_info, columns);
-        let context_code = context(&columns);
+        let context_code = context(struct_info, columns);
+        let salvage_code = salvage_body(struct_info, columns);
         let mut components = Vec::new();
         if columns.has_constant {
             components.push(format_ident!("constants"));
@@ -153,13 +200,31 @@ mod read {
         if columns.has_row {
             components.push(format_ident!("rows"));
         }
-        if columns.has_optional_row {
+        if struct_info.write_context {
             components.push(format_ident!("write_context"));
         }
         quote! {
+            fn reader_options() -> ::criware_utf::ReaderOptions {
+                ::criware_utf::ReaderOptions {
+                    endianness: ::criware_utf::Endianness::#endianness,
+                    encoding: ::criware_utf::Encoding::#encoding,
+                    ..::std::default::Default::default()
+                }
+            }
             fn read(reader: &mut dyn ::std::io::Read) -> ::std::result::Result<Self, ::criware_utf::Error> {
-                let mut reader = ::criware_utf::Reader::new(reader)?;
-                if reader.field_count() != #field_count || reader.table_name() != #table_name {
+                Self::read_from_reader(::criware_utf::Reader::new_with_options(
+                    reader,
+                    Self::reader_options(),
+                )?)
+            }
+            fn read_from_slice(data: &[u8]) -> ::std::result::Result<Self, ::criware_utf::Error> {
+                Self::read_from_reader(::criware_utf::Reader::from_bytes_with_options(
+                    data,
+                    Self::reader_options(),
+                )?)
+            }
+            fn read_from_reader(mut reader: ::criware_utf::Reader<'_>) -> ::std::result::Result<Self, ::criware_utf::Error> {
+                if reader.field_count() != #field_count || !reader.table_name_matches(#table_name) {
                     return ::std::result::Result::Err(::criware_utf::Error::WrongTableSchema);
                 }
                 #column_code
@@ -167,6 +232,18 @@ mod read {
                 #row_code
                 ::std::result::Result::Ok(#table_ident { #(#components),* })
             }
+            fn read_salvage(mut reader: ::criware_utf::Reader<'_>) -> (Self, ::std::option::Option<::criware_utf::Error>) {
+                let mut table = <Self as ::criware_utf::Table>::new();
+                if reader.field_count() != #field_count || !reader.table_name_matches(#table_name) {
+                    return (table, ::std::option::Option::Some(::criware_utf::Error::WrongTableSchema));
+                }
+                let mut read = || -> ::std::result::Result<(), ::criware_utf::Error> {
+                    #salvage_code
+                    ::std::result::Result::Ok(())
+                };
+                let error = read().err();
+                (table, error)
+            }
         }
     }
 }
@@ -229,29 +306,28 @@ mod new {
                 TokenStream::new()
             }
         };
-        let write_context = {
-            if columns.has_optional_row {
-                components.push(Ident::new("write_context", Span::call_site()));
-                let inclusions = columns
-                    .columns
-                    .iter()
-                    .filter(|column| {
-                        column.storage_type == ColumnStorageType::Rowed && column.optional.is_some()
-                    })
-                    .map(|column| {
-                        let column_name = &column.column_name;
-                        let included = column.optional.unwrap();
-                        quote! {
-                            write_context.set_inclusion_state(#column_name, #included);
-                        }
-                    });
-                quote! {
-                    let mut write_context = ::criware_utf::WriteContext::new();
-                    #(#inclusions)*
-                }
-            } else {
-                TokenStream::new()
+        let write_context = if struct_info.write_context {
+            components.push(Ident::new("write_context", Span::call_site()));
+            let inclusions = columns
+                .columns
+                .iter()
+                .filter(|column| {
+                    column.storage_type == ColumnStorageType::Rowed && column.optional.is_some()
+                })
+                .map(|column| {
+                    let column_name = &column.column_name;
+                    let included = column.optional.unwrap();
+                    quote! {
+                        write_context.set_inclusion_state(#column_name, #included);
+                    }
+                });
+            quote! {
+                #[allow(unused_mut)]
+                let mut write_context = ::criware_utf::RoundTripContext::new();
+                #(#inclusions)*
             }
+        } else {
+            TokenStream::new()
         };
         quote! {
             fn new() -> Self {
@@ -265,29 +341,35 @@ mod new {
 }
 
 mod write {
-    use proc_macro2::{Span, TokenStream};
-    use quote::quote;
-    use syn::Ident;
+    use proc_macro2::TokenStream;
+    use quote::{format_ident, quote};
 
     use crate::utf_table::{
         field_attr::{Column, ColumnStorageType, Columns},
         main_attr::StructInfo,
     };
 
+    /// Returns the suffix for the writer method to call, and the extra hook
+    /// argument to pass to it (if the column has an `on_write` hook)
+    fn hook(column: &Column) -> (&'static str, TokenStream) {
+        match &column.on_write {
+            Some(path) => ("_with_hook", quote! { , &#path }),
+            None => ("", TokenStream::new()),
+        }
+    }
+
     fn push_column(column: &Column) -> TokenStream {
         let column_name = &column.column_name;
         let field_ident = &column.field_ident;
         if column.storage_type == ColumnStorageType::Constant {
-            let fn_ident = Ident::new(
-                if column.optional.is_some() {
-                    "push_constant_column_opt"
-                } else {
-                    "push_constant_column"
-                },
-                Span::call_site(),
-            );
+            let (suffix, hook_arg) = hook(column);
+            let fn_ident = if column.optional.is_some() {
+                format_ident!("push_constant_column_opt{suffix}")
+            } else {
+                format_ident!("push_constant_column{suffix}")
+            };
             quote! {
-                table_writer.#fn_ident(#column_name, &self.constants.#field_ident)?;
+                table_writer.#fn_ident(#column_name, &self.constants.#field_ident #hook_arg)?;
             }
         } else {
             let ty = &column.ty;
@@ -311,6 +393,8 @@ mod write {
 
     fn write_row_value(column: &Column) -> TokenStream {
         let field_ident = &column.field_ident;
+        let (suffix, hook_arg) = hook(column);
+        let fn_ident = format_ident!("write_value{suffix}");
         if column.optional.is_some() {
             let cond_ident = &column.condition_ident;
             let name = &column.column_name;
@@ -318,12 +402,12 @@ mod write {
                 if #cond_ident != row.#field_ident.is_some() {
                     return ::std::result::Result::Err(::criware_utf::Error::OptionalColumnConflict(#name));
                 } else if #cond_ident {
-                    table_writer.write_value(true, row.#field_ident.as_ref().unwrap())?;
+                    table_writer.#fn_ident(true, row.#field_ident.as_ref().unwrap() #hook_arg)?;
                 }
             }
         } else {
             quote! {
-                table_writer.write_value(true, &row.#field_ident)?;
+                table_writer.#fn_ident(true, &row.#field_ident #hook_arg)?;
             }
         }
     }
@@ -335,7 +419,9 @@ mod write {
                 .iter()
                 .filter(|column| column.storage_type == ColumnStorageType::Rowed)
                 .map(write_row_value);
+            let row_size = row_size(columns);
             quote! {
+                table_writer.reserve(self.rows.len(), #row_size, 0, 0);
                 for row in &self.rows {
                     #(#values)*
                 }
@@ -345,46 +431,135 @@ mod write {
         }
     }
 
+    /// Returns the size of a row, as a `usize` expression (only valid after
+    /// every column is pushed)
+    fn row_size(columns: &Columns) -> TokenStream {
+        let utf_sizes = columns
+            .columns
+            .iter()
+            .filter(|column| column.storage_type == ColumnStorageType::Rowed)
+            .map(|column| {
+                let ty = &column.ty;
+                if column.optional.is_some() {
+                    let cond_ident = &column.condition_ident;
+                    quote! {
+                        if #cond_ident {::criware_utf::utf_size_of::<#ty>()} else {0}
+                    }
+                } else {
+                    quote! {
+                        ::criware_utf::utf_size_of::<#ty>()
+                    }
+                }
+            });
+        quote! {
+            (#(#utf_sizes)+*)
+        }
+    }
+
     fn end(columns: &Columns) -> TokenStream {
         if columns.has_row {
-            let utf_sizes = columns
-                .columns
-                .iter()
-                .filter(|column| column.storage_type == ColumnStorageType::Rowed)
-                .map(|column| {
-                    let ty = &column.ty;
-                    if column.optional.is_some() {
-                        let cond_ident = &column.condition_ident;
-                        quote! {
-                            if #cond_ident {::criware_utf::utf_size_of::<#ty>()} else {0}
-                        }
-                    } else {
-                        quote! {
-                            ::criware_utf::utf_size_of::<#ty>()
-                        }
-                    }
-                });
+            let row_size = row_size(columns);
             quote! {
-                table_writer.end(writer, (#(#utf_sizes)+*) as u16, self.rows.len() as u32)
+                let row_size = u16::try_from(#row_size)
+                    .map_err(|_| ::criware_utf::Error::TableTooLarge("row size"))?;
+                let row_count = u32::try_from(self.rows.len())
+                    .map_err(|_| ::criware_utf::Error::TableTooLarge("row count"))?;
+                table_writer.end(writer, row_size, row_count)?;
+                ::std::result::Result::Ok(())
             }
         } else {
             quote! {
-                table_writer.end(writer, 0, 0)
+                table_writer.end(writer, 0, 0)?;
+                ::std::result::Result::Ok(())
             }
         }
     }
 
     pub fn fn_write(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
         let table_name = &struct_info.table_name;
-        let column_code = columns.columns.iter().map(push_column);
+        let endianness = &struct_info.endianness;
+        let encoding = &struct_info.encoding;
+        let column_code: Vec<_> = columns.columns.iter().map(push_column).collect();
         let row_code = write_rows(columns);
         let end_code = end(columns);
+        let body = quote! {
+            let mut table_writer = ::criware_utf::Writer::with_options(#table_name, options);
+            #(#column_code)*
+            #row_code
+            #end_code
+        };
+        let apply_context = if struct_info.write_context {
+            quote! { let options = self.write_context.apply(options); }
+        } else {
+            TokenStream::new()
+        };
         quote! {
+            fn writer_options() -> ::criware_utf::WriterOptions {
+                ::criware_utf::WriterOptions {
+                    endianness: ::criware_utf::Endianness::#endianness,
+                    encoding: ::criware_utf::Encoding::#encoding,
+                    ..::std::default::Default::default()
+                }
+            }
             fn write(&self, writer: &mut dyn ::std::io::Write) -> ::std::result::Result<(), ::criware_utf::Error> {
-                let mut table_writer = ::criware_utf::Writer::new(#table_name);
-                #(#column_code)*
-                #row_code
-                #end_code
+                self.write_with_options(writer, Self::writer_options())
+            }
+            fn write_with_options(
+                &self,
+                writer: &mut dyn ::std::io::Write,
+                options: ::criware_utf::WriterOptions,
+            ) -> ::std::result::Result<(), ::criware_utf::Error> {
+                #apply_context
+                #body
+            }
+            fn write_deterministic(
+                &self,
+                writer: &mut dyn ::std::io::Write,
+            ) -> ::std::result::Result<(), ::criware_utf::Error> {
+                let options = Self::writer_options();
+                #body
+            }
+        }
+    }
+}
+
+mod schema {
+    use proc_macro2::TokenStream;
+    use quote::quote;
+
+    use crate::utf_table::{
+        field_attr::{Column, ColumnStorageType, Columns},
+        main_attr::StructInfo,
+    };
+
+    fn table_column(column: &Column) -> TokenStream {
+        let name = &column.column_name;
+        let ty = &column.ty;
+        let storage_format = match column.storage_type {
+            ColumnStorageType::Constant => quote! { Constant },
+            ColumnStorageType::Rowed => quote! { Rowed },
+        };
+        let optional = column.optional.is_some();
+        quote! {
+            ::criware_utf::TableColumn {
+                name: #name,
+                storage_format: ::criware_utf::ColumnStorageFormat::#storage_format,
+                value_kind: ::criware_utf::utf_kind_of::<#ty>(),
+                optional: #optional,
+            }
+        }
+    }
+
+    pub fn fn_table_schema(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
+        let table_name = &struct_info.table_name;
+        let columns = columns.columns.iter().map(table_column);
+        quote! {
+            fn table_schema() -> ::criware_utf::TableSchema {
+                const COLUMNS: &[::criware_utf::TableColumn] = &[#(#columns),*];
+                ::criware_utf::TableSchema {
+                    table_name: #table_name,
+                    columns: COLUMNS,
+                }
             }
         }
     }
@@ -395,11 +570,23 @@ pub fn impl_table(struct_info: &StructInfo, columns: &Columns) -> TokenStream {
     let new_fn = new::fn_new(struct_info, columns);
     let read_fn = read::fn_read(struct_info, columns);
     let write_fn = write::fn_write(struct_info, columns);
+    let schema_fn = schema::fn_table_schema(struct_info, columns);
+    let prefixed_impl = struct_info.prefix.map(|prefix| {
+        quote! {
+            impl ::criware_utf::PrefixedTable for #ident {
+                const PREFIX: [u8; 4] = [#(#prefix),*];
+            }
+        }
+    });
     quote! {
         impl ::criware_utf::Table for #ident {
             #new_fn
             #read_fn
             #write_fn
         }
+        impl ::criware_utf::DescribedTable for #ident {
+            #schema_fn
+        }
+        #prefixed_impl
     }
 }
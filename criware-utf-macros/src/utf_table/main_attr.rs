@@ -1,19 +1,52 @@
 use proc_macro2::TokenStream;
 use quote::format_ident;
 use syn::{
-    DataStruct, DeriveInput, Ident, MetaNameValue, Token, Visibility, parse::Parse,
+    DataStruct, DeriveInput, Expr, Ident, MetaNameValue, Token, Visibility, parse::Parse,
     punctuated::Punctuated, spanned::Spanned,
 };
 
 use crate::{
     Result,
-    utils::{ident_from_expr, string_from_expr},
+    utils::{bool_from_expr, ident_from_expr, string_from_expr},
 };
 
 pub struct TableParams {
     pub constants_ident: Option<Ident>,
     pub rows_ident: Option<Ident>,
     pub table_name: Option<String>,
+    pub endianness: Option<Ident>,
+    pub encoding: Option<Ident>,
+    pub prefix: Option<[u8; 4]>,
+    pub round_trip: Option<bool>,
+}
+
+fn endianness_from_expr(expr: &Expr) -> Result<Ident> {
+    match string_from_expr(expr)?.as_str() {
+        "big" => Ok(format_ident!("Big")),
+        "little" => Ok(format_ident!("Little")),
+        _ => syn_error!(
+            expr.span(),
+            "Unknown endianness. Expected \"big\" or \"little\""
+        ),
+    }
+}
+
+fn encoding_from_expr(expr: &Expr) -> Result<Ident> {
+    match string_from_expr(expr)?.as_str() {
+        "utf8" => Ok(format_ident!("Utf8")),
+        "shift_jis" => Ok(format_ident!("ShiftJis")),
+        _ => syn_error!(
+            expr.span(),
+            "Unknown encoding. Expected \"utf8\" or \"shift_jis\""
+        ),
+    }
+}
+
+fn prefix_from_expr(expr: &Expr) -> Result<[u8; 4]> {
+    match string_from_expr(expr)?.as_bytes().try_into() {
+        Ok(prefix) => Ok(prefix),
+        Err(_) => syn_error!(expr.span(), "Expected a 4-byte prefix"),
+    }
 }
 
 impl Parse for TableParams {
@@ -21,7 +54,11 @@ impl Parse for TableParams {
         let mut constants_ident = None;
         let mut rows_ident = None;
         let mut table_name = None;
-        for meta in Punctuated::<MetaNameValue, Token![,]>::parse_terminated(&input)? {
+        let mut endianness = None;
+        let mut encoding = None;
+        let mut prefix = None;
+        let mut round_trip = None;
+        for meta in Punctuated::<MetaNameValue, Token![,]>::parse_terminated(input)? {
             let name = match meta.path.get_ident() {
                 Some(ident) => ident.to_string(),
                 None => syn_error!(meta.path.span(), "Unknown parameter"),
@@ -38,6 +75,10 @@ impl Parse for TableParams {
                 "constants" => branch!(ident_from_expr => constants_ident),
                 "row" => branch!(ident_from_expr => rows_ident),
                 "table_name" => branch!(string_from_expr => table_name),
+                "endian" => branch!(endianness_from_expr => endianness),
+                "encoding" => branch!(encoding_from_expr => encoding),
+                "prefix" => branch!(prefix_from_expr => prefix),
+                "round_trip" => branch!(bool_from_expr => round_trip),
                 _ => syn_error!(meta.path.span(), "Unknown parameter"),
             }
         }
@@ -45,6 +86,10 @@ impl Parse for TableParams {
             constants_ident,
             rows_ident,
             table_name,
+            endianness,
+            encoding,
+            prefix,
+            round_trip,
         })
     }
 }
@@ -54,6 +99,11 @@ pub struct StructInfo {
     pub table_name: String,
     pub constants_ident: Ident,
     pub row_ident: Ident,
+    pub endianness: Ident,
+    pub encoding: Ident,
+    pub prefix: Option<[u8; 4]>,
+    /// Whether the table struct gets a `write_context` field
+    pub write_context: bool,
     pub data: DataStruct,
     pub vis: Visibility,
 }
@@ -76,11 +126,17 @@ pub fn parse_struct_info(attr: TokenStream, item: TokenStream) -> Result<StructI
         .rows_ident
         .unwrap_or(format_ident!("{}Row", derive_input.ident));
     let table_name = params.table_name.unwrap_or(derive_input.ident.to_string());
+    let endianness = params.endianness.unwrap_or(format_ident!("Big"));
+    let encoding = params.encoding.unwrap_or(format_ident!("Utf8"));
     Ok(StructInfo {
         table_ident: derive_input.ident.clone(),
         table_name,
         constants_ident,
         row_ident,
+        endianness,
+        encoding,
+        prefix: params.prefix,
+        write_context: params.round_trip.unwrap_or(false),
         data,
         vis: derive_input.vis,
     })
@@ -65,9 +65,9 @@ pub fn generate_structs(struct_info: &StructInfo, columns: &Columns) -> TokenStr
             rows: ::std::vec::Vec<#ident>
         });
     }
-    if columns.has_optional_row {
+    if struct_info.write_context {
         components.push(quote! {
-            write_context: ::criware_utf::WriteContext
+            write_context: ::criware_utf::RoundTripContext
         });
     }
     let core_ident = &struct_info.table_ident;
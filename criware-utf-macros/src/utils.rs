@@ -1,4 +1,4 @@
-use syn::{Expr, Ident, Lit, Meta, spanned::Spanned};
+use syn::{Expr, Ident, Lit, Meta, Path, spanned::Spanned};
 
 use crate::Result;
 
@@ -26,13 +26,13 @@ pub fn get_name_value<'a>(
 
 pub fn ident_from_expr(expr: &Expr) -> Result<Ident> {
     if let Expr::Path(path) = expr {
-        if let Some(attr) = path.attrs.get(0) {
+        if let Some(attr) = path.attrs.first() {
             syn_error!(attr.span(), "Attributes are not allowed here")
         }
-        if let None = path.qself {
-            if let Some(ident) = path.path.get_ident() {
-                return Ok(ident.clone());
-            }
+        if path.qself.is_none()
+            && let Some(ident) = path.path.get_ident()
+        {
+            return Ok(ident.clone());
         }
     }
     syn_error!(expr.span(), "Expected a struct value")
@@ -40,7 +40,7 @@ pub fn ident_from_expr(expr: &Expr) -> Result<Ident> {
 
 pub fn string_from_expr(expr: &Expr) -> Result<String> {
     if let Expr::Lit(expr_lit) = expr {
-        if let Some(attr) = expr_lit.attrs.get(0) {
+        if let Some(attr) = expr_lit.attrs.first() {
             syn_error!(attr.span(), "Attributes are not allowed here")
         }
         if let Lit::Str(str) = &expr_lit.lit {
@@ -50,6 +50,26 @@ pub fn string_from_expr(expr: &Expr) -> Result<String> {
     syn_error!(expr.span(), "Expected a string")
 }
 
+pub fn bool_from_expr(expr: &Expr) -> Result<bool> {
+    if let Expr::Lit(expr_lit) = expr {
+        if let Some(attr) = expr_lit.attrs.first() {
+            syn_error!(attr.span(), "Attributes are not allowed here")
+        }
+        if let Lit::Bool(bool) = &expr_lit.lit {
+            return Ok(bool.value);
+        }
+    }
+    syn_error!(expr.span(), "Expected a boolean")
+}
+
+pub fn path_from_expr(expr: &Expr) -> Result<Path> {
+    let string = string_from_expr(expr)?;
+    match syn::parse_str::<Path>(&string) {
+        Ok(path) => Ok(path),
+        Err(_) => syn_error!(expr.span(), "Expected a path to a function"),
+    }
+}
+
 pub fn snake_case_to_upper_camel(snake_case: &str) -> String {
     let mut result = String::with_capacity(snake_case.len());
     for word in snake_case.split('_') {
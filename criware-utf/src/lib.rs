@@ -7,6 +7,7 @@ struct definition (see the [`Table`] trait for more info on what is provided,
 and the macro itself for info on how to write the schema).
 
 ```
+# use criware_utf::utf_table;
 #[utf_table]
 struct Table {
     #[column_name = "ColumnName"]
@@ -36,8 +37,8 @@ specialized for the [`utf_table`] macro, so using them is
 # Examples
 
 This section demonstrates important features this crate provides. Each example
-can be dropped in to a project and compile (and run if you had the necessary
-table files).
+can be dropped in to a project and compile. They read and write tables in
+memory, but any stream (like a [`File`](std::fs::File)) works the same way.
 
 All of these examples demonstrate high-level functionality. For more precise
 examples and explanations, consult the page for the relevant type/macro.
@@ -56,23 +57,27 @@ struct ImportantTable {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut orig = std::fs::File::open("important-table.bin")?;
-    let mut new = std::fs::File::create("more-important-table.bin")?;
-    let mut table = ImportantTable::read(&mut orig)?;
+    # let mut orig = ImportantTable::new();
+    # orig.constants.comment = "first".to_owned();
+    # orig.rows.push(ImportantTableRow { id: 1, file_name: "a.bin".to_owned() });
+    # let data = orig.write_to_vec()?;
+    let mut new = Vec::new();
+    let mut table = ImportantTable::read(&mut data.as_slice())?;
     for row in &table.rows {
         println!("\"{}\" (id {})", row.file_name, row.id);
     }
     table.constants.comment = format!("\"{}\" -loser", table.constants.comment);
     table.write(&mut new)?;
+    # assert_eq!(ImportantTable::read(&mut new.as_slice())?.constants.comment, "\"first\" -loser");
     Ok(())
 }
 ```
 
 ## Example: Reading one of many schemas
 ```
-use std::io::{Seek, SeekFrom};
+use std::io::{Cursor, Seek, SeekFrom};
 
-use criware_utf::{Schema, Table, utf_table};
+use criware_utf::{Schema, Table, select_schema, utf_table};
 
 #[utf_table(table_name = "CoolTable")]
 struct CoolTableV1 {
@@ -94,17 +99,14 @@ enum CoolTable {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut file = std::fs::File::open("table.bin")?;
+    let mut file = Cursor::new(CoolTableV2::new().write_to_vec()?);
     let schema = Schema::read(&mut file)?;
-    let table = {
-        file.seek(SeekFrom::Start(0))?;
-        if schema.has_column("Crc32") {
-            CoolTable::V2(CoolTableV2::read(&mut file)?)
-        } else {
-            CoolTable::V1(CoolTableV1::read(&mut file)?)
-        }
-    };
-    // ... do something ...
+    file.seek(SeekFrom::Start(0))?;
+    let table = select_schema!(&schema, &mut file, {
+        CoolTableV1 => CoolTable::V1,
+        CoolTableV2 => CoolTable::V2,
+    })?;
+    assert!(matches!(table, CoolTable::V2(_)));
     Ok(())
 }
 ```
@@ -116,4 +118,4 @@ pub use criware_utf_core::*;
 #[allow(unused_imports)]
 extern crate criware_utf_macros;
 
-pub use criware_utf_macros::utf_table;
+pub use criware_utf_macros::{UtfEnum, UtfNewtype, utf_table};